@@ -0,0 +1,301 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![allow(clippy::large_enum_variant)]
+
+//! # Horse Race Factory
+//!
+//! Deploys `HorseRace` contracts from an uploaded code hash, one per event or
+//! per asset, each with its own config and operator.
+//!
+//! ## Features
+//! - Instantiates races through `HorseRace::with_config`
+//! - Registry of deployed races by ID and by operator
+//! - Code hash can be swapped for new deployments
+
+#[ink::contract]
+mod horse_race_factory {
+    use horse_race::{Config, HorseRaceRef};
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    // ============================================================================
+    // ERROR TYPES
+    // ============================================================================
+
+    /// Factory errors
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// Only owner can perform this action
+        NotOwner,
+        /// No deployed race with that ID
+        InstanceNotFound,
+        /// Race constructor rejected the config
+        Race(horse_race::Error),
+        /// Instantiation or a call to a deployed race failed
+        CallFailed,
+    }
+
+    /// Result type for factory operations
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    // ============================================================================
+    // DATA STRUCTURES
+    // ============================================================================
+
+    /// Race contract deployed by the factory
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RaceInstance {
+        pub id: u32,
+        pub contract: AccountId,
+        pub operator: AccountId,       // Owner of the race contract
+        pub label: Vec<u8>,            // Event or asset the race is for
+        pub code_hash: Hash,           // Code the race was deployed from
+        pub created_at: u64,
+    }
+
+    // ============================================================================
+    // EVENTS
+    // ============================================================================
+
+    #[ink(event)]
+    pub struct RaceDeployed {
+        #[ink(topic)]
+        instance_id: u32,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        label: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct OperatorChanged {
+        #[ink(topic)]
+        instance_id: u32,
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    // ============================================================================
+    // CONTRACT STORAGE
+    // ============================================================================
+
+    #[ink(storage)]
+    pub struct HorseRaceFactory {
+        /// Factory owner
+        owner: AccountId,
+
+        /// Code hash new races are deployed from
+        race_code_hash: Hash,
+
+        /// Deployed races by instance ID
+        instances: Mapping<u32, RaceInstance>,
+
+        /// Number of deployed races
+        instance_count: u32,
+
+        /// Instance IDs per operator, oldest first
+        operator_instances: Mapping<AccountId, Vec<u32>>,
+    }
+
+    // ============================================================================
+    // IMPLEMENTATION
+    // ============================================================================
+
+    impl HorseRaceFactory {
+        /// Initialize the factory with the code hash of an uploaded HorseRace
+        #[ink(constructor)]
+        pub fn new(race_code_hash: Hash) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                race_code_hash,
+                instances: Mapping::default(),
+                instance_count: 0,
+                operator_instances: Mapping::default(),
+            }
+        }
+
+        /// Deploy a race with `config`, owned by `operator` (owner only)
+        /// Returns the new contract's address
+        #[ink(message)]
+        pub fn deploy_race(&mut self, operator: AccountId, label: Vec<u8>, config: Config) -> Result<AccountId> {
+            use ink::ToAccountId;
+
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // The instance ID salts the address, so identical configs still deploy
+            let instance_id = self.instance_count;
+            let race = match HorseRaceRef::with_config(operator, config)
+                .code_hash(self.race_code_hash)
+                .endowment(0)
+                .salt_bytes(instance_id.to_le_bytes())
+                .try_instantiate()
+            {
+                Ok(Ok(Ok(race))) => race,
+                Ok(Ok(Err(error))) => return Err(Error::Race(error)),
+                _ => return Err(Error::CallFailed),
+            };
+            let contract = race.to_account_id();
+
+            self.instances.insert(instance_id, &RaceInstance {
+                id: instance_id,
+                contract,
+                operator,
+                label: label.clone(),
+                code_hash: self.race_code_hash,
+                created_at: self.env().block_timestamp(),
+            });
+            self.instance_count += 1;
+            self.index_operator(operator, instance_id);
+
+            self.env().emit_event(RaceDeployed {
+                instance_id,
+                contract,
+                operator,
+                label,
+            });
+
+            Ok(contract)
+        }
+
+        /// Re-read a race's owner and record it as the operator
+        /// Anyone can call this after an operator hands their race on
+        #[ink(message)]
+        pub fn sync_operator(&mut self, instance_id: u32) -> Result<AccountId> {
+            use ink::codegen::TraitCallBuilder;
+            use ink::env::call::FromAccountId;
+
+            let mut instance = self.instances.get(instance_id).ok_or(Error::InstanceNotFound)?;
+            let race: HorseRaceRef = FromAccountId::from_account_id(instance.contract);
+            let operator = match race.call().get_owner().try_invoke() {
+                Ok(Ok(operator)) => operator,
+                _ => return Err(Error::CallFailed),
+            };
+            if operator == instance.operator {
+                return Ok(operator);
+            }
+
+            let from = instance.operator;
+            let mut previous = self.operator_instances.get(from).unwrap_or_default();
+            previous.retain(|&id| id != instance_id);
+            self.operator_instances.insert(from, &previous);
+            self.index_operator(operator, instance_id);
+            instance.operator = operator;
+            self.instances.insert(instance_id, &instance);
+
+            self.env().emit_event(OperatorChanged {
+                instance_id,
+                from,
+                to: operator,
+            });
+
+            Ok(operator)
+        }
+
+        /// Add an instance to an operator's list
+        fn index_operator(&mut self, operator: AccountId, instance_id: u32) {
+            let mut ids = self.operator_instances.get(operator).unwrap_or_default();
+            ids.push(instance_id);
+            self.operator_instances.insert(operator, &ids);
+        }
+
+        /// Get a deployed race
+        #[ink(message)]
+        pub fn get_instance(&self, instance_id: u32) -> Option<RaceInstance> {
+            self.instances.get(instance_id)
+        }
+
+        /// Get deployed races, oldest first
+        #[ink(message)]
+        pub fn get_instances(&self, offset: u32, limit: u32) -> Vec<RaceInstance> {
+            (offset..self.instance_count.min(offset.saturating_add(limit)))
+                .filter_map(|id| self.instances.get(id))
+                .collect()
+        }
+
+        /// Get the races an operator runs, oldest first
+        #[ink(message)]
+        pub fn get_instances_by_operator(&self, operator: AccountId) -> Vec<RaceInstance> {
+            self.operator_instances
+                .get(operator)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|id| self.instances.get(id))
+                .collect()
+        }
+
+        /// Get number of deployed races
+        #[ink(message)]
+        pub fn get_instance_count(&self) -> u32 {
+            self.instance_count
+        }
+
+        /// Set the code hash for future deployments (owner only)
+        /// Races already deployed keep their code
+        #[ink(message)]
+        pub fn set_race_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.race_code_hash = code_hash;
+            Ok(())
+        }
+
+        /// Get the code hash new races are deployed from
+        #[ink(message)]
+        pub fn get_race_code_hash(&self) -> Hash {
+            self.race_code_hash
+        }
+
+        /// Get factory owner
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Set factory owner
+        #[ink(message)]
+        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.owner = new_owner;
+            Ok(())
+        }
+    }
+
+    // ============================================================================
+    // UNIT TESTS
+    // ============================================================================
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn only_owner_deploys_and_sets_code() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut factory = HorseRaceFactory::new(Hash::from([1; 32]));
+            assert_eq!(factory.get_race_code_hash(), Hash::from([1; 32]));
+            assert_eq!(factory.get_instance_count(), 0);
+            assert!(factory.get_instances(0, 10).is_empty());
+            assert_eq!(factory.sync_operator(0), Err(Error::InstanceNotFound));
+
+            // Instantiation needs a chain, so only the guards run off-chain
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let config = horse_race::HorseRace::new().get_config();
+            assert_eq!(factory.deploy_race(accounts.bob, b"Derby".to_vec(), config), Err(Error::NotOwner));
+            assert_eq!(factory.set_race_code_hash(Hash::from([2; 32])), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            factory.set_race_code_hash(Hash::from([2; 32])).unwrap();
+            assert_eq!(factory.get_race_code_hash(), Hash::from([2; 32]));
+        }
+    }
+}
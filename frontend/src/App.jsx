@@ -44,13 +44,13 @@ function ContractTestPanel({ contract, api, selectedAccount, addLog }) {
 
   // Test queries list
   const queryTests = [
-    { name: 'getStatus', args: [], description: 'Get race status' },
-    { name: 'getRaceId', args: [], description: 'Get current race ID' },
+    { name: 'raceBook::getStatus', args: [], description: 'Get race status' },
+    { name: 'raceBook::getRaceId', args: [], description: 'Get current race ID' },
     { name: 'getOwner', args: [], description: 'Get contract owner' },
     { name: 'getHorses', args: [], description: 'Get all horses' },
     { name: 'getTotalPot', args: [], description: 'Get total betting pot' },
     { name: 'getBets', args: [], description: 'Get all bets' },
-    { name: 'getLatestResult', args: [], description: 'Get latest race result' },
+    { name: 'raceBook::getLatestResult', args: [], description: 'Get latest race result' },
     { name: 'getPayouts', args: [], description: 'Get payouts' },
     { name: 'getWinners', args: [], description: 'Get winners' },
     { name: 'calculateExactaProbability', args: [0, 1], description: 'Calculate exacta probability (0→1)' },
@@ -646,7 +646,7 @@ function App() {
 
     try {
       // Check current status first
-      const currentStatus = await queryContractValue('getStatus');
+      const currentStatus = await queryContractValue('raceBook::getStatus');
       const statusValue = currentStatus?.Ok || currentStatus;
       addLog(`Current contract status: ${JSON.stringify(statusValue)}`, 'info');
 
@@ -685,7 +685,7 @@ function App() {
       }
 
       // Verify status is now Betting
-      const newStatus = await queryContractValue('getStatus');
+      const newStatus = await queryContractValue('raceBook::getStatus');
       const newStatusValue = newStatus?.Ok || newStatus;
       if (newStatusValue !== 'Betting' && newStatusValue?.Betting === undefined) {
         addLog(`Contract status is: ${JSON.stringify(newStatusValue)} (not Betting)`, 'error');
@@ -733,7 +733,7 @@ function App() {
     
     try {
       // First check the contract status
-      const currentStatus = await queryContractValue('getStatus');
+      const currentStatus = await queryContractValue('raceBook::getStatus');
       const statusValue = currentStatus?.Ok || currentStatus;
       
       if (statusValue !== 'Betting' && statusValue?.Betting === undefined) {
@@ -762,7 +762,7 @@ function App() {
       addLog(`  Multiplier: x${multiplier}`, 'info');
       addLog(`  Potential Win: ${(betAmountTokens * multiplier).toFixed(2)} tokens`, 'reward');
       
-      await sendTxAndWait('placeExactaBet', [playerBet.first, playerBet.second], betAmountSmallest);
+      await sendTxAndWait('raceBook::placeExactaBet', [playerBet.first, playerBet.second], betAmountSmallest);
       addLog('✓ Bet placed successfully!', 'success');
       setPlayerBetPlaced(true);
       
@@ -883,7 +883,7 @@ function App() {
       addLog('Fetching race results...', 'info');
       
       // Method 1: getLatestResult
-      let result = await queryContractValue('getLatestResult');
+      let result = await queryContractValue('raceBook::getLatestResult');
       addLog(`Raw getLatestResult: ${JSON.stringify(result)?.slice(0, 300)}`, 'info');
       
       // Method 2: getWinners (returns tuple directly)
@@ -1036,7 +1036,7 @@ function App() {
     try {
       setLoading(prev => ({ ...prev, placeBet: true }));
       const value = new BN(betAmount || '0');
-      await sendTxAndWait('placeExactaBet', [parseInt(firstPick), parseInt(secondPick)], value);
+      await sendTxAndWait('raceBook::placeExactaBet', [parseInt(firstPick), parseInt(secondPick)], value);
       setResults(prev => ({ ...prev, placeBet: { success: 'Bet placed successfully!' } }));
     } catch (error) {
       setResults(prev => ({ ...prev, placeBet: { error: error.message } }));
@@ -1152,9 +1152,9 @@ function App() {
                 return;
               }
               addLog('═══ CONTRACT STATUS CHECK ═══', 'phase');
-              const status = await queryContractValue('getStatus');
+              const status = await queryContractValue('raceBook::getStatus');
               const owner = await queryContractValue('getOwner');
-              const raceId = await queryContractValue('getRaceId');
+              const raceId = await queryContractValue('raceBook::getRaceId');
               const pot = await queryContractValue('getTotalPot');
               const ownerAddr = owner?.Ok || owner;
               const isOwner = addressesEqual(ownerAddr, selectedAccount.address);
@@ -1425,10 +1425,10 @@ function App() {
         <div className="section">
           <h2>📊 Contract Status</h2>
           <div className="button-group">
-            <button className="secondary" onClick={() => queryContract('getStatus', 'getStatus')} disabled={loading.getStatus || !contract}>
+            <button className="secondary" onClick={() => queryContract('raceBook::getStatus', 'getStatus')} disabled={loading.getStatus || !contract}>
               Status
             </button>
-            <button className="secondary" onClick={() => queryContract('getRaceId', 'getRaceId')} disabled={loading.getRaceId || !contract}>
+            <button className="secondary" onClick={() => queryContract('raceBook::getRaceId', 'getRaceId')} disabled={loading.getRaceId || !contract}>
               Race ID
             </button>
             <button className="secondary" onClick={() => queryContract('getTotalPot', 'getTotalPot')} disabled={loading.getTotalPot || !contract}>
@@ -1457,7 +1457,7 @@ function App() {
         <div className="section">
           <h2>🏆 Race Results</h2>
           <div className="button-group">
-            <button className="secondary" onClick={() => queryContract('getLatestResult', 'getLatestResult')} disabled={!contract}>
+            <button className="secondary" onClick={() => queryContract('raceBook::getLatestResult', 'getLatestResult')} disabled={!contract}>
               Latest Result
             </button>
             <button className="secondary" onClick={() => queryContract('getWinners', 'getWinners')} disabled={!contract}>
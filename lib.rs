@@ -4,6 +4,10 @@
 #![allow(clippy::cast_possible_wrap)]
 #![allow(clippy::cast_sign_loss)]
 #![allow(clippy::needless_range_loop)]
+#![allow(clippy::large_enum_variant)]
+#![allow(clippy::new_without_default)]
+#![allow(clippy::should_implement_trait)]
+#![allow(clippy::type_complexity)]
 
 //! # Horse Race Betting Engine
 //! 
@@ -18,6 +22,7 @@
 
 use ink::prelude::vec::Vec;
 use ink::prelude::vec;
+use ink::prelude::{format, string::String};
 
 /// pallet_assets runtime calls
 pub mod assets;
@@ -34,12 +39,293 @@ const HORSE_STRENGTHS: [u64; NUM_HORSES] = [6, 5, 4, 3, 2, 1];
 /// Sum of all strengths (6+5+4+3+2+1 = 21)
 const TOTAL_STRENGTH: u64 = 21;
 
+/// Horse acceleration (1-10, 5 is neutral): helps in the warm-up phase
+const HORSE_ACCELERATION: [u64; NUM_HORSES] = [6, 4, 8, 5, 7, 3];
+
+/// Horse stamina (1-10, 5 is neutral): helps in the sprint phase
+const HORSE_STAMINA: [u64; NUM_HORSES] = [6, 8, 3, 5, 4, 8];
+
+/// Phase modifier per attribute point (scaled by PRECISION, 400 = 4%)
+const ATTRIBUTE_STEP: u64 = 400;
+
+/// Number of recent finishes kept in a horse's form
+const FORM_WINDOW: usize = 5;
+
+/// Strength drift per place beaten or missed vs expectation (scaled by PRECISION, 100 = 1%)
+const FORM_K_FACTOR: u64 = 100;
+
+/// Maximum drift of adjusted strength from base strength (scaled by PRECISION, 2000 = 20%)
+const MAX_FORM_DRIFT: u64 = 2000;
+
+/// Default betting window (14 minutes, ms)
+const DEFAULT_BETTING_DURATION: u64 = 14 * 60 * 1000;
+
+/// Default racing window (1 minute, ms)
+const DEFAULT_RACING_DURATION: u64 = 60 * 1000;
+
+/// House edge on daily double odds (scaled by PRECISION, 1500 = 15%)
+const DAILY_DOUBLE_EDGE: u64 = 1500;
+
+/// House edge on head-to-head odds (scaled by PRECISION, 500 = 5%)
+const MATCH_BET_EDGE: u64 = 500;
+
+/// Default window to claim winnings in claim mode (30 days, ms)
+const DEFAULT_CLAIM_PERIOD: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Default inactivity after which a balance is dormant (365 days, ms)
+const DEFAULT_DORMANCY_PERIOD: u64 = 365 * 24 * 60 * 60 * 1000;
+
+/// Default loyalty points per unit wagered (scaled by PRECISION, 10000 = 1 point per unit)
+const DEFAULT_POINTS_ACCRUAL_RATE: u64 = 10000;
+
+/// Default points needed for one unit of free-bet credit
+const DEFAULT_POINTS_PER_CREDIT: u128 = 100;
+
+/// Default turnover needed before free-bet credits can be released (scaled by PRECISION, 30000 = 3x)
+const DEFAULT_WAGERING_REQUIREMENT: u64 = 30000;
+
+/// Default share of a bet's current value paid on cash-out (scaled by PRECISION, 9000 = 90%)
+const DEFAULT_CASH_OUT_FACTOR: u64 = 9000;
+
+/// Default exchange commission on net winnings (scaled by PRECISION, 500 = 5%)
+const DEFAULT_EXCHANGE_COMMISSION: u64 = 500;
+
+/// Default most exacta bets a race can take
+const DEFAULT_MAX_BETS_PER_RACE: u32 = 1000;
+
+/// Default most exacta bets one account can place in a race
+const DEFAULT_MAX_BETS_PER_ACCOUNT: u32 = 50;
+
+/// Rolling windows for self-imposed limits (ms)
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const WEEK_MS: u64 = 7 * DAY_MS;
+
+/// Delay before a raised self-limit takes effect (ms)
+const LIMIT_INCREASE_DELAY: u64 = DAY_MS;
+
+/// Decimals amounts are normalized to before fiat and limit math
+const INTERNAL_DECIMALS: u8 = 12;
+
+/// Most decimals a betting asset may have
+const MAX_ASSET_DECIMALS: u8 = 18;
+
+/// One whole token at the internal precision, the unit prices are quoted for
+const ASSET_UNIT: u128 = 1_000_000_000_000;
+
+/// Default age after which a cached price is stale (1 hour, ms)
+const DEFAULT_MAX_PRICE_AGE: u64 = 60 * 60 * 1000;
+
+/// Number of races (legs) in a Pick 6
+const PICK6_LEGS: usize = 6;
+
+/// Championship points for each finishing position, horses that don't finish score nothing
+const SEASON_POINTS: [u32; NUM_HORSES] = [10, 6, 4, 3, 2, 1];
+
+/// Most bettors a season leaderboard ranks
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Share of the Pick 6 pool paid to 5-of-6 tickets (scaled by PRECISION, 1000 = 10%)
+const PICK6_CONSOLATION_SHARE: u64 = 1000;
+
+/// Race distance in units
+const RACE_DISTANCE: u64 = 1000;
+
+/// Number of race phases (warm-up, cruise, sprint)
+const NUM_PHASES: usize = 3;
+
+/// Distance covered in each phase
+const PHASE_DISTANCE: u64 = RACE_DISTANCE / NUM_PHASES as u64;
+
+/// Number of track surfaces (dirt, turf, synthetic)
+const NUM_SURFACES: usize = 3;
+
+/// Speed of each horse on each surface (scaled by PRECISION), indexed by Surface
+const HORSE_SURFACE_AFFINITY: [[u64; NUM_SURFACES]; NUM_HORSES] = [
+    [10000, 9500, 10000],
+    [9500, 10500, 10000],
+    [10500, 9000, 10000],
+    [9500, 11000, 9500],
+    [10000, 10000, 10500],
+    [11000, 9500, 10000],
+];
+
+/// Shortest and longest track a race can be run on (units)
+const MIN_TRACK_DISTANCE: u64 = 600;
+const MAX_TRACK_DISTANCE: u64 = 3000;
+
+/// Largest speed change the going of a track can apply (scaled by PRECISION, 2000 = 20%)
+const MAX_GOING_DEVIATION: u64 = 2000;
+
+/// Most tracks the registry holds
+const MAX_TRACKS: usize = 32;
+
+/// Milliseconds per second, finish and sectional times are in milliseconds
+const MS_PER_SECOND: u64 = 1000;
+
+/// Play-money balance the demo faucet tops an account up to (1,000 units at 12 decimals)
+const DEMO_FAUCET_AMOUNT: u128 = 1_000_000_000_000_000;
+
+/// House edge on in-running odds (scaled by PRECISION, 1500 = 15%)
+const IN_RUNNING_EDGE: u64 = 1500;
+
+/// Smallest gap between consecutive finishers (ms)
+const MIN_MARGIN_MS: u64 = 50;
+
+/// Gap added per step of random variation between finishers (ms)
+const MARGIN_STEP_MS: u64 = 75;
+
+/// Approximate time for a horse to cover one length at racing speed (ms)
+const MS_PER_LENGTH: u64 = 200;
+
+/// Chance that a horse does not finish (scaled by PRECISION, 150 = 1.5%)
+const DNF_CHANCE: u64 = 150;
+
+/// Minimum number of horses that always finish, so an exacta can be settled
+const MIN_FINISHERS: usize = 3;
+
+/// Salt mixed into the seed for the DNF draws, keeps them off the ranking stream
+const DNF_SEED_SALT: u64 = 0x5EED_D1F0;
+
+/// Most members a syndicate can have
+const MAX_SYNDICATE_MEMBERS: usize = 50;
+
+/// Default fee for opening a private race room, paid to the treasury
+const DEFAULT_ROOM_FEE: u128 = 1_000_000_000_000;
+
+/// Most accounts a private race room can invite
+const MAX_ROOM_MEMBERS: usize = 50;
+
+/// Most exacta bets a private race room can take
+const MAX_ROOM_BETS: usize = 200;
+
+/// Time a room creator has to reveal the seed once betting closes (ms)
+const ROOM_REVEAL_PERIOD: u64 = DAY_MS;
+
+/// Most accounts a tournament can take
+const MAX_TOURNAMENT_ENTRANTS: usize = 100;
+
+/// Share of a tournament's prize pool paid to 1st, 2nd and 3rd (scaled by PRECISION)
+const TOURNAMENT_PRIZE_SHARES: [u64; 3] = [5000, 3000, 2000];
+
+/// Most free-to-play predictions a race can take
+const MAX_PREDICTIONS_PER_RACE: usize = 1000;
+
+/// Most sponsors that can add to one race's pot
+const MAX_SPONSORS_PER_RACE: usize = 20;
+
+/// Most settlement listeners that can be registered
+const MAX_LISTENERS: usize = 5;
+
+/// Gas (ref time) each listener callback or trophy mint may use
+const CALLBACK_GAS_LIMIT: u64 = 5_000_000_000;
+
+/// Most colors a horse's silks can have, each an RGB triple
+const MAX_SILK_COLORS: usize = 4;
+
+/// Longest image or metadata URI a horse can have (bytes)
+const MAX_HORSE_URI_LEN: usize = 256;
+
+/// Semantic version of the contract code (major, minor, patch)
+const CONTRACT_VERSION: (u16, u16, u16) = (0, 1, 0);
+
+/// Storage layout version, bumped with every layout change
+const STORAGE_VERSION: u32 = 1;
+
+pub use self::horse_race::{
+    Config, Error, ExactaOdds, HorseRace, HorseRaceRef, PSP34Error, RaceInfo, RaceResult, RaceStatus, ReceiptId,
+    UnwonPotPolicy,
+};
+
+/// Betting engine interface for other contracts (aggregators, bots, frontends
+/// using contract refs)
+#[ink::trait_definition]
+pub trait RaceBook {
+    /// Place an exacta bet (predict 1st and 2nd place in order)
+    /// Returns the bet's id, the same for repeat bets topping up a position
+    #[ink(message, payable)]
+    fn place_exacta_bet(&mut self, first_pick: u8, second_pick: u8) -> core::result::Result<u32, Error>;
+
+    /// Place an exacta bet staked from `bettor`'s balance
+    /// Anyone other than the bettor must hold an allowance covering the stake
+    #[ink(message)]
+    fn place_exacta_bet_for(
+        &mut self,
+        bettor: ink::primitives::AccountId,
+        first_pick: u8,
+        second_pick: u8,
+        amount: u128,
+    ) -> core::result::Result<u32, Error>;
+
+    /// Move the caller's unclaimed winnings into their balance
+    #[ink(message)]
+    fn claim_payout(&mut self) -> core::result::Result<u128, Error>;
+
+    /// Get an account's withdrawable balance
+    #[ink(message)]
+    fn get_balance(&self, account: ink::primitives::AccountId) -> u128;
+
+    /// Get the odds of an offered exacta as multiplier, decimal odds,
+    /// implied and model probability
+    #[ink(message)]
+    fn get_exacta_odds(&self, first: u8, second: u8) -> Option<ExactaOdds>;
+
+    /// Get current race status
+    #[ink(message)]
+    fn get_status(&self) -> RaceStatus;
+
+    /// Get current race ID
+    #[ink(message)]
+    fn get_race_id(&self) -> u64;
+
+    /// Get latest race result
+    #[ink(message)]
+    fn get_latest_result(&self) -> RaceResult;
+}
+
+/// Callbacks a listener contract receives after each settlement
+#[ink::trait_definition]
+pub trait RaceListener {
+    /// A race was settled with these finishers, in order
+    #[ink(message)]
+    fn on_race_finished(&mut self, race_id: u64, rankings: Vec<u8>);
+
+    /// An exacta winner was paid
+    #[ink(message)]
+    fn on_payout(&mut self, bettor: ink::primitives::AccountId, amount: u128);
+}
+
+/// Price feed quoting the betting asset in a fiat reference
+#[ink::trait_definition]
+pub trait PriceFeed {
+    /// Fiat cents per whole token and the ms timestamp of the quote
+    #[ink(message)]
+    fn latest_price(&self) -> (u128, u64);
+}
+
+/// Registry of accounts that passed compliance checks (KYC, jurisdiction)
+#[ink::trait_definition]
+pub trait AttestationRegistry {
+    /// Whether the account holds a valid attestation
+    #[ink(message)]
+    fn is_attested(&self, account: ink::primitives::AccountId) -> bool;
+}
+
+/// PSP34 messages used to mint bet receipts and trophies, and look up receipts
+#[ink::trait_definition]
+pub trait BetReceipts {
+    /// `PSP34::owner_of`
+    #[ink(message, selector = 0x1168624d)]
+    fn owner_of(&self, id: ReceiptId) -> Option<ink::primitives::AccountId>;
+
+    /// `PSP34Mintable::mint`
+    #[ink(message, selector = 0x6c41f2ec)]
+    fn mint(&mut self, account: ink::primitives::AccountId, id: ReceiptId) -> core::result::Result<(), PSP34Error>;
+}
+
 #[ink::contract]
 mod horse_race {
     use super::*;
-
-    // Asset transfer interfaces
-    use crate::assets::{AssetsCall, RuntimeCall};
+    use ink::storage::Mapping;
 
     // ============================================================================
     // ERROR TYPES
@@ -65,11 +351,274 @@ mod horse_race {
         RaceNotInProgress,
         /// Race not finished
         RaceNotFinished,
+        /// Race was already settled
+        AlreadySettled,
+        /// The race can't move from one status to the other
+        InvalidTransition { from: RaceStatus, to: RaceStatus },
+        /// The one-call simulation helper needs dev mode
+        DevModeDisabled,
+        /// Racing window has not elapsed yet
+        RaceStillRunning,
+        /// Durations must be greater than 0
+        InvalidDuration,
+        /// Betting window has not elapsed yet
+        BettingStillOpen,
+        /// Seed must not be 0
+        InvalidSeed,
+        /// Seed was already used by an earlier race
+        SeedAlreadyUsed,
+        /// Previous meeting card still has races to run
+        MeetingInProgress,
+        /// Meeting must have at least one race
+        InvalidRaceCount,
+        /// A Pick 6 pool is already open
+        Pick6InProgress,
+        /// No open Pick 6 pool
+        NoPick6Pool,
+        /// Pick 6 needs exactly one pick per leg
+        InvalidPickCount,
+        /// Pick 6 legs have not all finished
+        Pick6NotReady,
+        /// No finish-time line offered for this race
+        NoLineSet,
+        /// Odds must pay back more than the stake
+        InvalidOdds,
+        /// No futures odds offered for this horse
+        FuturesMarketClosed,
+        /// Account balance too low
+        InsufficientBalance,
+        /// Native transfer out of the contract failed
+        TransferFailed,
+        /// No unclaimed winnings for this account
+        NothingToClaim,
+        /// Claim deadline has passed
+        ClaimExpired,
+        /// Claim deadline or dormancy period has not passed yet
+        GracePeriodActive,
+        /// Not enough loyalty points
+        InsufficientPoints,
+        /// Not enough free-bet credits
+        InsufficientCredits,
+        /// Wagering requirement not met yet
+        WageringRequirementNotMet,
+        /// Bet would exceed the account's wager limit
+        WagerLimitExceeded,
+        /// Bet would exceed the account's loss limit
+        LossLimitExceeded,
+        /// Stake is worth less than the fiat minimum bet
+        BetBelowMinimum,
+        /// Stake is worth more than the fiat maximum bet
+        BetAboveMaximum,
+        /// No price feed is set, it failed, or it quoted zero
+        PriceFeedUnavailable,
+        /// Cached price is older than the allowed age
+        PriceStale,
+        /// Asset decimals can't change while the contract holds funds
+        FundsHeld,
+        /// Account has self-excluded
+        SelfExcluded,
+        /// Account is frozen by the operator
+        AccountFrozen,
+        /// Balance arithmetic overflowed or underflowed
+        ArithmeticOverflow,
+        /// Message re-entered while a value transfer was in flight
+        ReentrantCall,
+        /// Transferred value doesn't match the batch total
+        DepositMismatch,
+        /// Caller isn't a registered bridge
+        NotBridge,
+        /// Cross-chain message was already credited
+        DuplicateMessage,
+        /// Operator's allowance is too low for the bet
+        InsufficientAllowance,
+        /// Signature doesn't match the bet payload and bettor
+        InvalidSignature,
+        /// Nonce already used or out of order
+        InvalidNonce,
+        /// Exacta combination has no multiplier
+        CombinationNotOffered,
+        /// Results can still be challenged
+        ChallengePeriodActive,
+        /// Race result is disputed and awaiting admin review
+        SettlementFrozen,
+        /// Caller is not a settlement operator
+        NotOperator,
+        /// Settlement needs more operator approvals
+        ApprovalsPending,
+        /// Operator already approved this settlement
+        AlreadyApproved,
+        /// No settlement has been proposed for this race
+        NoProposal,
+        /// Approval threshold is zero or exceeds the number of operators
+        InvalidThreshold,
+        /// Caller is not the governance account
+        NotGovernance,
+        /// Strength, edge or multiplier out of range
+        InvalidConfig,
+        /// Not enough bankroll shares
+        InsufficientShares,
+        /// Bankroll can't change while a race is running or unsettled
+        BankrollLocked,
+        /// Bankroll was wiped out, shares have no value
+        BankrollDepleted,
+        /// Listener limit reached
+        TooManyListeners,
+        /// Receipt contract refused to mint the bet receipt
+        ReceiptMintFailed,
+        /// Horse already has an owner
+        HorseAlreadyOwned,
+        /// Caller does not own the horse
+        NotHorseOwner,
+        /// Syndicate does not exist
+        SyndicateNotFound,
+        /// Syndicate bet already placed or its race has moved on
+        SyndicateClosed,
+        /// Syndicate has the maximum number of members
+        SyndicateFull,
+        /// Caller did not create the syndicate
+        NotSyndicateCreator,
+        /// Caller has no stake in the syndicate
+        NotSyndicateMember,
+        /// Lay offer does not exist
+        OfferNotFound,
+        /// Stake exceeds what the offer has left, or the layer is backing their own offer
+        OfferUnavailable,
+        /// Caller did not post the offer
+        NotLayer,
+        /// No bet at that index in the current race
+        BetNotFound,
+        /// Caller doesn't hold the bet
+        NotBetOwner,
+        /// Bet is a free or syndicate bet, already cashed out, or cash-out is off
+        CashOutUnavailable,
+        /// Free, syndicate, in-running, receipt and cashed-out bets can't be topped up
+        CannotTopUp,
+        /// Bet was already cashed out or refunded
+        BetAlreadyClosed,
+        /// Only the final phase is left, it runs with run_race_simulation
+        FinalPhaseOnly,
+        /// No promo code with that hash
+        PromoCodeNotFound,
+        /// Promo code has expired
+        PromoCodeExpired,
+        /// Promo code has no uses left
+        PromoCodeExhausted,
+        /// Account already redeemed this promo code
+        PromoCodeAlreadyUsed,
+        /// No withdrawal request with that ID
+        WithdrawalNotFound,
+        /// Withdrawal request was already processed or rejected
+        WithdrawalNotPending,
+        /// Instant withdrawals are off, use request_withdrawal
+        CustodialWithdrawals,
+        /// Withdrawal would exceed the daily withdrawal cap
+        WithdrawalCapExceeded,
+        /// Race has taken the maximum number of bets
+        RaceBetLimitReached,
+        /// Account has placed the maximum number of bets in this race
+        AccountBetLimitReached,
+        /// Account has placed the maximum number of bets in this block
+        RateLimited,
+        /// Betting has stopped ahead of the race start
+        BettingCutoff,
+        /// Race is invite-only and the account is not on the allowlist
+        NotWhitelisted,
+        /// Account needs a compliance attestation to bet or deposit
+        AttestationRequired,
+        /// Caller is neither the owner nor the compliance operator
+        NotComplianceOperator,
+        /// No race room with that ID
+        RoomNotFound,
+        /// Caller did not create the room
+        NotRoomCreator,
+        /// Caller is not invited to the room, or created it
+        NotRoomMember,
+        /// Room has been run or cancelled
+        RoomClosed,
+        /// Room has the maximum number of members or bets
+        RoomFull,
+        /// Revealed seed doesn't match the room's commitment
+        SeedMismatch,
+        /// Silks aren't whole RGB colors within the limit, or the URI is too long
+        InvalidHorseMetadata,
+        /// No track with that ID
+        TrackNotFound,
+        /// Track distance or going out of range, or the registry is full
+        InvalidTrack,
+        /// No tournament with that ID
+        TournamentNotFound,
+        /// Tournament bankroll and race count must be greater than 0
+        InvalidTournament,
+        /// Entries have closed, the race isn't part of the tournament, or it's settled
+        TournamentClosed,
+        /// Tournament has the maximum number of entrants
+        TournamentFull,
+        /// Account has already entered the tournament
+        AlreadyEntered,
+        /// Account has not entered the tournament
+        NotEntered,
+        /// Tournament races have not all been settled
+        TournamentNotFinished,
+        /// Account already made a prediction on this race
+        AlreadyPredicted,
+        /// Race has taken the maximum number of predictions
+        PredictionLimitReached,
+        /// Race has the maximum number of sponsors
+        SponsorLimitReached,
+        /// Stored state is already at this code's version
+        AlreadyMigrated,
+        /// from_version isn't the stored storage version, or no migration path exists
+        StorageVersionMismatch,
+        /// Runtime refused the new code hash
+        UpgradeFailed,
     }
 
     /// Result type for contract operations
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// PSP22 token errors
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum PSP22Error {
+        /// Any other contract error, by name
+        Custom(String),
+        /// Not enough balance
+        InsufficientBalance,
+        /// Not enough allowance
+        InsufficientAllowance,
+        /// Recipient is the zero address
+        ZeroRecipientAddress,
+        /// Sender is the zero address
+        ZeroSenderAddress,
+        /// Recipient contract rejected the transfer
+        SafeTransferCheckFailed(String),
+    }
+
+    /// PSP34 token errors, as returned by the receipt contract
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum PSP34Error {
+        Custom(String),
+        SelfApprove,
+        NotApproved,
+        TokenExists,
+        TokenNotExists,
+        SafeTransferCheckFailed(String),
+    }
+
+    impl From<Error> for PSP22Error {
+        fn from(error: Error) -> Self {
+            match error {
+                Error::InsufficientBalance => PSP22Error::InsufficientBalance,
+                Error::InsufficientAllowance => PSP22Error::InsufficientAllowance,
+                other => PSP22Error::Custom(format!("{:?}", other)),
+            }
+        }
+    }
+
+    /// Timestamped amounts used for rolling-window limits
+    pub type ActivityLog = Vec<(u64, Balance)>;
+
     // ============================================================================
     // DATA STRUCTURES
     // ============================================================================
@@ -84,9 +633,37 @@ mod horse_race {
         pub strength: u64,
         pub normalized_strength: u64,  // S[i] * PRECISION
         pub base_speed: u64,           // Bs[i] = 14 + strength
+        pub acceleration: u64,         // 1-10, warm-up phase modifier
+        pub stamina: u64,              // 1-10, sprint phase modifier
+        pub silks: Vec<u8>,            // Silk colors as RGB triples, primary first
+        pub uri: Vec<u8>,              // Image or metadata URI
+        pub surface_affinity: [u64; NUM_SURFACES], // Speed per surface, scaled by PRECISION
+    }
+
+    /// Horse form: recent finishes and strength adjusted by results
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct HorseForm {
+        pub horse_id: u8,
+        pub recent_finishes: Vec<u8>,  // Most recent last, 1 = winner, 0 = did not finish
+        pub adjusted_strength: u64,    // Effective strength scaled by PRECISION
     }
 
     /// Horse state during race simulation
+    /// Lifetime placings of a horse
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct HorseRecord {
+        pub horse_id: u8,
+        pub starts: u32,
+        pub wins: u32,
+        pub seconds: u32,
+        pub thirds: u32,
+        pub did_not_finish: u32,
+    }
+
     /// Exacta bet structure (predicting 1st and 2nd in exact order)
     #[derive(Debug, Clone, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -97,770 +674,11576 @@ mod horse_race {
         pub first_pick: u8,            // Predicted 1st place horse ID
         pub second_pick: u8,           // Predicted 2nd place horse ID
         pub timestamp: u64,
+        pub free_bet: bool,            // Staked from free-bet credits
+        pub receipt: Option<(AccountId, u32)>, // Receipt NFT contract and token ID
+        pub syndicate: Option<u32>,    // Syndicate the bet was placed for
+        pub cash_out: Option<Balance>, // Amount paid if cashed out before the race
+        pub in_running_odds: Option<u64>, // Odds locked for an in-running bet (scaled by PRECISION)
+        pub bonus_stake: Balance,      // Part of the stake paid from bonus funds
+        pub additions: u32,            // Bets on the same exacta merged into this position
+        pub settled: bool,             // Paid, refunded or booked by settlement
+        pub beneficiary: Option<AccountId>, // Credited with payouts and refunds instead of the bettor
     }
 
-    /// Race result structure
-    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    /// Exchange offer to lay an exacta: the layer pays backers at `odds` if it comes in
+    #[derive(Debug, Clone, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
-    pub struct RaceResult {
+    pub struct LayOffer {
+        pub id: u32,
+        pub layer: AccountId,
+        pub first_pick: u8,
+        pub second_pick: u8,
+        pub odds: u64,                 // Decimal odds (scaled by PRECISION)
+        pub stake_open: Balance,       // Backer stake still unmatched
+        pub liability_open: Balance,   // Layer escrow covering the unmatched stake
+    }
+
+    /// Backer's stake matched against part of a lay offer
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct ExchangeMatch {
+        pub offer_id: u32,
+        pub backer: AccountId,
+        pub layer: AccountId,
+        pub stake: Balance,            // Backer's escrow
+        pub liability: Balance,        // Layer's escrow
+        pub status: BetStatus,         // From the backer's side
+    }
+
+    /// Group exacta bet pooled from members' balances
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Syndicate {
+        pub id: u32,
         pub race_id: u64,
-        pub rankings: Vec<u8>,         // Horse IDs in finish order [1st, 2nd, 3rd, ...]
-        pub finish_times: Vec<u64>,    // Finish times for each position
-        pub winning_exacta: (u8, u8),  // (1st, 2nd)
-        pub total_pot: Balance,
-        pub seed_used: u64,
+        pub creator: AccountId,
+        pub first_pick: u8,
+        pub second_pick: u8,
+        pub members: Vec<(AccountId, Balance)>, // Contribution per member
+        pub total: Balance,
+        pub placed: bool,              // Aggregated bet has been placed
     }
 
-    /// Payout record
+    /// Exacta bet in a private race room, staked from the bettor's balance
     #[derive(Debug, Clone, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
-    pub struct Payout {
+    pub struct RoomBet {
         pub bettor: AccountId,
-        pub bet_amount: Balance,
-        pub multiplier: u64,
-        pub payout_amount: Balance,
-        pub exacta: (u8, u8),
+        pub first_pick: u8,
+        pub second_pick: u8,
+        pub amount: Balance,
     }
 
-    /// Exacta probability entry
-    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    /// User-run private race with its own betting window, pot and allowlist
+    /// The creator commits to the seed up front and reveals it to run the race
+    #[derive(Debug, Clone, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
-    pub struct ExactaProbability {
-        pub first: u8,
-        pub second: u8,
-        pub probability: u64,          // Scaled by PRECISION
-        pub multiplier: u64,
+    pub struct RaceRoom {
+        pub id: u32,
+        pub creator: AccountId,
+        pub seed_hash: Hash,           // Blake2x256 of the seed's little-endian bytes
+        pub betting_start_time: u64,
+        pub betting_duration: u64,     // ms
+        pub members: Vec<AccountId>,   // Accounts invited to bet
+        pub bets: Vec<RoomBet>,
+        pub total_pot: Balance,
+        pub status: RaceStatus,        // Betting until run or cancelled, then Closed
+        pub result: Option<RaceResult>, // None if cancelled
     }
 
-    /// Race status enum
+    /// Betting tournament over consecutive races
+    /// Entrants pay the entry fee for a fixed chip bankroll; the final chip
+    /// counts rank the entrants and split the entry fees
     #[derive(Debug, Clone, PartialEq, Eq, Default)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
-    pub enum RaceStatus {
-        #[default]
-        Betting,        // 0 - Accepting bets (14 minutes)
-        Racing,         // 1 - Race in progress (1 minute)
-        Finished,       // 2 - Race complete, payouts ready
-        Closed,         // 3 - Payouts distributed
+    pub struct Tournament {
+        pub id: u32,
+        pub entry_fee: Balance,
+        pub bankroll: Balance,         // Chips each entrant starts with
+        pub first_race_id: u64,        // Races run to first_race_id + num_races - 1
+        pub num_races: u8,
+        pub entrants: Vec<TournamentEntry>,
+        pub prize_pool: Balance,       // Entry fees collected
+        pub settled: bool,
     }
 
-    // ============================================================================
-    // EVENTS
-    // ============================================================================
+    /// Tournament entrant and their chips
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct TournamentEntry {
+        pub account: AccountId,
+        pub chips: Balance,            // Tournament bankroll left, unsettled bets excluded
+        pub prize: Balance,            // Share of the prize pool, set at settlement
+    }
 
-    #[ink(event)]
-    pub struct RaceStarted {
-        #[ink(topic)]
-        race_id: u64,
-        seed: u64,
-        total_bets: u32,
+    /// Exacta bet in chips on the current race
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct TournamentBet {
+        pub tournament_id: u32,
+        pub bettor: AccountId,
+        pub first_pick: u8,
+        pub second_pick: u8,
+        pub chips: Balance,
     }
 
-    #[ink(event)]
-    pub struct RaceFinished {
-        #[ink(topic)]
-        race_id: u64,
-        first_place: u8,
-        second_place: u8,
-        third_place: u8,
+    /// Free-to-play exacta prediction on the current race, no stake involved
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Prediction {
+        pub predictor: AccountId,
+        pub first_pick: u8,
+        pub second_pick: u8,
     }
 
-    #[ink(event)]
-    pub struct BetPlaced {
-        #[ink(topic)]
-        bettor: AccountId,
-        first_pick: u8,
-        second_pick: u8,
-        amount: Balance,
+    /// A predictor's season score in the free-to-play game
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PredictionScore {
+        pub account: AccountId,
+        pub points: u64,               // Multiplier of each exacta hit
+        pub predictions: u32,          // Scored predictions (void DNF picks excluded)
+        pub hits: u32,
     }
 
-    #[ink(event)]
-    pub struct PayoutDistributed {
-        #[ink(topic)]
-        bettor: AccountId,
-        amount: Balance,
-        multiplier: u64,
+    /// PSP34 token ID; receipts use `U32` with the bet's archive ID
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum ReceiptId {
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        U128(u128),
+        Bytes(Vec<u8>),
     }
 
-    // ============================================================================
-    // CONTRACT STORAGE
-    // ============================================================================
+    /// Archived exacta bet with its race context
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct BetRecord {
+        pub id: u32,                   // Global bet sequence number
+        pub race_id: u64,              // Race the bet was placed on
+        pub bet: ExactaBet,
+    }
 
-    #[ink(storage)]
-    pub struct HorseRace {
-        /// Contract owner
-        owner: AccountId,
-        
-        /// Current race ID
-        race_id: u64,
-        
-        /// Race status
-        status: RaceStatus,
-        
-        /// Horses in the race
-        horses: Vec<Horse>,
-        
-        /// Current bets for this race
-        bets: Vec<ExactaBet>,
-        
-        /// Race results history
-        race_results: Vec<RaceResult>,
-        
-        /// Latest race result
-        latest_result: RaceResult,
-        
-        /// Payouts for current race
-        payouts: Vec<Payout>,
-        
-        /// Random seed for deterministic simulation
-        current_seed: u64,
-        
-        /// Race start timestamp
-        race_start_time: u64,
-        
-        /// Betting start timestamp
-        betting_start_time: u64,
-        
-        /// Total pot for current race
-        total_pot: Balance,
-        
-        /// Exacta reward multipliers (stored as flat array for gas efficiency)
-        /// Format: multipliers[first * 6 + second] = multiplier
-        reward_multipliers: Vec<u64>,
+    /// Racing surface of a track
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum Surface {
+        #[default]
+        Dirt,
+        Turf,
+        Synthetic,
     }
 
-    // ============================================================================
-    // IMPLEMENTATION
-    // ============================================================================
+    /// Track profile a race can be run on
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Track {
+        pub id: u32,
+        pub name: Vec<u8>,
+        pub distance: u64,             // Units, split evenly across the phases
+        pub surface: Surface,
+        pub going: u64,                // Speed factor, scaled by PRECISION (10000 = standard)
+    }
 
-    impl HorseRace {
-        /// Initialize the contract with 6 horses and reward multipliers
-        #[ink(constructor)]
-        pub fn new() -> Self {
+    /// Display details of a race, set by the operator when opening it
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RaceInfo {
+        pub name: Vec<u8>,
+        pub track: Vec<u8>,
+        pub distance: u32,             // Meters
+        pub start_time: u64,           // Scheduled start timestamp
+        pub commission_free: bool,     // Promotional race: no exchange rake or odds edge
+    }
+
+    /// Race result structure
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RaceResult {
+        pub race_id: u64,
+        pub rankings: Vec<u8>,         // Horse IDs in finish order [1st, 2nd, 3rd, ...]
+        pub finish_times: Vec<u64>,    // Finish times (ms) for each position
+        pub sectional_times: Vec<[u64; NUM_PHASES]>, // Per-phase times (ms) for each position
+        pub margins: Vec<u64>,         // Gap (ms) between position k and k+1
+        pub did_not_finish: Vec<u8>,   // Horse IDs that failed to finish (not ranked)
+        pub winning_exacta: (u8, u8),  // (1st, 2nd)
+        pub total_pot: Balance,
+        pub total_bets: u32,           // Exacta positions in the race
+        pub winning_bets: u32,         // Positions on the winning exacta (cashed-out excluded)
+        pub winning_stake: Balance,    // Stake on those positions
+        pub multiplier: u64,           // Table multiplier of the winning exacta
+        pub seed_used: u64,
+        pub meeting_id: Option<u32>,   // Meeting this race belongs to
+        pub race_number: u8,           // Position on the meeting card (1-based, 0 if none)
+        pub weights: Vec<u64>,         // Selection weights the race was run with
+        pub info: RaceInfo,            // Name, track, distance and start time
+        pub track_id: Option<u32>,     // Track profile the race was run on (None = standard course)
+    }
+
+    /// Settlement state of a multi-race bet
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum BetStatus {
+        #[default]
+        Pending,        // Waiting on race results
+        Won,            // All legs hit, payout recorded
+        Lost,           // A leg missed
+        Refunded,       // A picked horse did not finish
+    }
+
+    /// Daily double bet (winners of two consecutive races)
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct DailyDoubleBet {
+        pub id: u32,
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub first_race_id: u64,        // Leg 1, leg 2 is first_race_id + 1
+        pub picks: (u8, u8),           // Predicted winners of (leg 1, leg 2)
+        pub odds: u64,                 // Payout odds locked at placement (scaled by PRECISION)
+        pub status: BetStatus,
+        pub payout: Balance,
+    }
+
+    /// Head-to-head bet: `horse` finishes ahead of `opponent`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct MatchBet {
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub horse: u8,                 // Backed horse
+        pub opponent: u8,              // Horse it must beat
+        pub odds: u64,                 // Payout odds (scaled by PRECISION)
+        pub status: BetStatus,
+        pub payout: Balance,
+    }
+
+    /// Operator-set line on the winner's finish time
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct FinishTimeLine {
+        pub line: u64,                 // Winner's finish time (ms)
+        pub over_odds: u64,            // Scaled by PRECISION
+        pub under_odds: u64,           // Scaled by PRECISION
+    }
+
+    /// Over/under bet on the winner's finish time
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct OverUnderBet {
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub over: bool,                // true = over the line, false = under
+        pub line: u64,                 // Line at placement (ms)
+        pub odds: u64,                 // Scaled by PRECISION
+        pub status: BetStatus,
+        pub payout: Balance,
+    }
+
+    /// Futures bet on the season champion (most wins over the season)
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct FuturesBet {
+        pub id: u32,
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub season: u32,
+        pub horse: u8,
+        pub odds: u64,                 // Locked at placement (scaled by PRECISION)
+        pub status: BetStatus,
+        pub payout: Balance,
+    }
+
+    /// A horse's championship standing within a season
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct SeasonStanding {
+        pub horse_id: u8,
+        pub points: u32,               // Championship points from finishing positions
+        pub wins: u32,
+    }
+
+    /// Season championship table, live for the current season and snapshotted on close
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct SeasonStandings {
+        pub season: u32,
+        pub races: u32,                // Races run in the season
+        pub standings: Vec<SeasonStanding>, // Ranked by points, then wins, then horse ID
+        pub champions: Vec<u8>,        // Futures champions (most wins), set on close
+        pub closed_at: u64,            // Timestamp of close_season (0 while live)
+    }
+
+    /// A bettor's settled exacta results over a season, ranked on the leaderboard
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct LeaderboardEntry {
+        pub account: AccountId,
+        pub net_winnings: i128,        // Returned minus staked
+        pub staked: Balance,           // Real-money stakes settled
+        pub bets: u32,                 // Settled bets (refunds excluded)
+    }
+
+    /// Pick 6 pool over six consecutive races
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Pick6Pool {
+        pub id: u32,
+        pub first_race_id: u64,        // Leg 1, legs run to first_race_id + 5
+        pub total_stakes: Balance,
+        pub carryover_in: Balance,     // Jackpot carried in from earlier pools
+        pub entries: u32,
+        pub settled: bool,
+        pub jackpot_paid: Balance,     // Paid to 6-of-6 tickets
+        pub consolation_paid: Balance, // Paid to 5-of-6 tickets
+        pub carryover_out: Balance,    // Carried to the next pool
+    }
+
+    /// Pick 6 ticket
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Pick6Entry {
+        pub pool_id: u32,
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub picks: Vec<u8>,            // Predicted winner of each leg
+        pub hits: u8,                  // Legs hit, set at settlement
+        pub payout: Balance,
+    }
+
+    /// Race meeting: an ordered card of races sharing a session
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Meeting {
+        pub id: u32,
+        pub name: Vec<u8>,
+        pub session_start: u64,        // Timestamp the meeting was created
+        pub num_races: u8,             // Races on the card
+        pub race_ids: Vec<u64>,        // Races run so far, in card order
+    }
+
+    /// Payout record
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Payout {
+        pub bettor: AccountId,
+        pub bet_amount: Balance,
+        pub multiplier: u64,
+        pub payout_amount: Balance,
+        pub exacta: (u8, u8),
+    }
+
+    /// Refund record for a bet voided by a DNF
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Refund {
+        pub bettor: AccountId,
+        pub amount: Balance,
+        pub exacta: (u8, u8),          // Picks of the voided bet
+    }
+
+    /// Winnings waiting to be claimed (claim mode)
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct UnclaimedWinnings {
+        pub amount: Balance,
+        pub deadline: u64,             // Claimable until this timestamp
+    }
+
+    /// State of a queued withdrawal
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum WithdrawalStatus {
+        Pending,        // Funds locked, waiting on the operator
+        Processed,      // Paid out off-chain
+        Rejected,       // Funds returned to the balance
+    }
+
+    /// Withdrawal filed for operator processing
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct WithdrawalRequest {
+        pub id: u32,
+        pub account: AccountId,
+        pub amount: Balance,           // Taken from the balance
+        pub fee: Balance,              // Part of the amount kept by the treasury
+        pub status: WithdrawalStatus,
+        pub requested_at: u64,
+        pub resolved_at: Option<u64>,  // When it was processed or rejected
+    }
+
+    /// Provenance of a deposit that arrived over XCM or a bridge
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct CrossChainDeposit {
+        pub account: AccountId,        // Account credited
+        pub amount: Balance,
+        pub origin_chain: u32,         // Parachain ID the funds came from
+        pub message_id: Hash,          // XCM message or bridge transfer ID
+        pub bridge: AccountId,         // Bridge account that delivered it
+        pub race_id: u64,              // Latest race when it was credited
+        pub timestamp: u64,
+    }
+
+    /// Why an account balance changed
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum LedgerReason {
+        Deposit,        // Funds paid in
+        Withdrawal,     // Funds paid out of the contract
+        Bet,            // Stake taken from the balance
+        Payout,         // Winnings credited
+        Refund,         // Stake returned (DNF, void)
+        Claim,          // Unclaimed winnings moved into the balance
+        Transfer,       // Balance moved between accounts
+        Bonus,          // Free bet credits released
+        Bankroll,       // Bankroll shares redeemed
+        Sweep,          // Dormant balance swept to the treasury
+        OwnerShare,     // Horse owner's share of the house profit
+        CashOut,        // Open bet settled early
+        RoomFee,        // Fee for opening a private race room
+        TournamentEntry, // Tournament entry fee
+        WithdrawalRejected, // Queued withdrawal returned by the operator
+        Sponsorship,    // Sponsor funds added to a race's pot
+    }
+
+    /// Why a stake or escrow was handed back
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum RefundReason {
+        DidNotFinish,   // A picked horse did not finish
+        Push,           // Winning time landed exactly on the line
+        Cancelled,      // Room, offer or syndicate stake called off
+        Unmatched,      // Exchange escrow nobody matched
+        UnwonPot,       // Unwon pot returned to the losing stakes
+        Courtesy { bet_id: u32, code: u16 }, // Refunded by the owner, code says why
+    }
+
+    /// One balance mutation of an account
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct LedgerEntry {
+        pub reason: LedgerReason,
+        pub delta: i128,               // Signed change to the balance
+        pub balance_after: Balance,
+        pub race_id: u64,              // Race current at the time
+        pub timestamp: u64,
+    }
+
+    /// Winning bet commemorated by a trophy NFT, SCALE-encoded as its token ID
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct Trophy {
+        pub race_id: u64,
+        pub bet_index: u32,            // Bet's index within the race
+        pub exacta: (u8, u8),
+        pub payout: Balance,
+    }
+
+    /// Exacta bet payload signed off-chain by the bettor
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct SignedBet {
+        pub bettor: AccountId,
+        pub first_pick: u8,
+        pub second_pick: u8,
+        pub amount: Balance,
+        pub nonce: u64,                // Must equal the bettor's next nonce
+    }
+
+    /// Signature over a `SignedBet`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum BetSignature {
+        Sr25519([u8; 64]),
+        Ecdsa([u8; 65]),
+    }
+
+    /// Self-imposed betting limits (0 = no limit)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct BettingLimits {
+        pub daily_wager: Balance,
+        pub weekly_wager: Balance,
+        pub daily_loss: Balance,
+        pub weekly_loss: Balance,
+        pub in_fiat: bool,             // Limits are fiat cents, converted at the cached price
+    }
+
+    /// Cached quote from the price feed
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct FiatRate {
+        pub price: u128,               // Fiat cents per whole token (ASSET_UNIT)
+        pub updated_at: u64,           // When the feed quoted it (ms)
+    }
+
+    /// Exacta probability entry
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct ExactaProbability {
+        pub first: u8,
+        pub second: u8,
+        pub probability: u64,          // Scaled by PRECISION
+        pub multiplier: u64,
+        pub offered: bool,             // False when the multiplier is zero (unbettable)
+    }
+
+    /// House profit and loss of a race (race_id 0 holds lifetime totals)
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RacePnl {
+        pub race_id: u64,
+        pub staked: Balance,           // Real-money stakes settled
+        pub paid_out: Balance,         // Payouts and refunds
+        pub rake: Balance,             // Exchange commission taken
+        pub net: i128,                 // House result: staked + rake - paid_out
+    }
+
+    /// Everything back-office systems need about a settled race, in one struct
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct SettlementReport {
+        pub race_id: u64,
+        pub rankings: Vec<u8>,
+        pub did_not_finish: Vec<u8>,
+        pub winning_exacta: (u8, u8),
+        pub multiplier: u64,           // Table multiplier the winning exacta paid at
+        pub winners: u32,              // Winning exacta bets
+        pub exacta_paid: Balance,      // Paid to winning exacta bets, carryover included
+        pub total_staked: Balance,     // Real-money stakes settled across markets
+        pub total_paid: Balance,       // Payouts and refunds across markets
+        pub rake: Balance,             // Commission taken
+        pub commission_free: bool,     // Promotional race, rake and edges waived
+        pub sponsored: Balance,        // Sponsor funds in the pot, part of carryover_in
+        pub carryover_in: Balance,     // Unwon pot carried into the race
+        pub carryover_out: Balance,    // Unwon pot carried to the next race
+        pub settled_at: u64,
+    }
+
+    /// Contract-wide betting statistics and the biggest single payout
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct GlobalStats {
+        pub total_volume: Balance,     // Real-money stakes across every market
+        pub total_bets: u64,
+        pub unique_bettors: u32,
+        pub total_payouts: Balance,    // Winnings credited across every market
+        pub biggest_payout: Balance,
+        pub biggest_payout_race: u64,  // Race current when the record was paid
+        pub biggest_payout_bettor: Option<AccountId>,
+    }
+
+    /// Operator-created promo code, stored under the hash of the code
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PromoCode {
+        pub value: Balance,            // Bonus funds credited per redemption
+        pub max_uses: u32,
+        pub uses: u32,
+        pub expires_at: u64,           // Timestamp after which it can't be redeemed
+    }
+
+    /// What happens to the exacta pot when nobody hits the winning exacta
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum UnwonPotPolicy {
+        #[default]
+        Retain,         // House keeps the losing stakes
+        Carryover,      // Paid to the next race's winners
+        Refund,         // Returned to the losing bettors by stake
+        Treasury,       // Swept to the treasury
+    }
+
+    /// Runtime parameters, read and applied as one set
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Config {
+        pub betting_duration: u64,     // ms
+        pub racing_duration: u64,      // ms
+        pub betting_cutoff: u64,       // ms
+        pub challenge_period: u64,     // ms
+        pub keeper_reward: Balance,
+        pub approval_threshold: u32,
+        pub unwon_pot_policy: UnwonPotPolicy,
+        pub claim_mode: bool,
+        pub claim_period: u64,         // ms
+        pub dormancy_period: u64,      // ms
+        pub custodial_withdrawals: bool,
+        pub whitelist_mode: bool,
+        pub room_fee: Balance,
+        pub withdrawal_fee: u64,       // Scaled by PRECISION
+        pub daily_withdrawal_cap: Balance,
+        pub max_bets_per_race: u32,
+        pub max_bets_per_account: u32,
+        pub wagering_requirement: u64, // Scaled by PRECISION
+        pub points_accrual_rate: u64,  // Scaled by PRECISION
+        pub points_per_credit: u128,
+        // Governance-controlled when a governance account is set
+        pub match_bet_edge: u64,       // Scaled by PRECISION
+        pub daily_double_edge: u64,    // Scaled by PRECISION
+        pub exchange_commission: u64,  // Scaled by PRECISION
+        pub cash_out_factor: u64,      // Scaled by PRECISION
+        pub owner_shares: (u64, u64),  // Winner's and runner-up's owners, scaled by PRECISION
+    }
+
+    /// Funds a sponsor added to a race's pot
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Sponsorship {
+        pub sponsor: AccountId,
+        pub amount: Balance,           // Total the sponsor added
+        pub sponsored_at: u64,         // Timestamp of the latest contribution
+    }
+
+    /// Unwon exacta pot of a race and how it was handled
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct UnwonPot {
+        pub race_id: u64,
+        pub amount: Balance,           // Losing stakes plus any pot carried in
+        pub policy: UnwonPotPolicy,
+    }
+
+    /// Money staked on one exacta combination in the current race
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PoolShare {
+        pub first: u8,
+        pub second: u8,
+        pub total_staked: Balance,
+        pub bet_count: u32,
+    }
+
+    /// Money on one horse in the current race, as first and as second pick
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct HorsePopularity {
+        pub horse_id: u8,
+        pub first_stake: Balance,      // Staked on exactas with this horse first
+        pub first_bets: u32,
+        pub second_stake: Balance,     // Staked on exactas with this horse second
+        pub second_bets: u32,
+    }
+
+    /// One-call dashboard of the current race's exacta market
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct MarketSummary {
+        pub most_backed: Option<(u8, u8)>, // None until a paid stake is placed
+        pub most_backed_stake: Balance,
+        pub least_backed: Option<(u8, u8)>, // Offered exacta with the least paid stake
+        pub least_backed_stake: Balance,
+        pub total_pot: Balance,
+        pub largest_bet: Balance,      // Largest single position
+        pub largest_bettor: Option<AccountId>,
+    }
+
+    /// Exacta odds in every format frontends display
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct ExactaOdds {
+        pub first: u8,
+        pub second: u8,
+        pub multiplier: u64,           // Payout = stake × multiplier
+        pub decimal_odds: u64,         // Scaled by PRECISION
+        pub implied_probability: u64,  // 1 / decimal odds, scaled by PRECISION
+        pub model_probability: u64,    // calculate_exacta_probability, scaled by PRECISION
+    }
+
+    /// Bookmaker margin of the multiplier table (scaled by PRECISION)
+    /// Positive values favour the house, negative values the bettors
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct BookOverround {
+        pub per_horse: Vec<i64>,       // Implied minus model win probability, per first-place horse
+        pub overall: i64,              // Sum of implied probabilities minus 1
+    }
+
+    /// Race status enum
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum RaceStatus {
+        #[default]
+        Betting,        // 0 - Accepting bets (14 minutes)
+        Racing,         // 1 - Race in progress (1 minute)
+        Finished,       // 2 - Race complete, payouts ready
+        Closed,         // 3 - Payouts distributed
+    }
+
+    /// Lifecycle steps, validated against the status in one place
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum RaceAction {
+        Start,          // Betting -> Racing, once the betting window has elapsed
+        Simulate,       // Betting -> Racing, skipping the windows
+        Finish,         // Racing -> Finished, once the racing window has elapsed
+        Settle,         // Finished -> Closed, once the challenge period has passed
+        Reset,          // Any status -> Betting (owner only)
+    }
+
+    /// Current lifecycle phase and its countdown
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PhaseInfo {
+        pub status: RaceStatus,
+        pub started_at: u64,           // When the phase began
+        pub duration: u64,             // Configured length (ms), 0 if open-ended
+        pub remaining: u64,            // ms until the phase can move on
+    }
+
+    /// Deployed revision, so indexers and frontends can pick their decoding
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct ContractVersion {
+        pub major: u16,
+        pub minor: u16,
+        pub patch: u16,
+        pub storage_version: u32,      // Layout the stored state is in
+    }
+
+    // ============================================================================
+    // EVENTS
+    // ============================================================================
+
+    #[ink(event)]
+    pub struct RaceStatusChanged {
+        #[ink(topic)]
+        race_id: u64,
+        from: RaceStatus,
+        to: RaceStatus,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct ResultChallenged {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        challenger: AccountId,
+        upheld: bool,                  // Verification failed, settlement frozen
+    }
+
+    #[ink(event)]
+    pub struct PayoutsApproved {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        operator: AccountId,
+        approvals: u32,
+        threshold: u32,
+    }
+
+    #[ink(event)]
+    pub struct DisputeResolved {
+        #[ink(topic)]
+        race_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct RaceStarted {
+        #[ink(topic)]
+        race_id: u64,
+        seed: u64,
+        total_bets: u32,
+    }
+
+    #[ink(event)]
+    pub struct RaceFinished {
+        #[ink(topic)]
+        race_id: u64,
+        first_place: u8,
+        second_place: u8,
+        third_place: u8,
+        did_not_finish: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct MeetingCreated {
+        #[ink(topic)]
+        meeting_id: u32,
+        name: Vec<u8>,
+        num_races: u8,
+    }
+
+    #[ink(event)]
+    pub struct BetPlaced {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        bettor: AccountId,
+        bet_id: Option<u32>,
+        bet_index: u32,
+        first_pick: u8,
+        second_pick: u8,
+        amount: Balance,
+        demo: bool,
+    }
+
+    #[ink(event)]
+    pub struct PayoutDistributed {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        bettor: AccountId,
+        placed_by: AccountId,
+        bet_id: Option<u32>,
+        bet_index: u32,
+        amount: Balance,
+        multiplier: u64,
+        demo: bool,
+    }
+
+    #[ink(event)]
+    pub struct RecordPayout {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        bettor: AccountId,
+        amount: Balance,
+        previous_record: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DailyDoublePlaced {
+        #[ink(topic)]
+        bettor: AccountId,
+        bet_id: u32,
+        first_race_id: u64,
+        picks: (u8, u8),
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DailyDoubleSettled {
+        #[ink(topic)]
+        bettor: AccountId,
+        bet_id: u32,
+        status: BetStatus,
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MatchBetPlaced {
+        #[ink(topic)]
+        bettor: AccountId,
+        horse: u8,
+        opponent: u8,
+        amount: Balance,
+        odds: u64,
+    }
+
+    #[ink(event)]
+    pub struct MatchBetSettled {
+        #[ink(topic)]
+        bettor: AccountId,
+        horse: u8,
+        opponent: u8,
+        status: BetStatus,
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    pub struct OverUnderPlaced {
+        #[ink(topic)]
+        bettor: AccountId,
+        over: bool,
+        line: u64,
+        amount: Balance,
+        odds: u64,
+    }
+
+    #[ink(event)]
+    pub struct OverUnderSettled {
+        #[ink(topic)]
+        bettor: AccountId,
+        over: bool,
+        winning_time: u64,
+        status: BetStatus,
+        payout: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FuturesBetPlaced {
+        #[ink(topic)]
+        bettor: AccountId,
+        bet_id: u32,
+        season: u32,
+        horse: u8,
+        amount: Balance,
+        odds: u64,
+    }
+
+    #[ink(event)]
+    pub struct FuturesSettled {
+        #[ink(topic)]
+        season: u32,
+        champions: Vec<u8>,
+        winning_bets: u32,
+        total_paid: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SeasonClosed {
+        #[ink(topic)]
+        season: u32,
+        races: u32,
+        leader: Option<u8>,
+        leader_points: u32,
+    }
+
+    #[ink(event)]
+    pub struct Pick6Opened {
+        #[ink(topic)]
+        pool_id: u32,
+        first_race_id: u64,
+        carryover_in: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Pick6Settled {
+        #[ink(topic)]
+        pool_id: u32,
+        jackpot_winners: u32,
+        consolation_winners: u32,
+        carryover_out: Balance,
+    }
+
+    #[ink(event)]
+    pub struct KeeperRewarded {
+        #[ink(topic)]
+        keeper: AccountId,
+        race_id: u64,
+        status: RaceStatus,
+        reward: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TreasuryFunded {
+        #[ink(topic)]
+        funder: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ConfigUpdated {
+        #[ink(topic)]
+        updated_by: AccountId,
+        config: Config,
+    }
+
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        account: AccountId,
+        race_id: u64,                  // Latest race when the deposit was made
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CrossChainDeposited {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        origin_chain: u32,
+        message_id: Hash,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        account: AccountId,
+        race_id: u64,                  // Latest race when the withdrawal was made
+        amount: Balance,
+        fee: Balance,                  // Kept by the treasury, the rest is paid out
+    }
+
+    #[ink(event)]
+    pub struct WithdrawalRequested {
+        #[ink(topic)]
+        request_id: u32,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct WithdrawalResolved {
+        #[ink(topic)]
+        request_id: u32,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        status: WithdrawalStatus,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        amount: Balance,
+    }
+
+    /// PSP22 transfer; `from` is None for credits, `to` is None for debits
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BankrollStaked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        shares: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BankrollUnstaked {
+        #[ink(topic)]
+        account: AccountId,
+        shares: Balance,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct HorseMetadataUpdated {
+        #[ink(topic)]
+        horse_id: u8,
+        silks: Vec<u8>,
+        uri: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct HorseTransferred {
+        #[ink(topic)]
+        horse_id: u8,
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnerSharePaid {
+        #[ink(topic)]
+        owner: AccountId,
+        race_id: u64,
+        horse_id: u8,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct UnwonPotSettled {
+        #[ink(topic)]
+        race_id: u64,
+        amount: Balance,
+        policy: UnwonPotPolicy,
+    }
+
+    #[ink(event)]
+    pub struct CarryoverPaid {
+        #[ink(topic)]
+        race_id: u64,
+        amount: Balance,
+        winners: u32,
+    }
+
+    #[ink(event)]
+    pub struct PhaseCompleted {
+        #[ink(topic)]
+        race_id: u64,
+        phase: u8,
+        standings: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct CashedOut {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        bettor: AccountId,
+        bet_id: u32,
+        bet_index: u32,
+        stake: Balance,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct LayOffered {
+        #[ink(topic)]
+        offer_id: u32,
+        #[ink(topic)]
+        layer: AccountId,
+        first_pick: u8,
+        second_pick: u8,
+        odds: u64,
+        stake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct OfferMatched {
+        #[ink(topic)]
+        offer_id: u32,
+        #[ink(topic)]
+        backer: AccountId,
+        stake: Balance,
+        liability: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SyndicateCreated {
+        #[ink(topic)]
+        syndicate_id: u32,
+        #[ink(topic)]
+        creator: AccountId,
+        race_id: u64,
+        first_pick: u8,
+        second_pick: u8,
+    }
+
+    #[ink(event)]
+    pub struct SyndicateBetPlaced {
+        #[ink(topic)]
+        syndicate_id: u32,
+        total: Balance,
+        members: u32,
+    }
+
+    #[ink(event)]
+    pub struct RoomCreated {
+        #[ink(topic)]
+        room_id: u32,
+        #[ink(topic)]
+        creator: AccountId,
+        seed_hash: Hash,
+        betting_duration: u64,
+        fee: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoomBetPlaced {
+        #[ink(topic)]
+        room_id: u32,
+        #[ink(topic)]
+        bettor: AccountId,
+        first_pick: u8,
+        second_pick: u8,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoomSettled {
+        #[ink(topic)]
+        room_id: u32,
+        winning_exacta: (u8, u8),
+        total_pot: Balance,
+        paid_out: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoomCancelled {
+        #[ink(topic)]
+        room_id: u32,
+        refunded: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TournamentCreated {
+        #[ink(topic)]
+        tournament_id: u32,
+        entry_fee: Balance,
+        bankroll: Balance,
+        first_race_id: u64,
+        num_races: u8,
+    }
+
+    #[ink(event)]
+    pub struct TournamentJoined {
+        #[ink(topic)]
+        tournament_id: u32,
+        #[ink(topic)]
+        account: AccountId,
+        entry_fee: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TournamentBetPlaced {
+        #[ink(topic)]
+        tournament_id: u32,
+        #[ink(topic)]
+        bettor: AccountId,
+        race_id: u64,
+        first_pick: u8,
+        second_pick: u8,
+        chips: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PredictionMade {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        predictor: AccountId,
+        first_pick: u8,
+        second_pick: u8,
+    }
+
+    #[ink(event)]
+    pub struct PredictionScored {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        predictor: AccountId,
+        points: u64,
+    }
+
+    #[ink(event)]
+    pub struct TournamentSettled {
+        #[ink(topic)]
+        tournament_id: u32,
+        prize_pool: Balance,
+        winners: Vec<(AccountId, Balance)>,
+    }
+
+    #[ink(event)]
+    pub struct ListenerCallFailed {
+        #[ink(topic)]
+        listener: AccountId,
+        race_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct BalanceTransferred {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct WinningsClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct UnclaimedSwept {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DormantBalanceSwept {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PointsAwarded {
+        #[ink(topic)]
+        account: AccountId,
+        points: u128,
+        total_points: u128,
+    }
+
+    #[ink(event)]
+    pub struct PointsRedeemed {
+        #[ink(topic)]
+        account: AccountId,
+        points: u128,
+        credits: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FreeBetCreditsGranted {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FreeBetCreditsReleased {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BonusGranted {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PromoCodeCreated {
+        #[ink(topic)]
+        code_hash: Hash,
+        value: Balance,
+        max_uses: u32,
+        expires_at: u64,
+    }
+
+    #[ink(event)]
+    pub struct PromoCodeRedeemed {
+        #[ink(topic)]
+        code_hash: Hash,
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BonusReleased {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SelfExclusionStarted {
+        #[ink(topic)]
+        account: AccountId,
+        until: u64,
+    }
+
+    #[ink(event)]
+    pub struct AccountFrozen {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountUnfrozen {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct WhitelistUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        whitelisted: bool,
+    }
+
+    #[ink(event)]
+    pub struct AttestationUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        attested: bool,
+    }
+
+    #[ink(event)]
+    pub struct ContractUpgraded {
+        #[ink(topic)]
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
+    #[ink(event)]
+    pub struct BetRefunded {
+        #[ink(topic)]
+        bettor: AccountId,
+        amount: Balance,
+        reason: RefundReason,
+    }
+
+    #[ink(event)]
+    pub struct PotCarriedOver {
+        #[ink(topic)]
+        from_race: u64,
+        #[ink(topic)]
+        to_race: u64,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RaceSponsored {
+        #[ink(topic)]
+        race_id: u64,
+        #[ink(topic)]
+        sponsor: AccountId,
+        amount: Balance,
+        pot: Balance,
+    }
+
+    // ============================================================================
+    // CONTRACT STORAGE
+    // ============================================================================
+
+    #[ink(storage)]
+    pub struct HorseRace {
+        /// Contract owner
+        owner: AccountId,
+        
+        /// Code and storage revision, bumped by upgrades and migrations
+        version: ContractVersion,
+        
+        /// Lets the owner run a whole race in one call (test deployments only)
+        dev_mode: bool,
+        
+        /// Current race ID
+        race_id: u64,
+        
+        /// Race status
+        status: RaceStatus,
+        
+        /// Horses in the race
+        horses: Vec<Horse>,
+        
+        /// Form of each horse, indexed by horse ID
+        horse_forms: Vec<HorseForm>,
+        
+        /// Lifetime placings of each horse, indexed by horse ID
+        horse_records: Vec<HorseRecord>,
+        
+        /// Current bets for this race
+        bets: Vec<ExactaBet>,
+        
+        /// Every exacta bet ever placed, by global sequence number
+        bet_archive: Mapping<u32, BetRecord>,
+        
+        /// Number of archived bets
+        bet_count: u32,
+        
+        /// Archived bet IDs per account, oldest first
+        account_bets: Mapping<AccountId, Vec<u32>>,
+        
+        /// Positions of each bettor's bets in the race's bet list, by (race_id, bettor)
+        race_bets: Mapping<(u64, AccountId), Vec<u32>>,
+        
+        /// Most exacta bets a race can take (0 for no cap)
+        max_bets_per_race: u32,
+        
+        /// Most exacta bets one account can place in a race (0 for no cap)
+        max_bets_per_account: u32,
+        
+        /// Most bet placements one account can make per block (0 for no limit)
+        max_bets_per_block: u32,
+        
+        /// Block of each account's latest bet placements and how many it made there
+        block_bets: Mapping<AccountId, (BlockNumber, u32)>,
+        
+        /// Open paid stakes per exacta for the current race (free bets excluded)
+        stake_per_combination: Mapping<(u8, u8), Balance>,
+        
+        /// Open positions and their free-bet stake per exacta for the current race
+        positions_per_combination: Mapping<(u8, u8), (u32, Balance)>,
+        
+        /// Index of the largest exacta position in the current race
+        largest_bet: Option<u32>,
+        
+        /// Race results history
+        race_results: Vec<RaceResult>,
+        
+        /// Latest race result
+        latest_result: RaceResult,
+        
+        /// Display details per race ID
+        race_info: Mapping<u64, RaceInfo>,
+        
+        /// Registered track profiles, indexed by track ID
+        tracks: Vec<Track>,
+        
+        /// Track each race is run on, by race ID (standard course if unset)
+        race_tracks: Mapping<u64, u32>,
+        
+        /// Payouts for current race
+        payouts: Vec<Payout>,
+        
+        /// Refunds for current race (bets voided by a DNF)
+        refunds: Vec<Refund>,
+        
+        /// Head-to-head bets for current race
+        match_bets: Vec<MatchBet>,
+        
+        /// Finish-time line offered for current race
+        finish_time_line: Option<FinishTimeLine>,
+        
+        /// Over/under bets for current race
+        over_under_bets: Vec<OverUnderBet>,
+        
+        /// Random seed for deterministic simulation
+        current_seed: u64,
+        
+        /// Race each start seed was used for, so past outcomes can't be replayed
+        used_seeds: Mapping<u64, u64>,
+        
+        /// Race start timestamp
+        race_start_time: u64,
+        
+        /// Betting start timestamp
+        betting_start_time: u64,
+        
+        /// Bets close this long before the betting window ends (ms, 0 for none)
+        betting_cutoff: u64,
+        
+        /// Betting window length (ms)
+        betting_duration: u64,
+        
+        /// Racing window length (ms)
+        racing_duration: u64,
+        
+        /// Phases of the current race run so far, before the final one
+        race_phase: u8,
+        
+        /// Running time (ms) of each horse after the phases run so far
+        running_times: Vec<u64>,
+        
+        /// Time after a race finishes during which results can be challenged (ms)
+        challenge_period: u64,
+        
+        /// Timestamp the current race finished
+        race_finish_time: u64,
+        
+        /// Races whose settlement is frozen pending admin review
+        disputed_races: Vec<u64>,
+        
+        /// House P&L per race
+        race_pnl: Mapping<u64, RacePnl>,
+
+        /// Settlement report per settled race
+        settlement_reports: Mapping<u64, SettlementReport>,
+        
+        /// House P&L across all races
+        lifetime_pnl: RacePnl,
+        
+        /// Contract-wide betting statistics
+        global_stats: GlobalStats,
+        
+        /// Accounts that have placed a real-money wager
+        known_bettors: Mapping<AccountId, ()>,
+        
+        /// Handling of the exacta pot when there are no winners
+        unwon_pot_policy: UnwonPotPolicy,
+        
+        /// Unwon pot waiting to be paid to the next race's winners
+        carryover: Balance,
+        
+        /// Unwon pot of each race that had no winners
+        unwon_pots: Mapping<u64, UnwonPot>,
+        
+        /// Sponsors of each race's pot
+        sponsorships: Mapping<u64, Vec<Sponsorship>>,
+        
+        /// Play-money balances for demo mode
+        demo_balances: Mapping<AccountId, Balance>,
+        
+        /// Play-money exacta bets for current race
+        demo_bets: Vec<ExactaBet>,
+        
+        /// Share of an open bet's value paid on cash-out (scaled by PRECISION, 0 disables)
+        cash_out_factor: u64,
+        
+        /// Exchange lay offers for the current race, indexed by ID
+        lay_offers: Vec<LayOffer>,
+        
+        /// Matched exchange bets for the current race
+        exchange_matches: Vec<ExchangeMatch>,
+        
+        /// Commission on exchange net winnings (scaled by PRECISION)
+        exchange_commission: u64,
+        
+        /// Syndicates by ID
+        syndicates: Mapping<u32, Syndicate>,
+        
+        /// Number of syndicates created
+        syndicate_count: u32,
+        
+        /// Private race rooms by ID
+        rooms: Mapping<u32, RaceRoom>,
+        
+        /// Number of race rooms opened
+        room_count: u32,
+        
+        /// Fee for opening a race room
+        room_fee: Balance,
+        
+        /// Tournaments by ID
+        tournaments: Mapping<u32, Tournament>,
+        
+        /// Number of tournaments created
+        tournament_count: u32,
+        
+        /// Tournament bets on the current race, settled or refunded with it
+        tournament_bets: Vec<TournamentBet>,
+        
+        /// Free-to-play predictions on the current race
+        predictions: Vec<Prediction>,
+        
+        /// Each predictor's score per season
+        prediction_scores: Mapping<(u32, AccountId), PredictionScore>,
+        
+        /// Top predictors by points per season, best first
+        prediction_leaderboards: Mapping<u32, Vec<PredictionScore>>,
+        
+        /// Owner of each horse
+        horse_owners: Mapping<u8, AccountId>,
+        
+        /// Share of a race's house profit paid to the winner's and the
+        /// runner-up's owners (scaled by PRECISION)
+        owner_shares: (u64, u64),
+        
+        /// PSP34 contract minting bet receipts
+        receipt_contract: Option<AccountId>,
+        
+        /// PSP34 contract minting trophies to winners (None disables trophies)
+        trophy_minter: Option<AccountId>,
+        
+        /// Contracts called back after each settlement
+        listeners: Vec<AccountId>,
+        
+        /// Community bankroll backing house-priced bets
+        bankroll: Balance,
+        
+        /// Bankroll shares per staker
+        bankroll_shares: Mapping<AccountId, Balance>,
+        
+        /// Bankroll shares outstanding
+        total_bankroll_shares: Balance,
+        
+        /// When set, the only account that may change odds, edges and strengths
+        governance: Option<AccountId>,
+        
+        /// House edge on head-to-head odds (scaled by PRECISION)
+        match_bet_edge: u64,
+        
+        /// House edge on daily double odds (scaled by PRECISION)
+        daily_double_edge: u64,
+        
+        /// Accounts besides the owner that may co-sign settlement
+        settlement_operators: Vec<AccountId>,
+        
+        /// Operator approvals needed before payouts are credited
+        approval_threshold: u32,
+        
+        /// Race with a settlement proposal and the operators that approved it
+        payout_approvals: Option<(u64, Vec<AccountId>)>,
+        
+        /// House funds held by the contract (pays keeper rewards)
+        treasury: Balance,
+        
+        /// Rounding dust collected into the treasury
+        dust: Balance,
+        
+        /// Reward paid to non-owner callers that advance the lifecycle
+        keeper_reward: Balance,
+        
+        /// Race meetings, indexed by meeting ID
+        meetings: Vec<Meeting>,
+        
+        /// Meeting whose card is still being run
+        active_meeting: Option<u32>,
+        
+        /// Daily double bets, kept across races until settled
+        daily_doubles: Vec<DailyDoubleBet>,
+        
+        /// Stakes held in escrow for unsettled daily doubles
+        daily_double_escrow: Balance,
+        
+        /// Pick 6 pools, indexed by pool ID
+        pick6_pools: Vec<Pick6Pool>,
+        
+        /// Pick 6 tickets across all pools
+        pick6_entries: Vec<Pick6Entry>,
+        
+        /// Unwon Pick 6 funds waiting for the next pool
+        pick6_carryover: Balance,
+        
+        /// Current season number
+        season: u32,
+        
+        /// Wins per horse in the current season
+        season_wins: Vec<u32>,
+        
+        /// Championship points per horse in the current season
+        season_points: Vec<u32>,
+        
+        /// Races run in the current season
+        season_races: u32,
+        
+        /// Final standings of each closed season
+        season_history: Mapping<u32, SeasonStandings>,
+        
+        /// Each bettor's settled exacta results per season
+        bettor_seasons: Mapping<(u32, AccountId), LeaderboardEntry>,
+        
+        /// Top bettors by net winnings per season, best first
+        leaderboards: Mapping<u32, Vec<LeaderboardEntry>>,
+        
+        /// Futures odds per horse (scaled by PRECISION, 0 = not offered)
+        futures_odds: Vec<u64>,
+        
+        /// Futures bets, kept across races until the season is settled
+        futures_bets: Vec<FuturesBet>,
+        
+        /// Stakes held in escrow for unsettled futures
+        futures_escrow: Balance,
+        
+        /// Withdrawable balance per account (deposits and winnings)
+        balances: Mapping<AccountId, Balance>,
+        
+        /// XCM or bridge contracts allowed to credit cross-chain deposits
+        bridges: Vec<AccountId>,
+        
+        /// Cross-chain deposits by message ID, kept for audit
+        cross_chain_deposits: Mapping<Hash, CrossChainDeposit>,
+        
+        /// Withdrawals go through the operator queue instead of paying out instantly
+        custodial_withdrawals: bool,
+        
+        /// Withdrawal requests, indexed by ID
+        withdrawal_requests: Mapping<u32, WithdrawalRequest>,
+        
+        /// Number of withdrawal requests filed
+        withdrawal_count: u32,
+        
+        /// IDs of withdrawal requests waiting on the operator
+        pending_withdrawals: Vec<u32>,
+        
+        /// Fee on withdrawals, routed to the treasury (scaled by PRECISION)
+        withdrawal_fee: u64,
+        
+        /// Most an account can withdraw in any 24 hours (0 for no cap)
+        daily_withdrawal_cap: Balance,
+        
+        /// Withdrawals within the last day: (timestamp, amount)
+        withdrawal_history: Mapping<AccountId, ActivityLog>,
+        
+        /// Last deposit, withdrawal, bet or claim per account
+        last_activity: Mapping<AccountId, u64>,
+        
+        /// Winnings must be claimed before they reach the balance
+        claim_mode: bool,
+        
+        /// Window to claim winnings in claim mode (ms)
+        claim_period: u64,
+        
+        /// Inactivity after which a balance can be swept to the treasury (ms)
+        dormancy_period: u64,
+        
+        /// Winnings waiting to be claimed per account
+        unclaimed: Mapping<AccountId, UnclaimedWinnings>,
+        
+        /// Sum of all internal balances (PSP22 total supply)
+        total_balances: Balance,
+        
+        /// Balance ledger per account, keyed by (account, entry index)
+        ledger: Mapping<(AccountId, u32), LedgerEntry>,
+        
+        /// Number of ledger entries per account
+        ledger_count: Mapping<AccountId, u32>,
+        
+        /// Loyalty points per account
+        loyalty_points: Mapping<AccountId, u128>,
+        
+        /// Points per unit wagered (scaled by PRECISION)
+        points_accrual_rate: u64,
+        
+        /// Points needed for one unit of free-bet credit
+        points_per_credit: u128,
+        
+        /// Free-bet credits per account
+        free_bet_credits: Mapping<AccountId, Balance>,
+        
+        /// Bonus funds per account, spent before the real balance on exacta bets
+        bonus_balances: Mapping<AccountId, Balance>,
+        
+        /// Promo codes by blake2-256 hash of the code
+        promo_codes: Mapping<Hash, PromoCode>,
+        
+        /// Promo codes each account has redeemed
+        promo_redemptions: Mapping<(Hash, AccountId), ()>,
+        
+        /// Turnover per granted credit before credits are released (scaled by PRECISION)
+        wagering_requirement: u64,
+        
+        /// Per account: (amount wagered since grant, turnover required)
+        wagering_progress: Mapping<AccountId, (Balance, Balance)>,
+        
+        /// Self-imposed limits in force
+        betting_limits: Mapping<AccountId, BettingLimits>,
+        
+        /// Raised limits waiting out the delay: (limits, effective_at)
+        pending_limits: Mapping<AccountId, (BettingLimits, u64)>,
+        
+        /// Betting allowance per (account, operator)
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+        
+        /// Next signed-bet nonce per account
+        nonces: Mapping<AccountId, u64>,
+        
+        /// Set while a message that moves value out of the contract runs
+        reentrancy_lock: bool,
+        
+        /// Accounts frozen by the operator
+        frozen: Mapping<AccountId, bool>,
+        
+        /// Only allowlisted accounts can bet
+        whitelist_mode: bool,
+        
+        /// Accounts approved to bet in whitelist mode
+        whitelist: Mapping<AccountId, bool>,
+        
+        /// Only attested accounts can bet or deposit
+        attestation_mode: bool,
+        
+        /// Accounts attested by the compliance operator
+        attested: Mapping<AccountId, bool>,
+        
+        /// Account that manages attestations besides the owner
+        compliance_operator: Option<AccountId>,
+        
+        /// Registry contract consulted for accounts without a local attestation
+        attestation_registry: Option<AccountId>,
+        
+        /// Self-exclusion end timestamp per account
+        excluded_until: Mapping<AccountId, u64>,
+        
+        /// Wagers within the last week: (timestamp, amount)
+        wager_history: Mapping<AccountId, ActivityLog>,
+        
+        /// Winnings credited within the last week: (timestamp, amount)
+        winnings_history: Mapping<AccountId, ActivityLog>,
+        
+        /// Decimals of the betting asset (6 for USDT, 10 or 12 for DOT-likes)
+        asset_decimals: u8,
+        
+        /// Contract quoting the betting asset in fiat
+        price_feed: Option<AccountId>,
+        
+        /// Last quote read from the price feed
+        fiat_rate: Option<FiatRate>,
+        
+        /// Age after which the cached quote is stale (ms)
+        max_price_age: u64,
+        
+        /// Minimum and maximum stake in fiat cents (0 = no limit)
+        fiat_bet_limits: (u128, u128),
+        
+        /// Total pot for current race
+        total_pot: Balance,
+        
+        /// Exacta reward multipliers (stored as flat array for gas efficiency)
+        /// Format: multipliers[first * 6 + second] = multiplier
+        reward_multipliers: Vec<u64>,
+    }
+
+    // ============================================================================
+    // IMPLEMENTATION
+    // ============================================================================
+
+    impl HorseRace {
+        /// Initialize the contract with 6 horses and reward multipliers
+        #[ink(constructor)]
+        pub fn new() -> Self {
             let caller = Self::env().caller();
             let mut contract = Self {
                 owner: caller,
+                version: ContractVersion {
+                    major: CONTRACT_VERSION.0,
+                    minor: CONTRACT_VERSION.1,
+                    patch: CONTRACT_VERSION.2,
+                    storage_version: STORAGE_VERSION,
+                },
+                dev_mode: cfg!(test),
                 race_id: 0,
                 status: RaceStatus::Betting,
                 horses: Vec::new(),
+                horse_forms: Vec::new(),
+                horse_records: Vec::new(),
+                bets: Vec::new(),
+                bet_archive: Mapping::default(),
+                bet_count: 0,
+                account_bets: Mapping::default(),
+                race_bets: Mapping::default(),
+                max_bets_per_race: DEFAULT_MAX_BETS_PER_RACE,
+                max_bets_per_account: DEFAULT_MAX_BETS_PER_ACCOUNT,
+                max_bets_per_block: 0,
+                block_bets: Mapping::default(),
+                stake_per_combination: Mapping::default(),
+                positions_per_combination: Mapping::default(),
+                largest_bet: None,
+                race_results: Vec::new(),
+                race_info: Mapping::default(),
+                tracks: Vec::new(),
+                race_tracks: Mapping::default(),
+                latest_result: RaceResult::default(),
+                payouts: Vec::new(),
+                refunds: Vec::new(),
+                match_bets: Vec::new(),
+                finish_time_line: None,
+                over_under_bets: Vec::new(),
+                current_seed: 0,
+                used_seeds: Mapping::default(),
+                race_start_time: 0,
+                betting_start_time: Self::env().block_timestamp(),
+                betting_cutoff: 0,
+                betting_duration: DEFAULT_BETTING_DURATION,
+                racing_duration: DEFAULT_RACING_DURATION,
+                race_phase: 0,
+                running_times: Vec::new(),
+                challenge_period: 0,
+                race_finish_time: 0,
+                disputed_races: Vec::new(),
+                race_pnl: Mapping::default(),
+                settlement_reports: Mapping::default(),
+                lifetime_pnl: RacePnl::default(),
+                global_stats: GlobalStats::default(),
+                known_bettors: Mapping::default(),
+                unwon_pot_policy: UnwonPotPolicy::Retain,
+                carryover: 0,
+                unwon_pots: Mapping::default(),
+                sponsorships: Mapping::default(),
+                demo_balances: Mapping::default(),
+                demo_bets: Vec::new(),
+                cash_out_factor: DEFAULT_CASH_OUT_FACTOR,
+                lay_offers: Vec::new(),
+                exchange_matches: Vec::new(),
+                exchange_commission: DEFAULT_EXCHANGE_COMMISSION,
+                syndicates: Mapping::default(),
+                syndicate_count: 0,
+                rooms: Mapping::default(),
+                room_count: 0,
+                room_fee: DEFAULT_ROOM_FEE,
+                tournaments: Mapping::default(),
+                tournament_count: 0,
+                tournament_bets: Vec::new(),
+                predictions: Vec::new(),
+                prediction_scores: Mapping::default(),
+                prediction_leaderboards: Mapping::default(),
+                horse_owners: Mapping::default(),
+                owner_shares: (0, 0),
+                receipt_contract: None,
+                trophy_minter: None,
+                listeners: Vec::new(),
+                bankroll: 0,
+                bankroll_shares: Mapping::default(),
+                total_bankroll_shares: 0,
+                governance: None,
+                match_bet_edge: MATCH_BET_EDGE,
+                daily_double_edge: DAILY_DOUBLE_EDGE,
+                settlement_operators: Vec::new(),
+                approval_threshold: 1,
+                payout_approvals: None,
+                treasury: 0,
+                dust: 0,
+                keeper_reward: 0,
+                meetings: Vec::new(),
+                active_meeting: None,
+                daily_doubles: Vec::new(),
+                daily_double_escrow: 0,
+                pick6_pools: Vec::new(),
+                pick6_entries: Vec::new(),
+                pick6_carryover: 0,
+                season: 1,
+                season_wins: vec![0; NUM_HORSES],
+                season_points: vec![0; NUM_HORSES],
+                season_races: 0,
+                season_history: Mapping::default(),
+                bettor_seasons: Mapping::default(),
+                leaderboards: Mapping::default(),
+                futures_odds: vec![0; NUM_HORSES],
+                futures_bets: Vec::new(),
+                futures_escrow: 0,
+                balances: Mapping::default(),
+                bridges: Vec::new(),
+                cross_chain_deposits: Mapping::default(),
+                custodial_withdrawals: false,
+                withdrawal_requests: Mapping::default(),
+                withdrawal_count: 0,
+                pending_withdrawals: Vec::new(),
+                withdrawal_fee: 0,
+                daily_withdrawal_cap: 0,
+                withdrawal_history: Mapping::default(),
+                last_activity: Mapping::default(),
+                claim_mode: false,
+                claim_period: DEFAULT_CLAIM_PERIOD,
+                dormancy_period: DEFAULT_DORMANCY_PERIOD,
+                unclaimed: Mapping::default(),
+                total_balances: 0,
+                ledger: Mapping::default(),
+                ledger_count: Mapping::default(),
+                loyalty_points: Mapping::default(),
+                points_accrual_rate: DEFAULT_POINTS_ACCRUAL_RATE,
+                points_per_credit: DEFAULT_POINTS_PER_CREDIT,
+                free_bet_credits: Mapping::default(),
+                bonus_balances: Mapping::default(),
+                promo_codes: Mapping::default(),
+                promo_redemptions: Mapping::default(),
+                wagering_requirement: DEFAULT_WAGERING_REQUIREMENT,
+                wagering_progress: Mapping::default(),
+                betting_limits: Mapping::default(),
+                pending_limits: Mapping::default(),
+                allowances: Mapping::default(),
+                nonces: Mapping::default(),
+                reentrancy_lock: false,
+                frozen: Mapping::default(),
+                whitelist_mode: false,
+                whitelist: Mapping::default(),
+                attestation_mode: false,
+                attested: Mapping::default(),
+                compliance_operator: None,
+                attestation_registry: None,
+                excluded_until: Mapping::default(),
+                wager_history: Mapping::default(),
+                winnings_history: Mapping::default(),
+                asset_decimals: INTERNAL_DECIMALS,
+                price_feed: None,
+                fiat_rate: None,
+                max_price_age: DEFAULT_MAX_PRICE_AGE,
+                fiat_bet_limits: (0, 0),
+                total_pot: 0,
+                reward_multipliers: Vec::new(),
+            };
+            
+            // Initialize horses
+            contract.initialize_horses();
+            
+            // Initialize reward multipliers
+            contract.initialize_multipliers();
+            
+            contract
+        }
+
+        /// Default constructor
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            Self::new()
+        }
+
+        /// Initialize with a runtime config and hand ownership to `owner`
+        /// Used by the factory to deploy one contract per event or asset
+        #[ink(constructor)]
+        pub fn with_config(owner: AccountId, config: Config) -> Result<Self> {
+            let mut contract = Self::new();
+            contract.apply_config(&config)?;
+            contract.owner = owner;
+            Ok(contract)
+        }
+
+        // ========================================================================
+        // INITIALIZATION HELPERS
+        // ========================================================================
+
+        /// Initialize the 6 horses with their strengths
+        fn initialize_horses(&mut self) {
+            let names = [
+                b"Thunder Bolt".to_vec(),
+                b"Silver Arrow".to_vec(),
+                b"Golden Star".to_vec(),
+                b"Dark Knight".to_vec(),
+                b"Wild Spirit".to_vec(),
+                b"Lucky Charm".to_vec(),
+            ];
+
+            for i in 0..NUM_HORSES {
+                let strength = HORSE_STRENGTHS[i];
+                // S[i] = strength[i] / sum(strength) * PRECISION
+                let normalized = (strength * PRECISION) / TOTAL_STRENGTH;
+                // Bs[i] = 14 + strength[i]
+                let base_speed = 14 + strength;
+
+                self.horses.push(Horse {
+                    id: i as u8,
+                    name: names[i].clone(),
+                    strength,
+                    normalized_strength: normalized,
+                    base_speed,
+                    acceleration: HORSE_ACCELERATION[i],
+                    stamina: HORSE_STAMINA[i],
+                    silks: Vec::new(),
+                    uri: Vec::new(),
+                    surface_affinity: HORSE_SURFACE_AFFINITY[i],
+                });
+
+                self.horse_forms.push(HorseForm {
+                    horse_id: i as u8,
+                    recent_finishes: Vec::new(),
+                    adjusted_strength: strength * PRECISION,
+                });
+
+                self.horse_records.push(HorseRecord {
+                    horse_id: i as u8,
+                    ..Default::default()
+                });
+            }
+        }
+
+        /// Initialize exacta reward multipliers based on specification
+        fn initialize_multipliers(&mut self) {
+            // Initialize 36 slots (6x6 matrix)
+            self.reward_multipliers = vec![0; 36];
+
+            // H[0] combinations
+            self.set_multiplier(0, 5, 60);
+            self.set_multiplier(0, 4, 30);
+            self.set_multiplier(0, 3, 10);
+            self.set_multiplier(0, 2, 3);
+            self.set_multiplier(0, 1, 2);
+
+            // H[1] combinations
+            self.set_multiplier(1, 5, 175);
+            self.set_multiplier(1, 4, 125);
+            self.set_multiplier(1, 3, 20);
+            self.set_multiplier(1, 2, 5);
+            self.set_multiplier(1, 0, 3);  // Reverse of H[0]→H[1]
+
+            // H[2] combinations
+            self.set_multiplier(2, 5, 100);
+            self.set_multiplier(2, 4, 80);
+            self.set_multiplier(2, 3, 8);
+            self.set_multiplier(2, 1, 6);
+            self.set_multiplier(2, 0, 4);
+
+            // H[3] combinations
+            self.set_multiplier(3, 5, 500);
+            self.set_multiplier(3, 4, 250);
+            self.set_multiplier(3, 2, 12);
+            self.set_multiplier(3, 1, 15);
+            self.set_multiplier(3, 0, 8);
+
+            // H[4] combinations
+            self.set_multiplier(4, 5, 1000);
+            self.set_multiplier(4, 3, 300);
+            self.set_multiplier(4, 2, 100);
+            self.set_multiplier(4, 1, 150);
+            self.set_multiplier(4, 0, 40);
+
+            // H[5] combinations (weakest horse winning)
+            self.set_multiplier(5, 4, 1500);
+            self.set_multiplier(5, 3, 600);
+            self.set_multiplier(5, 2, 200);
+            self.set_multiplier(5, 1, 250);
+            self.set_multiplier(5, 0, 80);
+        }
+
+        fn set_multiplier(&mut self, first: u8, second: u8, multiplier: u64) {
+            let index = (first as usize) * 6 + (second as usize);
+            if index < 36 {
+                self.reward_multipliers[index] = multiplier;
+            }
+        }
+
+        fn get_multiplier(&self, first: u8, second: u8) -> u64 {
+            let index = (first as usize) * 6 + (second as usize);
+            if index < 36 {
+                self.reward_multipliers[index]
+            } else {
+                0
+            }
+        }
+
+        // ========================================================================
+        // BETTING FUNCTIONS
+        // ========================================================================
+
+        /// Place an exacta bet paid by the caller with payouts and refunds
+        /// credited to `beneficiary` (a gift); returns the bet id
+        #[ink(message, payable)]
+        pub fn place_gift_bet(&mut self, first_pick: u8, second_pick: u8, beneficiary: AccountId) -> Result<u32> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            let beneficiary = (beneficiary != caller).then_some(beneficiary);
+            self.record_exacta_bet(caller, first_pick, second_pick, amount, false, None, None, 0, beneficiary)
+        }
+
+        /// Place an exacta bet staked from the caller's free-bet credits
+        /// Winnings are paid to the real balance
+        #[ink(message)]
+        pub fn place_free_bet(&mut self, first_pick: u8, second_pick: u8, amount: Balance) -> Result<u32> {
+            let caller = self.env().caller();
+            let credits = self.free_bet_credits.get(caller).unwrap_or(0);
+            if credits < amount {
+                return Err(Error::InsufficientCredits);
+            }
+
+            let bet_id = self.record_exacta_bet(caller, first_pick, second_pick, amount, true, None, None, 0, None)?;
+            self.free_bet_credits.insert(caller, &Self::sub(credits, amount)?);
+            Ok(bet_id)
+        }
+
+        /// Place an exacta bet signed off-chain by the bettor
+        /// Any relayer can submit it; the stake comes from the bettor's balance.
+        /// The signature covers this contract's address and the SCALE-encoded bet.
+        #[ink(message)]
+        pub fn place_signed_bet(&mut self, bet: SignedBet, signature: BetSignature) -> Result<u32> {
+            let nonce = self.nonces.get(bet.bettor).unwrap_or(0);
+            if bet.nonce != nonce {
+                return Err(Error::InvalidNonce);
+            }
+            self.verify_bet_signature(&bet, &signature)?;
+            self.nonces.insert(bet.bettor, &(nonce + 1));
+
+            let bonus = self.debit_stake(bet.bettor, bet.amount)?;
+            self.record_exacta_bet(bet.bettor, bet.first_pick, bet.second_pick, bet.amount, false, None, None, bonus, None)
+        }
+
+        /// Get the next signed-bet nonce of an account
+        #[ink(message)]
+        pub fn get_nonce(&self, account: AccountId) -> u64 {
+            self.nonces.get(account).unwrap_or(0)
+        }
+
+        /// Bytes a bettor signs to authorize `bet`
+        fn signed_bet_message(&self, bet: &SignedBet) -> Vec<u8> {
+            use ink::scale::Encode;
+            (self.env().account_id(), bet).encode()
+        }
+
+        /// Check the signature was made by the bettor's key
+        /// Sr25519 keys are the account itself; ECDSA accounts are the
+        /// blake2-256 hash of the compressed public key
+        fn verify_bet_signature(&self, bet: &SignedBet, signature: &BetSignature) -> Result<()> {
+            use ink::env::hash::{Blake2x256, CryptoHash};
+
+            let message = self.signed_bet_message(bet);
+            let verified = match signature {
+                BetSignature::Sr25519(signature) => {
+                    let public_key: &[u8; 32] = bet.bettor.as_ref();
+                    ink::env::sr25519_verify(signature, &message, public_key).is_ok()
+                }
+                BetSignature::Ecdsa(signature) => {
+                    let mut message_hash = [0u8; 32];
+                    Blake2x256::hash(&message, &mut message_hash);
+                    match self.env().ecdsa_recover(signature, &message_hash) {
+                        Ok(public_key) => {
+                            let mut account = [0u8; 32];
+                            Blake2x256::hash(&public_key, &mut account);
+                            AccountId::from(account) == bet.bettor
+                        }
+                        Err(_) => false,
+                    }
+                }
+            };
+
+            if !verified {
+                return Err(Error::InvalidSignature);
+            }
+            Ok(())
+        }
+
+        /// Fail unless the race is taking bets and the cutoff before its start
+        /// hasn't been reached
+        fn ensure_betting_open(&self) -> Result<()> {
+            if self.status != RaceStatus::Betting {
+                return Err(Error::BettingClosed);
+            }
+            let closes_at = self.betting_start_time + self.betting_duration;
+            let now = self.env().block_timestamp();
+            if now > closes_at {
+                return Err(Error::BettingClosed);
+            }
+            if now.saturating_add(self.betting_cutoff) > closes_at {
+                return Err(Error::BettingCutoff);
+            }
+            Ok(())
+        }
+
+        /// Fail unless betting is open and the exacta is valid and offered
+        fn ensure_exacta_open(&self, first_pick: u8, second_pick: u8) -> Result<()> {
+            // Validate race status and betting window
+            self.ensure_betting_open()?;
+            self.ensure_exacta_offered(first_pick, second_pick)
+        }
+
+        /// Fail unless the picks are two different horses on an offered exacta
+        fn ensure_exacta_offered(&self, first_pick: u8, second_pick: u8) -> Result<()> {
+            if first_pick >= NUM_HORSES as u8 || second_pick >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if first_pick == second_pick {
+                return Err(Error::SameHorsePicked);
+            }
+            if self.get_multiplier(first_pick, second_pick) == 0 {
+                return Err(Error::CombinationNotOffered);
+            }
+            Ok(())
+        }
+
+        /// Add the transferred value to one of the caller's open exacta bets
+        /// Runs the same limit checks as a new bet and keeps the bet list compact
+        #[ink(message, payable)]
+        pub fn top_up_bet(&mut self, bet_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            let first_id = self.bet_id(0);
+            if bet_id < first_id || bet_id >= self.bet_count {
+                return Err(Error::BetNotFound);
+            }
+            let bet_index = bet_id - first_id;
+            let bet = self.bets[bet_index as usize].clone();
+            self.ensure_exacta_open(bet.first_pick, bet.second_pick)?;
+            if bet.bettor != caller {
+                return Err(Error::NotBetOwner);
+            }
+            if bet.free_bet
+                || bet.receipt.is_some()
+                || bet.syndicate.is_some()
+                || bet.cash_out.is_some()
+                || bet.in_running_odds.is_some()
+            {
+                return Err(Error::CannotTopUp);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            self.top_up_position(caller, self.race_id + 1, bet_index, amount, 0)?;
+            Ok(())
+        }
+
+        /// Validate and record an exacta bet, returning its id
+        /// Syndicate bets skip the wager checks, members passed them when joining
+        #[allow(clippy::too_many_arguments)]
+        fn record_exacta_bet(
+            &mut self,
+            caller: AccountId,
+            first_pick: u8,
+            second_pick: u8,
+            amount: Balance,
+            free_bet: bool,
+            syndicate: Option<u32>,
+            in_running_odds: Option<u64>,
+            bonus_stake: Balance,
+            beneficiary: Option<AccountId>,
+        ) -> Result<u32> {
+            // In-running bets are taken during the race, which already has its ID
+            let race_id = match in_running_odds {
+                Some(_) => self.race_id,
+                None => {
+                    self.ensure_exacta_open(first_pick, second_pick)?;
+                    self.race_id + 1
+                }
+            };
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            // Repeat bets on an exacta top up the bettor's open position.
+            // Bets with receipts, syndicates or locked odds stay separate.
+            let mut own_bets = self.race_bets.get((race_id, caller)).unwrap_or_default();
+            let position = own_bets.iter().copied().find(|&bet_index| {
+                let Some(bet) = self.bets.get(bet_index as usize) else {
+                    return false;
+                };
+                bet.bettor == caller
+                    && bet.first_pick == first_pick
+                    && bet.second_pick == second_pick
+                    && bet.free_bet == free_bet
+                    && bet.receipt.is_none()
+                    && bet.syndicate.is_none()
+                    && bet.cash_out.is_none()
+                    && bet.in_running_odds.is_none()
+                    && bet.beneficiary == beneficiary
+            });
+            let mergeable = syndicate.is_none() && in_running_odds.is_none() && self.receipt_contract.is_none();
+            if let (Some(bet_index), true) = (position, mergeable) {
+                return self.top_up_position(caller, race_id, bet_index, amount, bonus_stake);
+            }
+
+            // New positions count against the race and account caps
+            if self.max_bets_per_race > 0 && self.bets.len() as u32 >= self.max_bets_per_race {
+                return Err(Error::RaceBetLimitReached);
+            }
+            if self.max_bets_per_account > 0 && own_bets.len() as u32 >= self.max_bets_per_account {
+                return Err(Error::AccountBetLimitReached);
+            }
+
+            // Create bet, with a receipt NFT when a receipt contract is set
+            let bet_id = self.bet_count;
+            let receipt = match syndicate {
+                Some(_) => None,
+                None => self.mint_receipt(beneficiary.unwrap_or(caller), bet_id)?,
+            };
+            let bet = ExactaBet {
+                bettor: caller,
+                amount,
+                first_pick,
+                second_pick,
+                timestamp: self.env().block_timestamp(),
+                free_bet,
+                receipt,
+                syndicate,
+                cash_out: None,
+                in_running_odds,
+                bonus_stake,
+                additions: 1,
+                settled: false,
+                beneficiary,
+            };
+
+            // Archive the bet and index it by account
+            self.bet_archive.insert(bet_id, &BetRecord {
+                id: bet_id,
+                race_id,
+                bet: bet.clone(),
+            });
+            self.bet_count += 1;
+            let mut account_bets = self.account_bets.get(caller).unwrap_or_default();
+            account_bets.push(bet_id);
+            self.account_bets.insert(caller, &account_bets);
+
+            let bet_index = self.bets.len() as u32;
+            self.bets.push(bet);
+            own_bets.push(bet_index);
+            self.race_bets.insert((race_id, caller), &own_bets);
+            self.finish_exacta_bet(caller, race_id, bet_index, first_pick, second_pick, amount)?;
+            Ok(bet_id)
+        }
+
+        /// Add a stake to an open position, returning the bet's id
+        fn top_up_position(
+            &mut self,
+            caller: AccountId,
+            race_id: u64,
+            bet_index: u32,
+            amount: Balance,
+            bonus_stake: Balance,
+        ) -> Result<u32> {
+            let now = self.env().block_timestamp();
+            let bet = &mut self.bets[bet_index as usize];
+            bet.amount = Self::add(bet.amount, amount)?;
+            bet.bonus_stake = Self::add(bet.bonus_stake, bonus_stake)?;
+            bet.additions += 1;
+            bet.timestamp = now;
+            let bet = bet.clone();
+            let bet_id = self.bet_id(bet_index);
+            if let Some(mut record) = self.bet_archive.get(bet_id) {
+                record.bet = bet.clone();
+                self.bet_archive.insert(bet_id, &record);
+            }
+            self.finish_exacta_bet(caller, race_id, bet_index, bet.first_pick, bet.second_pick, amount)?;
+            Ok(bet_id)
+        }
+
+        /// Global id of a bet in the current race's book
+        fn bet_id(&self, bet_index: u32) -> u32 {
+            self.bet_count - self.bets.len() as u32 + bet_index
+        }
+
+        /// Add a new or topped-up exacta stake to the pot and announce it
+        /// Syndicate stakes were already counted as wagers when members joined
+        fn finish_exacta_bet(
+            &mut self,
+            caller: AccountId,
+            race_id: u64,
+            bet_index: u32,
+            first_pick: u8,
+            second_pick: u8,
+            amount: Balance,
+        ) -> Result<()> {
+            self.total_pot = Self::add(self.total_pot, amount)?;
+            let bet = &self.bets[bet_index as usize];
+            let (free_bet, new_position) = (bet.free_bet, bet.additions == 1);
+            if !free_bet {
+                self.add_combination_stake(first_pick, second_pick, amount)?;
+            }
+            let (positions, free_staked) = self.positions_per_combination.get((first_pick, second_pick)).unwrap_or_default();
+            self.positions_per_combination.insert((first_pick, second_pick), &(
+                positions + new_position as u32,
+                if free_bet { Self::add(free_staked, amount)? } else { free_staked },
+            ));
+            let largest = self.largest_bet.map(|index| self.bets[index as usize].amount).unwrap_or(0);
+            if self.bets[bet_index as usize].amount > largest {
+                self.largest_bet = Some(bet_index);
+            }
+            self.last_activity.insert(caller, &self.env().block_timestamp());
+            if self.bets[bet_index as usize].syndicate.is_none() {
+                self.on_wager(caller, amount)?;
+            }
+
+            // Emit event
+            self.env().emit_event(BetPlaced {
+                race_id,
+                bettor: caller,
+                bet_id: Some(self.bet_id(bet_index)),
+                bet_index,
+                first_pick,
+                second_pick,
+                amount,
+                demo: false,
+            });
+
+            Ok(())
+        }
+
+        /// Mint a receipt NFT for a bet on the receipt contract, if one is set
+        fn mint_receipt(&mut self, bettor: AccountId, bet_id: u32) -> Result<Option<(AccountId, u32)>> {
+            use ink::codegen::TraitCallBuilder;
+            let Some(contract) = self.receipt_contract else {
+                return Ok(None);
+            };
+            let mut receipts: ink::contract_ref!(BetReceipts) = contract.into();
+            match receipts.call_mut().mint(bettor, ReceiptId::U32(bet_id)).try_invoke() {
+                Ok(Ok(Ok(()))) => Ok(Some((contract, bet_id))),
+                _ => Err(Error::ReceiptMintFailed),
+            }
+        }
+
+        /// Account a bet settles to: the current receipt holder, else the
+        /// beneficiary, else the bettor
+        fn receipt_holder(&self, bet: &ExactaBet) -> AccountId {
+            use ink::codegen::TraitCallBuilder;
+            let payee = bet.beneficiary.unwrap_or(bet.bettor);
+            let Some((contract, bet_id)) = bet.receipt else {
+                return payee;
+            };
+            let receipts: ink::contract_ref!(BetReceipts) = contract.into();
+            match receipts.call().owner_of(ReceiptId::U32(bet_id)).try_invoke() {
+                Ok(Ok(Some(holder))) => holder,
+                _ => payee,
+            }
+        }
+
+        /// Set the PSP34 contract that mints bet receipts; None stops minting
+        /// (owner only). Bets already placed keep their receipts.
+        #[ink(message)]
+        pub fn set_receipt_contract(&mut self, contract: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.receipt_contract = contract;
+            Ok(())
+        }
+
+        /// Get the bet receipt contract
+        #[ink(message)]
+        pub fn get_receipt_contract(&self) -> Option<AccountId> {
+            self.receipt_contract
+        }
+
+        /// Get the open paid stake on an exacta and the house's liability if it
+        /// comes in at the table multiplier: (stake, liability)
+        #[ink(message)]
+        pub fn get_combination_stake(&self, first_pick: u8, second_pick: u8) -> (Balance, Balance) {
+            let stake = self.stake_per_combination.get((first_pick, second_pick)).unwrap_or(0);
+            let multiplier = self.get_multiplier(first_pick, second_pick) as u128;
+            (stake, stake.saturating_mul(multiplier))
+        }
+
+        /// Preview settlement if an exacta came in: (total paid, winning positions)
+        /// Read from the per-combination maps at the table multiplier, carryover
+        /// included; in-running bets at locked odds are not reflected
+        #[ink(message)]
+        pub fn preview_payouts(&self, first: u8, second: u8) -> (Balance, u32) {
+            let (positions, free_staked) = self.positions_per_combination.get((first, second)).unwrap_or_default();
+            if positions == 0 {
+                return (0, 0);
+            }
+            let staked = self.stake_per_combination.get((first, second)).unwrap_or(0).saturating_add(free_staked);
+            let multiplier = self.get_multiplier(first, second) as u128;
+            (staked.saturating_mul(multiplier).saturating_add(self.carryover), positions)
+        }
+
+        /// Add to the open paid stake on an exacta
+        fn add_combination_stake(&mut self, first_pick: u8, second_pick: u8, amount: Balance) -> Result<()> {
+            let staked = self.stake_per_combination.get((first_pick, second_pick)).unwrap_or(0);
+            self.stake_per_combination
+                .insert((first_pick, second_pick), &Self::add(staked, amount)?);
+            Ok(())
+        }
+
+        /// Get stakes and bet counts per exacta combination for the current race
+        /// Lists all 30 combinations, including those without bets
+        #[ink(message)]
+        pub fn get_pool_breakdown(&self) -> Vec<PoolShare> {
+            let mut breakdown: Vec<PoolShare> = Vec::new();
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if first != second {
+                        breakdown.push(PoolShare { first, second, ..Default::default() });
+                    }
+                }
+            }
+
+            for bet in self.bets.iter().filter(|bet| bet.cash_out.is_none()) {
+                if let Some(share) = breakdown
+                    .iter_mut()
+                    .find(|share| share.first == bet.first_pick && share.second == bet.second_pick)
+                {
+                    share.total_staked = share.total_staked.saturating_add(bet.amount);
+                    share.bet_count += bet.additions;
+                }
+            }
+
+            breakdown
+        }
+
+        /// Get the most and least backed exactas, the pot and the largest bet
+        /// Read from the per-combination stake map, without scanning bets
+        #[ink(message)]
+        pub fn get_market_summary(&self) -> MarketSummary {
+            let mut summary = MarketSummary {
+                total_pot: self.total_pot,
+                ..Default::default()
+            };
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if first == second || self.get_multiplier(first, second) == 0 {
+                        continue;
+                    }
+                    let stake = self.stake_per_combination.get((first, second)).unwrap_or(0);
+                    if stake > summary.most_backed_stake {
+                        summary.most_backed = Some((first, second));
+                        summary.most_backed_stake = stake;
+                    }
+                    if summary.least_backed.is_none() || stake < summary.least_backed_stake {
+                        summary.least_backed = Some((first, second));
+                        summary.least_backed_stake = stake;
+                    }
+                }
+            }
+            if let Some(bet) = self.largest_bet.and_then(|index| self.bets.get(index as usize)) {
+                summary.largest_bet = bet.amount;
+                summary.largest_bettor = Some(bet.bettor);
+            }
+            summary
+        }
+
+        /// Get stakes and bet counts on each horse as first and as second pick
+        /// Folded from the pool breakdown, one entry per horse
+        #[ink(message)]
+        pub fn get_horse_popularity(&self) -> Vec<HorsePopularity> {
+            let mut popularity: Vec<HorsePopularity> = (0..NUM_HORSES as u8)
+                .map(|horse_id| HorsePopularity { horse_id, ..Default::default() })
+                .collect();
+            for share in self.get_pool_breakdown() {
+                let first = &mut popularity[share.first as usize];
+                first.first_stake = first.first_stake.saturating_add(share.total_staked);
+                first.first_bets += share.bet_count;
+                let second = &mut popularity[share.second as usize];
+                second.second_stake = second.second_stake.saturating_add(share.total_staked);
+                second.second_bets += share.bet_count;
+            }
+            popularity
+        }
+
+        /// Get all bets for current race
+        #[ink(message)]
+        pub fn get_bets(&self) -> Vec<ExactaBet> {
+            self.bets.clone()
+        }
+
+        /// Get the positions of an account's bets in a race's bet list
+        #[ink(message)]
+        pub fn get_race_bet_indices(&self, race_id: u64, account: AccountId) -> Vec<u32> {
+            self.race_bets.get((race_id, account)).unwrap_or_default()
+        }
+
+        /// Get an account's winning bets in the latest finished race, looked up
+        /// through its own bets: (bet index, payout at the bet's odds)
+        #[ink(message)]
+        pub fn get_winning_bets(&self, account: AccountId) -> Vec<(u32, Balance)> {
+            if self.status != RaceStatus::Finished && self.status != RaceStatus::Closed {
+                return Vec::new();
+            }
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+            self.get_race_bet_indices(self.race_id, account)
+                .into_iter()
+                .filter_map(|bet_index| {
+                    let bet = self.bets.get(bet_index as usize)?;
+                    if bet.bettor != account
+                        || bet.cash_out.is_some()
+                        || (bet.first_pick, bet.second_pick) != winning_exacta
+                    {
+                        return None;
+                    }
+                    let payout = match bet.in_running_odds {
+                        Some(odds) => Self::apply_odds(bet.amount, odds).ok()?,
+                        None => bet.amount.checked_mul(multiplier as u128)?,
+                    };
+                    Some((bet_index, payout))
+                })
+                .collect()
+        }
+
+        /// Get an account's open positions on an exacta in the current race
+        /// Each carries the summed stake and how many bets were merged into it
+        #[ink(message)]
+        pub fn get_positions(&self, account: AccountId, first_pick: u8, second_pick: u8) -> Vec<ExactaBet> {
+            self.get_race_bet_indices(self.bets_race_id(), account)
+                .into_iter()
+                .filter_map(|bet_index| self.bets.get(bet_index as usize))
+                .filter(|bet| {
+                    bet.bettor == account
+                        && bet.first_pick == first_pick
+                        && bet.second_pick == second_pick
+                        && bet.cash_out.is_none()
+                })
+                .cloned()
+                .collect()
+        }
+
+        /// Race the current bet list belongs to: the next race while betting,
+        /// the running or settled one after that
+        fn bets_race_id(&self) -> u64 {
+            match self.status {
+                RaceStatus::Betting => self.race_id + 1,
+                _ => self.race_id,
+            }
+        }
+
+        /// Price to cash out a bet now: its expected return at the current
+        /// odds and model probability, capped at the stake, times the cash-out factor
+        #[ink(message)]
+        pub fn get_cash_out_price(&self, bet_index: u32) -> Option<Balance> {
+            let bet = self.bets.get(bet_index as usize)?;
+            if bet.cash_out.is_some() || bet.free_bet || bet.syndicate.is_some() {
+                return None;
+            }
+            let multiplier = self.get_multiplier(bet.first_pick, bet.second_pick) as u128;
+            let probability = self.calculate_exacta_probability(bet.first_pick, bet.second_pick) as u128;
+            let value = Self::mul_div(bet.amount, multiplier * probability, PRECISION as u128).ok()?;
+            Self::apply_odds(value.min(bet.amount), self.cash_out_factor).ok()
+        }
+
+        /// Settle an open bet early at the cash-out price, credited straight
+        /// to the balance. The bet is void and drops out of settlement.
+        #[ink(message)]
+        pub fn cash_out(&mut self, bet_index: u32) -> Result<Balance> {
+            let caller = self.env().caller();
+            let bet = self.bets.get(bet_index as usize).cloned().ok_or(Error::BetNotFound)?;
+            self.ensure_exacta_open(bet.first_pick, bet.second_pick)?;
+            if self.receipt_holder(&bet) != caller {
+                return Err(Error::NotBetOwner);
+            }
+            let amount = match self.get_cash_out_price(bet_index) {
+                Some(amount) if self.cash_out_factor > 0 && amount > 0 => amount,
+                _ => return Err(Error::CashOutUnavailable),
+            };
+
+            self.bets[bet_index as usize].cash_out = Some(amount);
+            let bet_id = self.bet_id(bet_index);
+            if let Some(mut record) = self.bet_archive.get(bet_id) {
+                record.bet.cash_out = Some(amount);
+                self.bet_archive.insert(bet_id, &record);
+            }
+            self.remove_open_stake(&bet)?;
+            self.record_winnings(caller, amount);
+            let bonus = Self::mul_div(amount, bet.bonus_stake, bet.amount)?;
+            self.credit_bonus(caller, bonus)?;
+            self.credit_balance(caller, amount - bonus, LedgerReason::CashOut)?;
+
+            self.env().emit_event(CashedOut {
+                race_id: self.race_id + 1,
+                bettor: caller,
+                bet_id,
+                bet_index,
+                stake: bet.amount,
+                amount,
+            });
+
+            Ok(amount)
+        }
+
+        /// Refund an open exacta bet by id before the race starts (owner only)
+        /// `code` says why (user error, support case, ...) and is emitted with
+        /// the refund. The bet is closed like a cash-out at its full stake.
+        #[ink(message)]
+        pub fn courtesy_refund(&mut self, bet_id: u32, code: u16) -> Result<Balance> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.status != RaceStatus::Betting {
+                return Err(Error::RaceNotInBettingPhase);
+            }
+            let first_id = self.bet_id(0);
+            if bet_id < first_id || bet_id >= self.bet_count {
+                return Err(Error::BetNotFound);
+            }
+            let bet_index = (bet_id - first_id) as usize;
+            let bet = self.bets[bet_index].clone();
+            if bet.cash_out.is_some() {
+                return Err(Error::BetAlreadyClosed);
+            }
+
+            self.bets[bet_index].cash_out = Some(bet.amount);
+            if let Some(mut record) = self.bet_archive.get(bet_id) {
+                record.bet.cash_out = Some(bet.amount);
+                self.bet_archive.insert(bet_id, &record);
+            }
+            self.remove_open_stake(&bet)?;
+
+            let payee = self.receipt_holder(&bet);
+            if bet.free_bet {
+                let credits = self.free_bet_credits.get(bet.bettor).unwrap_or(0);
+                self.free_bet_credits.insert(bet.bettor, &Self::add(credits, bet.amount)?);
+            } else {
+                match bet.syndicate {
+                    Some(syndicate_id) => self.split_syndicate(syndicate_id, bet.amount, LedgerReason::Refund)?,
+                    None => self.credit_bet(&bet, payee, bet.amount, LedgerReason::Refund)?,
+                }
+            }
+            self.emit_refund(payee, bet.amount, RefundReason::Courtesy { bet_id, code });
+            Ok(bet.amount)
+        }
+
+        /// Take a bet leaving the book before the race out of the pot and the
+        /// per-combination aggregates
+        fn remove_open_stake(&mut self, bet: &ExactaBet) -> Result<()> {
+            let exacta = (bet.first_pick, bet.second_pick);
+            self.total_pot = Self::sub(self.total_pot, bet.amount)?;
+            let (positions, mut free_staked) = self.positions_per_combination.get(exacta).unwrap_or_default();
+            if bet.free_bet {
+                free_staked = Self::sub(free_staked, bet.amount)?;
+            } else {
+                let staked = self.stake_per_combination.get(exacta).unwrap_or(0);
+                self.stake_per_combination.insert(exacta, &Self::sub(staked, bet.amount)?);
+            }
+            self.positions_per_combination.insert(exacta, &(positions.saturating_sub(1), free_staked));
+            Ok(())
+        }
+
+        /// Set the cash-out factor (scaled by PRECISION, 0 disables; governance or owner)
+        #[ink(message)]
+        pub fn set_cash_out_factor(&mut self, factor: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if factor > PRECISION {
+                return Err(Error::InvalidConfig);
+            }
+            self.cash_out_factor = factor;
+            Ok(())
+        }
+
+        /// Get the cash-out factor
+        #[ink(message)]
+        pub fn get_cash_out_factor(&self) -> u64 {
+            self.cash_out_factor
+        }
+
+        /// Get an account's past and current bets, oldest first
+        #[ink(message)]
+        pub fn get_bets_by_account(&self, account: AccountId, offset: u32, limit: u32) -> Vec<BetRecord> {
+            self.account_bets
+                .get(account)
+                .unwrap_or_default()
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .filter_map(|id| self.bet_archive.get(id))
+                .collect()
+        }
+
+        /// Get a bet by its id, in any race
+        #[ink(message)]
+        pub fn get_bet_by_id(&self, bet_id: u32) -> Option<BetRecord> {
+            self.bet_archive.get(bet_id)
+        }
+
+        /// Get number of bets an account has placed
+        #[ink(message)]
+        pub fn get_bet_count_by_account(&self, account: AccountId) -> u32 {
+            self.account_bets.get(account).unwrap_or_default().len() as u32
+        }
+
+        /// Get total pot for current race
+        #[ink(message)]
+        pub fn get_total_pot(&self) -> Balance {
+            self.total_pot
+        }
+
+        // ========================================================================
+        // SYNDICATES
+        // ========================================================================
+
+        /// Open a syndicate on an exacta for the current race
+        #[ink(message)]
+        pub fn create_syndicate(&mut self, first_pick: u8, second_pick: u8) -> Result<u32> {
+            self.ensure_exacta_open(first_pick, second_pick)?;
+            let creator = self.env().caller();
+            let syndicate_id = self.syndicate_count;
+            let race_id = self.race_id + 1;
+            self.syndicates.insert(syndicate_id, &Syndicate {
+                id: syndicate_id,
+                race_id,
+                creator,
+                first_pick,
+                second_pick,
+                members: Vec::new(),
+                total: 0,
+                placed: false,
+            });
+            self.syndicate_count += 1;
+
+            self.env().emit_event(SyndicateCreated {
+                syndicate_id,
+                creator,
+                race_id,
+                first_pick,
+                second_pick,
+            });
+
+            Ok(syndicate_id)
+        }
+
+        /// Contribute a stake from the caller's balance to a syndicate
+        #[ink(message)]
+        pub fn join_syndicate(&mut self, syndicate_id: u32, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let mut syndicate = self.open_syndicate(syndicate_id)?;
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.ensure_not_frozen(caller)?;
+            self.ensure_whitelisted(caller)?;
+            self.ensure_attested(caller)?;
+            self.ensure_not_excluded(caller)?;
+
+            let full = syndicate.members.len() >= MAX_SYNDICATE_MEMBERS;
+            match syndicate.members.iter_mut().find(|(member, _)| *member == caller) {
+                Some((_, stake)) => *stake = Self::add(*stake, amount)?,
+                None if full => return Err(Error::SyndicateFull),
+                None => syndicate.members.push((caller, amount)),
+            }
+            syndicate.total = Self::add(syndicate.total, amount)?;
+            self.debit_balance(caller, amount, LedgerReason::Bet)?;
+            self.syndicates.insert(syndicate_id, &syndicate);
+            Ok(())
+        }
+
+        /// Withdraw the caller's whole stake from a syndicate whose bet
+        /// hasn't been placed, back to their balance
+        #[ink(message)]
+        pub fn leave_syndicate(&mut self, syndicate_id: u32) -> Result<Balance> {
+            let caller = self.env().caller();
+            let mut syndicate = self.syndicates.get(syndicate_id).ok_or(Error::SyndicateNotFound)?;
+            if syndicate.placed {
+                return Err(Error::SyndicateClosed);
+            }
+            let position = syndicate
+                .members
+                .iter()
+                .position(|(member, _)| *member == caller)
+                .ok_or(Error::NotSyndicateMember)?;
+
+            let (_, stake) = syndicate.members.remove(position);
+            syndicate.total = Self::sub(syndicate.total, stake)?;
+            self.syndicates.insert(syndicate_id, &syndicate);
+            self.credit_balance(caller, stake, LedgerReason::Refund)?;
+            self.emit_refund(caller, stake, RefundReason::Cancelled);
+            Ok(stake)
+        }
+
+        /// Place the syndicate's pooled stake as one exacta bet (creator only)
+        /// Every member's stake goes through their own wager checks
+        #[ink(message)]
+        pub fn place_syndicate_bet(&mut self, syndicate_id: u32) -> Result<()> {
+            let mut syndicate = self.open_syndicate(syndicate_id)?;
+            if self.env().caller() != syndicate.creator {
+                return Err(Error::NotSyndicateCreator);
+            }
+
+            for (member, stake) in &syndicate.members {
+                self.on_wager(*member, *stake)?;
+            }
+            self.record_exacta_bet(
+                syndicate.creator,
+                syndicate.first_pick,
+                syndicate.second_pick,
+                syndicate.total,
+                false,
+                Some(syndicate_id),
+                None,
+                0,
+                None,
+            )?;
+            syndicate.placed = true;
+            self.syndicates.insert(syndicate_id, &syndicate);
+
+            self.env().emit_event(SyndicateBetPlaced {
+                syndicate_id,
+                total: syndicate.total,
+                members: syndicate.members.len() as u32,
+            });
+
+            Ok(())
+        }
+
+        /// Get a syndicate
+        #[ink(message)]
+        pub fn get_syndicate(&self, syndicate_id: u32) -> Option<Syndicate> {
+            self.syndicates.get(syndicate_id)
+        }
+
+        /// Load a syndicate that can still take stakes
+        fn open_syndicate(&self, syndicate_id: u32) -> Result<Syndicate> {
+            let syndicate = self.syndicates.get(syndicate_id).ok_or(Error::SyndicateNotFound)?;
+            if syndicate.placed || syndicate.race_id != self.race_id + 1 {
+                return Err(Error::SyndicateClosed);
+            }
+            self.ensure_exacta_open(syndicate.first_pick, syndicate.second_pick)?;
+            Ok(syndicate)
+        }
+
+        /// Credit a syndicate's payout or refund to its members pro rata
+        /// Rounding dust goes to the treasury
+        fn split_syndicate(&mut self, syndicate_id: u32, amount: Balance, reason: LedgerReason) -> Result<()> {
+            let syndicate = self.syndicates.get(syndicate_id).ok_or(Error::SyndicateNotFound)?;
+            let mut paid: Balance = 0;
+            for (member, stake) in &syndicate.members {
+                let share = Self::mul_div(amount, *stake, syndicate.total)?;
+                paid = Self::add(paid, share)?;
+                self.credit_winnings(*member, share, reason)?;
+            }
+            self.collect_dust(Self::sub(amount, paid)?)
+        }
+
+        // ========================================================================
+        // PRIVATE RACE ROOMS
+        // ========================================================================
+
+        /// Open a private race room, paying the room fee from the caller's balance
+        /// `seed_hash` commits to the seed that will run the race; only `members`
+        /// can bet, and the creator can't bet in their own room
+        #[ink(message)]
+        pub fn create_room(&mut self, seed_hash: Hash, betting_duration: u64, members: Vec<AccountId>) -> Result<u32> {
+            let creator = self.env().caller();
+            if betting_duration == 0 {
+                return Err(Error::InvalidDuration);
+            }
+            if members.len() > MAX_ROOM_MEMBERS {
+                return Err(Error::RoomFull);
+            }
+            self.ensure_not_frozen(creator)?;
+            let fee = self.room_fee;
+            self.debit_balance(creator, fee, LedgerReason::RoomFee)?;
+            self.treasury = Self::add(self.treasury, fee)?;
+
+            let room_id = self.room_count;
+            self.rooms.insert(room_id, &RaceRoom {
+                id: room_id,
+                creator,
+                seed_hash,
+                betting_start_time: self.env().block_timestamp(),
+                betting_duration,
+                members,
                 bets: Vec::new(),
-                race_results: Vec::new(),
-                latest_result: RaceResult::default(),
-                payouts: Vec::new(),
-                current_seed: 0,
-                race_start_time: 0,
-                betting_start_time: Self::env().block_timestamp(),
                 total_pot: 0,
-                reward_multipliers: Vec::new(),
+                status: RaceStatus::Betting,
+                result: None,
+            });
+            self.room_count += 1;
+
+            self.env().emit_event(RoomCreated {
+                room_id,
+                creator,
+                seed_hash,
+                betting_duration,
+                fee,
+            });
+
+            Ok(room_id)
+        }
+
+        /// Invite more accounts to a room (creator only)
+        #[ink(message)]
+        pub fn add_room_members(&mut self, room_id: u32, accounts: Vec<AccountId>) -> Result<()> {
+            let mut room = self.open_room(room_id)?;
+            if self.env().caller() != room.creator {
+                return Err(Error::NotRoomCreator);
+            }
+            for account in accounts {
+                if !room.members.contains(&account) {
+                    room.members.push(account);
+                }
+            }
+            if room.members.len() > MAX_ROOM_MEMBERS {
+                return Err(Error::RoomFull);
+            }
+            self.rooms.insert(room_id, &room);
+            Ok(())
+        }
+
+        /// Bet on an exacta in a room, staked from the caller's balance
+        /// Room bets go through the same wager checks as the main race
+        #[ink(message)]
+        pub fn place_room_bet(&mut self, room_id: u32, first_pick: u8, second_pick: u8, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let mut room = self.open_room(room_id)?;
+            if self.env().block_timestamp() > room.betting_start_time + room.betting_duration {
+                return Err(Error::BettingClosed);
+            }
+            if caller == room.creator || !room.members.contains(&caller) {
+                return Err(Error::NotRoomMember);
+            }
+            self.ensure_exacta_offered(first_pick, second_pick)?;
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            if room.bets.len() >= MAX_ROOM_BETS {
+                return Err(Error::RoomFull);
+            }
+
+            self.on_wager(caller, amount)?;
+            self.debit_balance(caller, amount, LedgerReason::Bet)?;
+            room.bets.push(RoomBet {
+                bettor: caller,
+                first_pick,
+                second_pick,
+                amount,
+            });
+            room.total_pot = Self::add(room.total_pot, amount)?;
+            self.rooms.insert(room_id, &room);
+            self.last_activity.insert(caller, &self.env().block_timestamp());
+
+            self.env().emit_event(RoomBetPlaced {
+                room_id,
+                bettor: caller,
+                first_pick,
+                second_pick,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Reveal the committed seed and run the room's race (creator only)
+        /// Uses the main race's engine and weights; winners are paid at the
+        /// multiplier table and bets on a horse that did not finish are refunded
+        #[ink(message)]
+        pub fn run_room(&mut self, room_id: u32, seed: u64) -> Result<RaceResult> {
+            use ink::env::hash::{Blake2x256, CryptoHash};
+
+            let mut room = self.open_room(room_id)?;
+            if self.env().caller() != room.creator {
+                return Err(Error::NotRoomCreator);
+            }
+            if self.env().block_timestamp() <= room.betting_start_time + room.betting_duration {
+                return Err(Error::BettingStillOpen);
+            }
+            let mut seed_hash = [0u8; 32];
+            Blake2x256::hash(&seed.to_le_bytes(), &mut seed_hash);
+            if Hash::from(seed_hash) != room.seed_hash {
+                return Err(Error::SeedMismatch);
+            }
+
+            let result = RaceResult {
+                total_pot: room.total_pot,
+                ..self.simulate(seed, &self.track_weights(None), None)
+            };
+            let winning_exacta = result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+            let did_not_finish = result.did_not_finish.clone();
+
+            let mut paid_out: Balance = 0;
+            for bet in &room.bets {
+                let void = did_not_finish.contains(&bet.first_pick) || did_not_finish.contains(&bet.second_pick);
+                let (amount, reason) = if void {
+                    (bet.amount, LedgerReason::Refund)
+                } else if (bet.first_pick, bet.second_pick) == winning_exacta {
+                    (Self::mul_div(bet.amount, multiplier as u128, 1)?, LedgerReason::Payout)
+                } else {
+                    continue;
+                };
+                self.credit_winnings(bet.bettor, amount, reason)?;
+                if void {
+                    self.emit_refund(bet.bettor, amount, RefundReason::DidNotFinish);
+                }
+                paid_out = Self::add(paid_out, amount)?;
+            }
+            self.book_house(room.total_pot, paid_out)?;
+
+            room.status = RaceStatus::Closed;
+            room.result = Some(result.clone());
+            self.rooms.insert(room_id, &room);
+
+            self.env().emit_event(RoomSettled {
+                room_id,
+                winning_exacta,
+                total_pot: room.total_pot,
+                paid_out,
+            });
+
+            Ok(result)
+        }
+
+        /// Call off a room and refund every stake
+        /// The creator can cancel until the race is run; anyone can once the
+        /// creator has let the reveal period lapse
+        #[ink(message)]
+        pub fn cancel_room(&mut self, room_id: u32) -> Result<Balance> {
+            let mut room = self.open_room(room_id)?;
+            let reveal_deadline = room.betting_start_time + room.betting_duration + ROOM_REVEAL_PERIOD;
+            if self.env().caller() != room.creator && self.env().block_timestamp() <= reveal_deadline {
+                return Err(Error::GracePeriodActive);
+            }
+
+            for bet in &room.bets {
+                self.credit_balance(bet.bettor, bet.amount, LedgerReason::Refund)?;
+                self.emit_refund(bet.bettor, bet.amount, RefundReason::Cancelled);
+            }
+            room.status = RaceStatus::Closed;
+            self.rooms.insert(room_id, &room);
+
+            self.env().emit_event(RoomCancelled {
+                room_id,
+                refunded: room.total_pot,
+            });
+
+            Ok(room.total_pot)
+        }
+
+        /// Get a race room
+        #[ink(message)]
+        pub fn get_room(&self, room_id: u32) -> Option<RaceRoom> {
+            self.rooms.get(room_id)
+        }
+
+        /// Set the fee for opening a race room (owner only)
+        #[ink(message)]
+        pub fn set_room_fee(&mut self, fee: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.room_fee = fee;
+            Ok(())
+        }
+
+        /// Get the fee for opening a race room
+        #[ink(message)]
+        pub fn get_room_fee(&self) -> Balance {
+            self.room_fee
+        }
+
+        /// Load a room that hasn't been run or cancelled
+        fn open_room(&self, room_id: u32) -> Result<RaceRoom> {
+            let room = self.rooms.get(room_id).ok_or(Error::RoomNotFound)?;
+            if room.status != RaceStatus::Betting {
+                return Err(Error::RoomClosed);
+            }
+            Ok(room)
+        }
+
+        // ========================================================================
+        // TOURNAMENTS
+        // ========================================================================
+
+        /// Create a tournament over the next `num_races` races (owner only)
+        /// Entries close when the first race starts
+        #[ink(message)]
+        pub fn create_tournament(&mut self, entry_fee: Balance, bankroll: Balance, num_races: u8) -> Result<u32> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if bankroll == 0 || num_races == 0 {
+                return Err(Error::InvalidTournament);
+            }
+
+            let tournament_id = self.tournament_count;
+            let first_race_id = self.race_id + 1;
+            self.tournaments.insert(tournament_id, &Tournament {
+                id: tournament_id,
+                entry_fee,
+                bankroll,
+                first_race_id,
+                num_races,
+                ..Default::default()
+            });
+            self.tournament_count += 1;
+
+            self.env().emit_event(TournamentCreated {
+                tournament_id,
+                entry_fee,
+                bankroll,
+                first_race_id,
+                num_races,
+            });
+
+            Ok(tournament_id)
+        }
+
+        /// Enter a tournament, paying the entry fee from the caller's balance
+        #[ink(message)]
+        pub fn join_tournament(&mut self, tournament_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            let mut tournament = self.tournaments.get(tournament_id).ok_or(Error::TournamentNotFound)?;
+            if self.race_id >= tournament.first_race_id {
+                return Err(Error::TournamentClosed);
+            }
+            if tournament.entrants.iter().any(|entry| entry.account == caller) {
+                return Err(Error::AlreadyEntered);
+            }
+            if tournament.entrants.len() >= MAX_TOURNAMENT_ENTRANTS {
+                return Err(Error::TournamentFull);
+            }
+            self.ensure_not_frozen(caller)?;
+            self.ensure_not_excluded(caller)?;
+            self.debit_balance(caller, tournament.entry_fee, LedgerReason::TournamentEntry)?;
+
+            tournament.prize_pool = Self::add(tournament.prize_pool, tournament.entry_fee)?;
+            tournament.entrants.push(TournamentEntry {
+                account: caller,
+                chips: tournament.bankroll,
+                prize: 0,
+            });
+            self.tournaments.insert(tournament_id, &tournament);
+
+            self.env().emit_event(TournamentJoined {
+                tournament_id,
+                account: caller,
+                entry_fee: tournament.entry_fee,
+            });
+
+            Ok(())
+        }
+
+        /// Bet tournament chips on an exacta in the race open for betting
+        /// Settled with the race; real balances, the pot and limits are untouched
+        #[ink(message)]
+        pub fn place_tournament_bet(&mut self, tournament_id: u32, first_pick: u8, second_pick: u8, chips: Balance) -> Result<()> {
+            self.ensure_exacta_open(first_pick, second_pick)?;
+            if chips == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            let caller = self.env().caller();
+            let mut tournament = self.tournaments.get(tournament_id).ok_or(Error::TournamentNotFound)?;
+            let race_id = self.bets_race_id();
+            if race_id < tournament.first_race_id || race_id >= tournament.first_race_id + tournament.num_races as u64 {
+                return Err(Error::TournamentClosed);
+            }
+            let entry = tournament
+                .entrants
+                .iter_mut()
+                .find(|entry| entry.account == caller)
+                .ok_or(Error::NotEntered)?;
+            entry.chips = entry.chips.checked_sub(chips).ok_or(Error::InsufficientBalance)?;
+            self.tournaments.insert(tournament_id, &tournament);
+
+            self.tournament_bets.push(TournamentBet {
+                tournament_id,
+                bettor: caller,
+                first_pick,
+                second_pick,
+                chips,
+            });
+
+            self.env().emit_event(TournamentBetPlaced {
+                tournament_id,
+                bettor: caller,
+                race_id,
+                first_pick,
+                second_pick,
+                chips,
+            });
+
+            Ok(())
+        }
+
+        /// Pay the current race's tournament bets back in chips, refunding DNF picks
+        fn settle_tournament_bets(&mut self) -> Result<()> {
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+
+            for bet in core::mem::take(&mut self.tournament_bets) {
+                let returned = if did_not_finish.contains(&bet.first_pick) || did_not_finish.contains(&bet.second_pick) {
+                    bet.chips
+                } else if (bet.first_pick, bet.second_pick) == winning_exacta {
+                    Self::mul_div(bet.chips, multiplier as u128, 1)?
+                } else {
+                    continue;
+                };
+                self.return_chips(&bet, returned)?;
+            }
+            Ok(())
+        }
+
+        /// Give the chips of unsettled tournament bets back, for a race that never ran
+        fn refund_tournament_bets(&mut self) -> Result<()> {
+            for bet in core::mem::take(&mut self.tournament_bets) {
+                self.return_chips(&bet, bet.chips)?;
+            }
+            Ok(())
+        }
+
+        /// Add chips back to a bettor's tournament entry
+        fn return_chips(&mut self, bet: &TournamentBet, chips: Balance) -> Result<()> {
+            let Some(mut tournament) = self.tournaments.get(bet.tournament_id) else {
+                return Ok(());
+            };
+            if let Some(entry) = tournament.entrants.iter_mut().find(|entry| entry.account == bet.bettor) {
+                entry.chips = Self::add(entry.chips, chips)?;
+            }
+            self.tournaments.insert(bet.tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Rank entrants by chips and split the prize pool once the last race is settled
+        /// The top three share it by TOURNAMENT_PRIZE_SHARES, rescaled when
+        /// fewer entered; equal chip counts go to the earlier entrant
+        #[ink(message)]
+        pub fn settle_tournament(&mut self, tournament_id: u32) -> Result<Vec<(AccountId, Balance)>> {
+            let mut tournament = self.tournaments.get(tournament_id).ok_or(Error::TournamentNotFound)?;
+            if tournament.settled {
+                return Err(Error::TournamentClosed);
+            }
+            let last_race_id = tournament.first_race_id + tournament.num_races as u64 - 1;
+            let last_settled = self.race_id > last_race_id
+                || (self.race_id == last_race_id && matches!(self.status, RaceStatus::Closed | RaceStatus::Betting));
+            if !last_settled {
+                return Err(Error::TournamentNotFinished);
+            }
+
+            let mut ranked: Vec<usize> = (0..tournament.entrants.len()).collect();
+            ranked.sort_by(|&a, &b| tournament.entrants[b].chips.cmp(&tournament.entrants[a].chips));
+            let places = ranked.len().min(TOURNAMENT_PRIZE_SHARES.len());
+            let total_shares: u64 = TOURNAMENT_PRIZE_SHARES[..places].iter().sum();
+
+            let mut winners: Vec<(AccountId, Balance)> = Vec::new();
+            let mut remaining = tournament.prize_pool;
+            for (place, &index) in ranked.iter().take(places).enumerate() {
+                let prize = Self::mul_div(tournament.prize_pool, TOURNAMENT_PRIZE_SHARES[place] as u128, total_shares as u128)?;
+                remaining = Self::sub(remaining, prize)?;
+                let entry = &mut tournament.entrants[index];
+                entry.prize = prize;
+                winners.push((entry.account, prize));
+            }
+            for &(account, prize) in winners.iter() {
+                self.credit_winnings(account, prize, LedgerReason::Payout)?;
+            }
+            if places > 0 {
+                self.collect_dust(remaining)?;
+            }
+            tournament.settled = true;
+            self.tournaments.insert(tournament_id, &tournament);
+
+            self.env().emit_event(TournamentSettled {
+                tournament_id,
+                prize_pool: tournament.prize_pool,
+                winners: winners.clone(),
+            });
+
+            Ok(winners)
+        }
+
+        /// Get tournament by ID
+        #[ink(message)]
+        pub fn get_tournament(&self, tournament_id: u32) -> Option<Tournament> {
+            self.tournaments.get(tournament_id)
+        }
+
+        /// Get all tournament bets for current race
+        #[ink(message)]
+        pub fn get_tournament_bets(&self) -> Vec<TournamentBet> {
+            self.tournament_bets.clone()
+        }
+
+        // ========================================================================
+        // PREDICTION GAME
+        // ========================================================================
+
+        /// Predict the exacta of the race open for betting, for points and no stake
+        /// One prediction per account and race; open to accounts that can't wager
+        #[ink(message)]
+        pub fn predict_exacta(&mut self, first_pick: u8, second_pick: u8) -> Result<()> {
+            self.ensure_exacta_open(first_pick, second_pick)?;
+            let caller = self.env().caller();
+            self.ensure_not_frozen(caller)?;
+            if self.predictions.iter().any(|prediction| prediction.predictor == caller) {
+                return Err(Error::AlreadyPredicted);
+            }
+            if self.predictions.len() >= MAX_PREDICTIONS_PER_RACE {
+                return Err(Error::PredictionLimitReached);
+            }
+            self.predictions.push(Prediction {
+                predictor: caller,
+                first_pick,
+                second_pick,
+            });
+
+            self.env().emit_event(PredictionMade {
+                race_id: self.race_id + 1,
+                predictor: caller,
+                first_pick,
+                second_pick,
+            });
+
+            Ok(())
+        }
+
+        /// Score the current race's predictions and re-rank the predictors
+        /// A hit scores the exacta's multiplier, so longshots are worth more;
+        /// predictions on a horse that did not finish are void
+        fn settle_predictions(&mut self) {
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+            let season = self.season;
+            let mut board = self.prediction_leaderboards.get(season).unwrap_or_default();
+
+            for prediction in core::mem::take(&mut self.predictions) {
+                if did_not_finish.contains(&prediction.first_pick) || did_not_finish.contains(&prediction.second_pick) {
+                    continue;
+                }
+                let account = prediction.predictor;
+                let mut score = self.prediction_scores.get((season, account)).unwrap_or(PredictionScore {
+                    account,
+                    points: 0,
+                    predictions: 0,
+                    hits: 0,
+                });
+                score.predictions += 1;
+                if (prediction.first_pick, prediction.second_pick) == winning_exacta {
+                    score.hits += 1;
+                    score.points = score.points.saturating_add(multiplier);
+                    self.env().emit_event(PredictionScored {
+                        race_id: self.race_id,
+                        predictor: account,
+                        points: multiplier,
+                    });
+                }
+                self.prediction_scores.insert((season, account), &score);
+
+                board.retain(|ranked| ranked.account != account);
+                let position = board.partition_point(|ranked| ranked.points >= score.points);
+                if position < LEADERBOARD_SIZE {
+                    board.insert(position, score);
+                    board.truncate(LEADERBOARD_SIZE);
+                }
+            }
+            self.prediction_leaderboards.insert(season, &board);
+        }
+
+        /// Get all predictions for current race
+        #[ink(message)]
+        pub fn get_predictions(&self) -> Vec<Prediction> {
+            self.predictions.clone()
+        }
+
+        /// Get an account's prediction score for a season
+        #[ink(message)]
+        pub fn get_prediction_score(&self, season: u32, account: AccountId) -> Option<PredictionScore> {
+            self.prediction_scores.get((season, account))
+        }
+
+        /// Get a season's prediction leaderboard, most points first
+        #[ink(message)]
+        pub fn get_prediction_leaderboard(&self, season: u32) -> Vec<PredictionScore> {
+            self.prediction_leaderboards.get(season).unwrap_or_default()
+        }
+
+        // ========================================================================
+        // DEMO MODE
+        // ========================================================================
+
+        /// Top the caller's play-money balance up to DEMO_FAUCET_AMOUNT
+        /// Returns the amount granted
+        #[ink(message)]
+        pub fn demo_faucet(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            let balance = self.demo_balances.get(caller).unwrap_or(0);
+            let granted = DEMO_FAUCET_AMOUNT.saturating_sub(balance);
+            self.demo_balances.insert(caller, &balance.max(DEMO_FAUCET_AMOUNT));
+            Ok(granted)
+        }
+
+        /// Place an exacta bet with play money
+        /// Settled with the race into demo balances; real balances, the pot,
+        /// limits and loyalty points are untouched
+        #[ink(message)]
+        pub fn demo_place_exacta_bet(&mut self, first_pick: u8, second_pick: u8, amount: Balance) -> Result<()> {
+            self.ensure_exacta_open(first_pick, second_pick)?;
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            let caller = self.env().caller();
+            let balance = self.demo_balances.get(caller).unwrap_or(0);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            self.demo_balances.insert(caller, &(balance - amount));
+
+            let bet_index = self.demo_bets.len() as u32;
+            self.demo_bets.push(ExactaBet {
+                bettor: caller,
+                amount,
+                first_pick,
+                second_pick,
+                timestamp: self.env().block_timestamp(),
+                free_bet: false,
+                receipt: None,
+                syndicate: None,
+                cash_out: None,
+                in_running_odds: None,
+                bonus_stake: 0,
+                additions: 1,
+                settled: false,
+                beneficiary: None,
+            });
+
+            self.env().emit_event(BetPlaced {
+                race_id: self.race_id + 1,
+                bettor: caller,
+                bet_id: None,
+                bet_index,
+                first_pick,
+                second_pick,
+                amount,
+                demo: true,
+            });
+
+            Ok(())
+        }
+
+        /// Settle demo bets into demo balances, refunding DNF picks
+        fn settle_demo_bets(&mut self) -> Result<()> {
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+
+            for (bet_index, bet) in self.demo_bets.clone().iter().enumerate() {
+                let returned = if did_not_finish.contains(&bet.first_pick) || did_not_finish.contains(&bet.second_pick) {
+                    bet.amount
+                } else if (bet.first_pick, bet.second_pick) == winning_exacta {
+                    let payout = Self::mul_div(bet.amount, multiplier as u128, 1)?;
+                    self.env().emit_event(PayoutDistributed {
+                        race_id: self.race_id,
+                        bettor: bet.bettor,
+                        placed_by: bet.bettor,
+                        bet_id: None,
+                        bet_index: bet_index as u32,
+                        amount: payout,
+                        multiplier,
+                        demo: true,
+                    });
+                    payout
+                } else {
+                    continue;
+                };
+                let balance = self.demo_balances.get(bet.bettor).unwrap_or(0);
+                self.demo_balances.insert(bet.bettor, &Self::add(balance, returned)?);
+            }
+            Ok(())
+        }
+
+        /// Get an account's play-money balance
+        #[ink(message)]
+        pub fn get_demo_balance(&self, account: AccountId) -> Balance {
+            self.demo_balances.get(account).unwrap_or(0)
+        }
+
+        /// Get all demo bets for current race
+        #[ink(message)]
+        pub fn get_demo_bets(&self) -> Vec<ExactaBet> {
+            self.demo_bets.clone()
+        }
+
+        // ========================================================================
+        // RACE SIMULATION ENGINE
+        // ========================================================================
+
+        /// Check a lifecycle step against the status, caller and windows
+        /// Returns the status to move to once the step has done its work
+        fn ensure_transition(&self, action: RaceAction) -> Result<RaceStatus> {
+            let (from, to) = match action {
+                RaceAction::Start | RaceAction::Simulate => (RaceStatus::Betting, RaceStatus::Racing),
+                RaceAction::Finish => (RaceStatus::Racing, RaceStatus::Finished),
+                RaceAction::Settle => (RaceStatus::Finished, RaceStatus::Closed),
+                RaceAction::Reset => (self.status.clone(), RaceStatus::Betting),
+            };
+            if self.status != from {
+                return Err(Error::InvalidTransition { from: self.status.clone(), to });
+            }
+
+            let now = self.env().block_timestamp();
+            match action {
+                RaceAction::Start if now <= self.betting_start_time + self.betting_duration => {
+                    Err(Error::BettingStillOpen)
+                }
+                RaceAction::Finish if now < self.race_start_time + self.racing_duration => {
+                    Err(Error::RaceStillRunning)
+                }
+                RaceAction::Settle if now < self.race_finish_time.saturating_add(self.challenge_period) => {
+                    Err(Error::ChallengePeriodActive)
+                }
+                RaceAction::Reset if self.env().caller() != self.owner => Err(Error::NotOwner),
+                _ => Ok(to),
+            }
+        }
+
+        /// Move the race state machine and announce the transition
+        fn set_status(&mut self, to: RaceStatus) {
+            let from = core::mem::replace(&mut self.status, to.clone());
+            self.env().emit_event(RaceStatusChanged {
+                race_id: self.race_id,
+                from,
+                to,
+                timestamp: self.env().block_timestamp(),
+            });
+        }
+
+        /// Start the race with a given seed for deterministic simulation
+        /// Anyone can start the race once the betting window has elapsed.
+        /// Seeds from callers other than the owner are mixed with block data
+        /// so a keeper can't pick the outcome.
+        #[ink(message)]
+        pub fn start_race(&mut self, seed: u64) -> Result<()> {
+            let to = self.ensure_transition(RaceAction::Start)?;
+            if seed == 0 {
+                return Err(Error::InvalidSeed);
+            }
+            let seed = self.mix_seed(seed);
+            if self.used_seeds.contains(seed) {
+                return Err(Error::SeedAlreadyUsed);
+            }
+
+            self.current_seed = seed;
+            self.race_id += 1;
+            self.used_seeds.insert(seed, &self.race_id);
+            self.set_status(to);
+            self.race_start_time = self.env().block_timestamp();
+            self.add_to_meeting();
+
+            self.env().emit_event(RaceStarted {
+                race_id: self.race_id,
+                seed,
+                total_bets: self.bets.len() as u32,
+            });
+
+            self.reward_keeper();
+
+            Ok(())
+        }
+
+        /// Run the race simulation and determine winners
+        /// Uses probability-weighted selection based on the exacta formula:
+        /// P(i wins 1st) = S[i] / sum(S)
+        /// P(j wins 2nd | i won 1st) = S[j] / (sum(S) - S[i])
+        #[ink(message)]
+        pub fn run_race_simulation(&mut self) -> Result<RaceResult> {
+            self.ensure_transition(RaceAction::Finish)?;
+
+            // Once standings are public, the final phase needs fresh entropy too
+            if self.race_phase > 0 {
+                self.current_seed = self.mix_seed(self.current_seed);
+            }
+            let result = self.execute_race()?;
+            self.reward_keeper();
+            Ok(result)
+        }
+
+        /// Mix block data into a seed unless the owner is the caller
+        fn mix_seed(&self, seed: u64) -> u64 {
+            if self.env().caller() == self.owner {
+                return seed;
+            }
+            let now = self.env().block_timestamp();
+            let block = self.env().block_number() as u64;
+            self.next_random(seed ^ now ^ (block << 32))
+        }
+
+        /// Simulate the race in progress and record the result
+        /// Phases already run weight the finish toward the horses in front
+        fn execute_race(&mut self) -> Result<RaceResult> {
+            let weights = self.running_weights();
+            let (meeting_id, race_number) = self.meeting_slot(self.race_id);
+            let track = self.race_track();
+
+            let mut result = RaceResult {
+                race_id: self.race_id,
+                total_pot: self.total_pot,
+                total_bets: self.bets.len() as u32,
+                meeting_id,
+                race_number,
+                info: self.race_info.get(self.race_id).unwrap_or_default(),
+                track_id: track.as_ref().map(|track| track.id),
+                ..self.simulate(self.current_seed, &weights, track.as_ref())
+            };
+            let (first, second) = result.winning_exacta;
+            for bet in &self.bets {
+                if bet.cash_out.is_none() && (bet.first_pick, bet.second_pick) == (first, second) {
+                    result.winning_bets += 1;
+                    result.winning_stake = Self::add(result.winning_stake, bet.amount)?;
+                }
+            }
+            result.multiplier = self.get_multiplier(first, second);
+            let rankings = result.rankings.clone();
+            let did_not_finish = result.did_not_finish.clone();
+
+            self.latest_result = result.clone();
+            self.race_results.push(result.clone());
+            self.update_form(&rankings);
+            self.update_records(&rankings, &did_not_finish);
+            self.update_season(&rankings);
+            self.race_finish_time = self.env().block_timestamp();
+            self.set_status(RaceStatus::Finished);
+
+            self.env().emit_event(RaceFinished {
+                race_id: self.race_id,
+                first_place: rankings[0],
+                second_place: rankings[1],
+                third_place: rankings[2],
+                did_not_finish,
+            });
+
+            Ok(result)
+        }
+
+        /// Run the simulation for a seed and set of weights on a track
+        /// Deterministic, so a stored result can be reproduced from its
+        /// seed, weights and track. Race context fields are left at their defaults.
+        fn simulate(&self, seed: u64, weights: &[u64; NUM_HORSES], track: Option<&Track>) -> RaceResult {
+            let mut rng_state = seed;
+            
+            // Track which horses are still available
+            let mut available: Vec<bool> = vec![true; NUM_HORSES];
+            let mut rankings: Vec<u8> = Vec::new();
+            let mut finish_times: Vec<u64> = Vec::new();
+            let mut sectional_times: Vec<[u64; NUM_PHASES]> = Vec::new();
+            let mut margins: Vec<u64> = Vec::new();
+
+            // Seed-derived DNF draws, horses that fail to finish are never ranked
+            let mut did_not_finish: Vec<u8> = Vec::new();
+            let mut dnf_state = seed ^ DNF_SEED_SALT;
+            for i in 0..NUM_HORSES {
+                dnf_state = self.next_random(dnf_state);
+                let finishers = NUM_HORSES - did_not_finish.len();
+                if dnf_state % PRECISION < DNF_CHANCE && finishers > MIN_FINISHERS {
+                    available[i] = false;
+                    did_not_finish.push(i as u8);
+                }
+            }
+
+            // Select each position using weighted probability
+            for position in 0..NUM_HORSES {
+                // Calculate total remaining strength
+                let mut remaining_strength: u64 = 0;
+                for i in 0..NUM_HORSES {
+                    if available[i] {
+                        remaining_strength += weights[i];
+                    }
+                }
+                
+                if remaining_strength == 0 {
+                    break;
+                }
+                
+                // Generate random number for selection
+                rng_state = self.next_random(rng_state);
+                let random_val = rng_state % remaining_strength;
+                
+                // Select horse based on weighted probability
+                let mut cumulative: u64 = 0;
+                let mut selected_horse: u8 = 0;
+                
+                for i in 0..NUM_HORSES {
+                    if available[i] {
+                        cumulative += weights[i];
+                        if random_val < cumulative {
+                            selected_horse = i as u8;
+                            break;
+                        }
+                    }
+                }
+                
+                // Mark horse as finished
+                available[selected_horse as usize] = false;
+                rankings.push(selected_horse);
+                
+                // Winner runs close to its nominal pace, every following
+                // horse finishes a random margin behind the one before it
+                rng_state = self.next_random(rng_state);
+                let time_variation = rng_state % 5;
+                let finish_time = if position == 0 {
+                    let nominal: u64 = self.nominal_sectionals(selected_horse, track).iter().sum();
+                    nominal + time_variation * MARGIN_STEP_MS
+                } else {
+                    let margin = MIN_MARGIN_MS + time_variation * MARGIN_STEP_MS;
+                    margins.push(margin);
+                    finish_times[position - 1] + margin
+                };
+                finish_times.push(finish_time);
+                sectional_times.push(self.scale_sectionals(selected_horse, finish_time, track));
+            }
+
+            RaceResult {
+                winning_exacta: (rankings[0], rankings[1]),
+                rankings,
+                finish_times,
+                sectional_times,
+                margins,
+                did_not_finish,
+                seed_used: seed,
+                weights: weights.to_vec(),
+                ..Default::default()
+            }
+        }
+
+        /// Re-run a stored race from its seed, weights and track and compare
+        /// Returns whether rankings, times and DNFs match, plus the
+        /// recomputed rankings; None if the race isn't stored
+        #[ink(message)]
+        pub fn verify_result(&self, race_id: u64) -> Option<(bool, Vec<u8>)> {
+            let stored = self.find_result(race_id)?;
+            let weights: [u64; NUM_HORSES] = stored.weights.clone().try_into().ok()?;
+            let track = stored.track_id.and_then(|id| self.tracks.get(id as usize));
+            let replay = self.simulate(stored.seed_used, &weights, track);
+
+            let matches = replay.rankings == stored.rankings
+                && replay.finish_times == stored.finish_times
+                && replay.did_not_finish == stored.did_not_finish;
+            Some((matches, replay.rankings))
+        }
+
+        /// Count starts and placings for every horse in a finished race
+        fn update_records(&mut self, rankings: &[u8], did_not_finish: &[u8]) {
+            for record in self.horse_records.iter_mut() {
+                record.starts += 1;
+            }
+            for (position, &horse) in rankings.iter().take(3).enumerate() {
+                let record = &mut self.horse_records[horse as usize];
+                match position {
+                    0 => record.wins += 1,
+                    1 => record.seconds += 1,
+                    _ => record.thirds += 1,
+                }
+            }
+            for &horse in did_not_finish {
+                self.horse_records[horse as usize].did_not_finish += 1;
+            }
+        }
+
+        /// Phase modifiers (scaled by PRECISION) from acceleration and stamina
+        /// Warm-up uses acceleration, cruise is neutral, sprint uses stamina
+        fn phase_modifiers(&self, horse_id: u8) -> [u64; NUM_PHASES] {
+            let horse = &self.horses[horse_id as usize];
+            let neutral = PRECISION - 5 * ATTRIBUTE_STEP;
+            [
+                neutral + horse.acceleration * ATTRIBUTE_STEP,
+                PRECISION,
+                neutral + horse.stamina * ATTRIBUTE_STEP,
+            ]
+        }
+
+        /// Selection weights on the track of the race open or running
+        fn race_weights(&self) -> [u64; NUM_HORSES] {
+            self.track_weights(self.race_track().as_ref())
+        }
+
+        /// Selection weight of each horse: W[i] = S'[i] * sum(phase modifiers) * A[i]
+        /// where S'[i] is the form-adjusted strength and A[i] the horse's
+        /// affinity for the track's surface (1.0 on the standard course)
+        fn track_weights(&self, track: Option<&Track>) -> [u64; NUM_HORSES] {
+            let mut weights = [0u64; NUM_HORSES];
+            for i in 0..NUM_HORSES {
+                let modifiers: u64 = self.phase_modifiers(i as u8).iter().sum();
+                let weight = (self.horse_forms[i].adjusted_strength * modifiers) / PRECISION;
+                weights[i] = (weight * self.surface_affinity(i as u8, track)) / PRECISION;
+            }
+            weights
+        }
+
+        /// Speed of a horse on a track's surface (scaled by PRECISION)
+        fn surface_affinity(&self, horse_id: u8, track: Option<&Track>) -> u64 {
+            match track {
+                Some(track) => self.horses[horse_id as usize].surface_affinity[track.surface as usize],
+                None => PRECISION,
+            }
+        }
+
+        /// Update every horse's form after a race (ELO-style)
+        /// Each place finished above the expected place (rank by adjusted strength)
+        /// adds FORM_K_FACTOR of base strength, each place below removes it
+        fn update_form(&mut self, rankings: &[u8]) {
+            let mut expected = [0usize; NUM_HORSES];
+            for i in 0..NUM_HORSES {
+                for j in 0..NUM_HORSES {
+                    let stronger = self.horse_forms[j].adjusted_strength
+                        > self.horse_forms[i].adjusted_strength;
+                    let tie_ahead = self.horse_forms[j].adjusted_strength
+                        == self.horse_forms[i].adjusted_strength && j < i;
+                    if stronger || tie_ahead {
+                        expected[i] += 1;
+                    }
+                }
+            }
+
+            for i in 0..NUM_HORSES {
+                // Horses that did not finish count as finishing behind the field
+                let actual = rankings
+                    .iter()
+                    .position(|&horse| horse as usize == i)
+                    .unwrap_or(NUM_HORSES);
+
+                let base = self.horses[i].strength * PRECISION;
+                let step = (base * FORM_K_FACTOR) / PRECISION;
+                let max_drift = (base * MAX_FORM_DRIFT) / PRECISION;
+
+                let form = &mut self.horse_forms[i];
+                if actual < expected[i] {
+                    let gain = step * (expected[i] - actual) as u64;
+                    form.adjusted_strength = (form.adjusted_strength + gain).min(base + max_drift);
+                } else if actual > expected[i] {
+                    let loss = step * (actual - expected[i]) as u64;
+                    form.adjusted_strength = form
+                        .adjusted_strength
+                        .saturating_sub(loss)
+                        .max(base - max_drift);
+                }
+
+                let finish = if actual < NUM_HORSES { actual as u8 + 1 } else { 0 };
+                form.recent_finishes.push(finish);
+                if form.recent_finishes.len() > FORM_WINDOW {
+                    form.recent_finishes.remove(0);
+                }
+            }
+        }
+
+        /// Nominal time (ms) a horse needs for each phase of the race
+        /// Phase constants: warm-up 0.85, cruise 1.0, sprint 1.0 + S[i] / 12,
+        /// each scaled by the horse's phase modifier. On a track, each phase
+        /// covers a third of its distance and speed is scaled by the going
+        /// and the horse's surface affinity.
+        fn nominal_sectionals(&self, horse_id: u8, track: Option<&Track>) -> [u64; NUM_PHASES] {
+            let strength = self.horses[horse_id as usize].strength;
+            let base_speed = self.horses[horse_id as usize].base_speed;
+            let modifiers = self.phase_modifiers(horse_id);
+            let (phase_distance, going) = match track {
+                Some(track) => (track.distance / NUM_PHASES as u64, track.going),
+                None => (PHASE_DISTANCE, PRECISION),
+            };
+            let affinity = self.surface_affinity(horse_id, track);
+            let phase_constants = [
+                (8500 * modifiers[0]) / PRECISION,
+                (PRECISION * modifiers[1]) / PRECISION,
+                ((PRECISION + (strength * PRECISION) / 12) * modifiers[2]) / PRECISION,
+            ];
+
+            let mut sectionals = [0u64; NUM_PHASES];
+            for phase in 0..NUM_PHASES {
+                // t = d / (Bs[i] * C * G * A[i]), speed scaled by PRECISION
+                let speed = (base_speed * phase_constants[phase] * going / PRECISION) * affinity / PRECISION;
+                sectionals[phase] = (phase_distance * MS_PER_SECOND * PRECISION) / speed;
+            }
+            sectionals
+        }
+
+        /// Split a finish time across the phases following the horse's pace profile
+        fn scale_sectionals(&self, horse_id: u8, finish_time: u64, track: Option<&Track>) -> [u64; NUM_PHASES] {
+            let nominal = self.nominal_sectionals(horse_id, track);
+            let nominal_total: u64 = nominal.iter().sum();
+
+            let mut sectionals = [0u64; NUM_PHASES];
+            let mut assigned: u64 = 0;
+            for phase in 0..NUM_PHASES - 1 {
+                sectionals[phase] = (nominal[phase] * finish_time) / nominal_total;
+                assigned += sectionals[phase];
+            }
+            // Last phase absorbs rounding so sectionals sum to the finish time
+            sectionals[NUM_PHASES - 1] = finish_time - assigned;
+            sectionals
+        }
+
+        /// Pay the keeper reward from the treasury to a non-owner caller
+        /// that advanced the race. Skipped if unset or the treasury is short.
+        fn reward_keeper(&mut self) {
+            let keeper = self.env().caller();
+            let reward = self.keeper_reward;
+            if keeper == self.owner || reward == 0 || self.treasury < reward || self.reentrancy_lock {
+                return;
+            }
+
+            self.reentrancy_lock = true;
+            let transferred = self.env().transfer(keeper, reward).is_ok();
+            self.reentrancy_lock = false;
+            if transferred {
+                self.treasury -= reward;
+                self.env().emit_event(KeeperRewarded {
+                    keeper,
+                    race_id: self.race_id,
+                    status: self.status.clone(),
+                    reward,
+                });
+            }
+        }
+
+        /// Simple LCG random number generator
+        fn next_random(&self, state: u64) -> u64 {
+            // LCG parameters (same as glibc)
+            const A: u64 = 1103515245;
+            const C: u64 = 12345;
+            const M: u64 = 2147483648; // 2^31
+            
+            (state.wrapping_mul(A).wrapping_add(C)) % M
+        }
+
+        // ========================================================================
+        // IN-RUNNING BETTING
+        // ========================================================================
+
+        /// Run the next phase of the race in progress and publish the standings
+        /// Phase p can run once p/NUM_PHASES of the racing window has elapsed.
+        /// Every phase but the last runs here, the last resolves the race in
+        /// run_race_simulation. Each horse covers the phase in its nominal
+        /// sectional time plus a random variation.
+        #[ink(message)]
+        pub fn run_phase(&mut self) -> Result<Vec<u8>> {
+            if self.status != RaceStatus::Racing {
+                return Err(Error::RaceNotInProgress);
+            }
+            let phase = self.race_phase as usize;
+            if phase >= NUM_PHASES - 1 {
+                return Err(Error::FinalPhaseOnly);
+            }
+            let due = self.race_start_time + self.racing_duration * (phase as u64 + 1) / NUM_PHASES as u64;
+            if self.env().block_timestamp() < due {
+                return Err(Error::RaceStillRunning);
+            }
+
+            let mut rng_state = self.mix_seed(self.current_seed);
+            self.current_seed = rng_state;
+            if self.running_times.is_empty() {
+                self.running_times = vec![0; NUM_HORSES];
+            }
+            let track = self.race_track();
+            for horse in 0..NUM_HORSES {
+                rng_state = self.next_random(rng_state);
+                let variation = (rng_state % 5) * MARGIN_STEP_MS;
+                self.running_times[horse] += self.nominal_sectionals(horse as u8, track.as_ref())[phase] + variation;
+            }
+            self.race_phase += 1;
+
+            let standings = self.get_standings();
+            self.env().emit_event(PhaseCompleted {
+                race_id: self.race_id,
+                phase: self.race_phase,
+                standings: standings.clone(),
+            });
+
+            Ok(standings)
+        }
+
+        /// Horses ordered by running time after the phases run so far
+        /// Empty until the first phase has run
+        #[ink(message)]
+        pub fn get_standings(&self) -> Vec<u8> {
+            let mut standings: Vec<u8> = (0..self.running_times.len() as u8).collect();
+            standings.sort_by_key(|&horse| (self.running_times[horse as usize], horse));
+            standings
+        }
+
+        /// Get the running time (ms) of each horse, empty before the first phase
+        #[ink(message)]
+        pub fn get_running_times(&self) -> Vec<u64> {
+            self.running_times.clone()
+        }
+
+        /// Get the number of phases of the current race run so far
+        #[ink(message)]
+        pub fn get_race_phase(&self) -> u8 {
+            self.race_phase
+        }
+
+        /// Race weights adjusted for the phases run so far:
+        /// W'[i] = W[i] * (T[leader] / T[i])^2, the leader keeps its weight
+        fn running_weights(&self) -> [u64; NUM_HORSES] {
+            let mut weights = self.race_weights();
+            let Some(&leader_time) = self.running_times.iter().min() else {
+                return weights;
+            };
+            for (weight, &time) in weights.iter_mut().zip(self.running_times.iter()) {
+                *weight = *weight * leader_time / time * leader_time / time;
+            }
+            weights
+        }
+
+        /// Odds for an in-running exacta from the current standings, 0 when
+        /// in-running betting is closed or the picks are invalid
+        /// odds = (1 - edge) / P(first → second), P from the running weights
+        #[ink(message)]
+        pub fn get_in_running_odds(&self, first: u8, second: u8) -> u64 {
+            if self.ensure_in_running_open().is_err() {
+                return 0;
+            }
+            let probability = Self::exacta_probability(&self.running_weights(), first, second);
+            if probability == 0 {
+                return 0;
+            }
+            ((PRECISION - self.house_edge(IN_RUNNING_EDGE)) * PRECISION) / probability
+        }
+
+        /// Place an exacta bet between phases, locked at the current in-running odds
+        /// Open once the first phase has run, until the racing window elapses
+        #[ink(message, payable)]
+        pub fn place_in_running_bet(&mut self, first_pick: u8, second_pick: u8) -> Result<u32> {
+            self.ensure_in_running_open()?;
+            if first_pick >= NUM_HORSES as u8 || second_pick >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if first_pick == second_pick {
+                return Err(Error::SameHorsePicked);
+            }
+
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            let odds = self.get_in_running_odds(first_pick, second_pick);
+            self.record_exacta_bet(caller, first_pick, second_pick, amount, false, None, Some(odds), 0, None)
+        }
+
+        /// Fail unless a phase has run and the race can't be resolved yet
+        fn ensure_in_running_open(&self) -> Result<()> {
+            if self.status != RaceStatus::Racing || self.race_phase == 0 {
+                return Err(Error::BettingClosed);
+            }
+            if self.env().block_timestamp() >= self.race_start_time + self.racing_duration {
+                return Err(Error::BettingClosed);
+            }
+            Ok(())
+        }
+
+        // ========================================================================
+        // HEAD-TO-HEAD MATCH BETS
+        // ========================================================================
+
+        /// Odds that `horse` finishes ahead of `opponent`, scaled by PRECISION
+        /// P(a ahead of b) = W[a] / (W[a] + W[b]), odds = (1 - edge) / P
+        #[ink(message)]
+        pub fn get_match_odds(&self, horse: u8, opponent: u8) -> u64 {
+            if horse >= NUM_HORSES as u8 || opponent >= NUM_HORSES as u8 || horse == opponent {
+                return 0;
+            }
+            let weights = self.race_weights();
+            let w_horse = weights[horse as usize];
+            let w_opponent = weights[opponent as usize];
+            let probability = (w_horse * PRECISION) / (w_horse + w_opponent);
+            ((PRECISION - self.house_edge(self.match_bet_edge)) * PRECISION) / probability
+        }
+
+        /// Bet that `horse` finishes ahead of `opponent` regardless of placing
+        #[ink(message, payable)]
+        pub fn place_match_bet(&mut self, horse: u8, opponent: u8) -> Result<()> {
+            self.ensure_betting_open()?;
+            if horse >= NUM_HORSES as u8 || opponent >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if horse == opponent {
+                return Err(Error::SameHorsePicked);
+            }
+
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let odds = self.get_match_odds(horse, opponent);
+            self.on_wager(caller, amount)?;
+            self.match_bets.push(MatchBet {
+                bettor: caller,
+                amount,
+                horse,
+                opponent,
+                odds,
+                status: BetStatus::Pending,
+                payout: 0,
+            });
+
+            self.env().emit_event(MatchBetPlaced {
+                bettor: caller,
+                horse,
+                opponent,
+                amount,
+                odds,
+            });
+
+            Ok(())
+        }
+
+        /// Settle head-to-head bets from the rankings of the latest race
+        /// A horse that did not finish loses to any finisher; if both fail
+        /// to finish the stake is refunded
+        fn settle_match_bets(&mut self) -> Result<()> {
+            let rankings = self.latest_result.rankings.clone();
+            let position = |horse: u8| rankings.iter().position(|&h| h == horse);
+
+            for i in 0..self.match_bets.len() {
+                let bet = &self.match_bets[i];
+                if bet.status != BetStatus::Pending {
+                    continue;
+                }
+                let (status, payout) = match (position(bet.horse), position(bet.opponent)) {
+                    (None, None) => (BetStatus::Refunded, bet.amount),
+                    (Some(a), Some(b)) if a > b => (BetStatus::Lost, 0),
+                    (None, Some(_)) => (BetStatus::Lost, 0),
+                    _ => (BetStatus::Won, Self::apply_odds(bet.amount, bet.odds)?),
+                };
+
+                let bet = &mut self.match_bets[i];
+                bet.status = status.clone();
+                bet.payout = payout;
+                let (bettor, horse, opponent, amount) = (bet.bettor, bet.horse, bet.opponent, bet.amount);
+                let reason = match status {
+                    BetStatus::Refunded => LedgerReason::Refund,
+                    _ => LedgerReason::Payout,
+                };
+                self.book_house(amount, payout)?;
+
+                self.env().emit_event(MatchBetSettled {
+                    bettor,
+                    horse,
+                    opponent,
+                    status,
+                    payout,
+                });
+                self.credit_winnings(bettor, payout, reason)?;
+                if reason == LedgerReason::Refund {
+                    self.emit_refund(bettor, payout, RefundReason::DidNotFinish);
+                }
+            }
+            Ok(())
+        }
+
+        /// Get head-to-head bets for current race
+        #[ink(message)]
+        pub fn get_match_bets(&self) -> Vec<MatchBet> {
+            self.match_bets.clone()
+        }
+
+        // ========================================================================
+        // OVER/UNDER FINISH TIME
+        // ========================================================================
+
+        /// Offer an over/under line on the winner's finish time for this race
+        #[ink(message)]
+        pub fn set_finish_time_line(&mut self, line: u64, over_odds: u64, under_odds: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.status != RaceStatus::Betting {
+                return Err(Error::RaceNotInBettingPhase);
+            }
+            if over_odds <= PRECISION || under_odds <= PRECISION {
+                return Err(Error::InvalidOdds);
+            }
+
+            self.finish_time_line = Some(FinishTimeLine {
+                line,
+                over_odds,
+                under_odds,
+            });
+            Ok(())
+        }
+
+        /// Get the finish-time line for current race
+        #[ink(message)]
+        pub fn get_finish_time_line(&self) -> Option<FinishTimeLine> {
+            self.finish_time_line.clone()
+        }
+
+        /// Bet the winner's finish time lands over (or under) the line
+        #[ink(message, payable)]
+        pub fn place_over_under_bet(&mut self, over: bool) -> Result<()> {
+            self.ensure_betting_open()?;
+            let line = self.finish_time_line.clone().ok_or(Error::NoLineSet)?;
+
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let odds = if over { line.over_odds } else { line.under_odds };
+            self.on_wager(caller, amount)?;
+            self.over_under_bets.push(OverUnderBet {
+                bettor: caller,
+                amount,
+                over,
+                line: line.line,
+                odds,
+                status: BetStatus::Pending,
+                payout: 0,
+            });
+
+            self.env().emit_event(OverUnderPlaced {
+                bettor: caller,
+                over,
+                line: line.line,
+                amount,
+                odds,
+            });
+
+            Ok(())
+        }
+
+        /// Settle over/under bets from the winner's finish time
+        /// A time exactly on the line is a push and refunds the stake
+        fn settle_over_under_bets(&mut self) -> Result<()> {
+            let winning_time = self.latest_result.finish_times[0];
+
+            for i in 0..self.over_under_bets.len() {
+                let bet = &self.over_under_bets[i];
+                if bet.status != BetStatus::Pending {
+                    continue;
+                }
+                let (status, payout) = if winning_time == bet.line {
+                    (BetStatus::Refunded, bet.amount)
+                } else if (winning_time > bet.line) == bet.over {
+                    (BetStatus::Won, Self::apply_odds(bet.amount, bet.odds)?)
+                } else {
+                    (BetStatus::Lost, 0)
+                };
+
+                let bet = &mut self.over_under_bets[i];
+                bet.status = status.clone();
+                bet.payout = payout;
+                let (bettor, over, amount) = (bet.bettor, bet.over, bet.amount);
+                let reason = match status {
+                    BetStatus::Refunded => LedgerReason::Refund,
+                    _ => LedgerReason::Payout,
+                };
+                self.book_house(amount, payout)?;
+
+                self.env().emit_event(OverUnderSettled {
+                    bettor,
+                    over,
+                    winning_time,
+                    status,
+                    payout,
+                });
+                self.credit_winnings(bettor, payout, reason)?;
+                if reason == LedgerReason::Refund {
+                    self.emit_refund(bettor, payout, RefundReason::Push);
+                }
+            }
+            Ok(())
+        }
+
+        /// Get over/under bets for current race
+        #[ink(message)]
+        pub fn get_over_under_bets(&self) -> Vec<OverUnderBet> {
+            self.over_under_bets.clone()
+        }
+
+        // ========================================================================
+        // DAILY DOUBLE
+        // ========================================================================
+
+        /// Place a daily double: winners of the next race and the race after it
+        /// Odds are locked at placement: (1 - edge) / (P(leg 1) * P(leg 2))
+        #[ink(message, payable)]
+        pub fn place_daily_double(&mut self, first_leg_pick: u8, second_leg_pick: u8) -> Result<u32> {
+            self.ensure_betting_open()?;
+            if first_leg_pick >= NUM_HORSES as u8 || second_leg_pick >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let p_first = self.calculate_win_probability(first_leg_pick);
+            let p_second = self.calculate_win_probability(second_leg_pick);
+            let odds = ((PRECISION - self.daily_double_edge) * PRECISION * PRECISION) / (p_first * p_second);
+
+            let bet_id = self.daily_doubles.len() as u32;
+            let first_race_id = self.race_id + 1;
+            self.daily_doubles.push(DailyDoubleBet {
+                id: bet_id,
+                bettor: caller,
+                amount,
+                first_race_id,
+                picks: (first_leg_pick, second_leg_pick),
+                odds,
+                status: BetStatus::Pending,
+                payout: 0,
+            });
+            self.daily_double_escrow = Self::add(self.daily_double_escrow, amount)?;
+            self.on_wager(caller, amount)?;
+
+            self.env().emit_event(DailyDoublePlaced {
+                bettor: caller,
+                bet_id,
+                first_race_id,
+                picks: (first_leg_pick, second_leg_pick),
+                amount,
+            });
+
+            Ok(bet_id)
+        }
+
+        /// Settle daily double legs decided by the latest race
+        fn settle_daily_doubles(&mut self) -> Result<()> {
+            let race_id = self.latest_result.race_id;
+            let winner = self.latest_result.rankings[0];
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+
+            for i in 0..self.daily_doubles.len() {
+                let bet = &self.daily_doubles[i];
+                if bet.status != BetStatus::Pending {
+                    continue;
+                }
+
+                let pick = if bet.first_race_id == race_id {
+                    bet.picks.0
+                } else if bet.first_race_id + 1 == race_id {
+                    bet.picks.1
+                } else {
+                    continue;
+                };
+                let final_leg = bet.first_race_id + 1 == race_id;
+
+                let (status, payout) = if did_not_finish.contains(&pick) {
+                    (BetStatus::Refunded, bet.amount)
+                } else if pick != winner {
+                    (BetStatus::Lost, 0)
+                } else if final_leg {
+                    (BetStatus::Won, Self::apply_odds(bet.amount, bet.odds)?)
+                } else {
+                    // Leg 1 hit, stake stays in escrow for leg 2
+                    continue;
+                };
+
+                let bet = &mut self.daily_doubles[i];
+                bet.status = status.clone();
+                bet.payout = payout;
+                let (bettor, bet_id, amount) = (bet.bettor, bet.id, bet.amount);
+                let reason = match status {
+                    BetStatus::Refunded => LedgerReason::Refund,
+                    _ => LedgerReason::Payout,
+                };
+                self.daily_double_escrow = Self::sub(self.daily_double_escrow, amount)?;
+                self.book_house(amount, payout)?;
+
+                self.env().emit_event(DailyDoubleSettled {
+                    bettor,
+                    bet_id,
+                    status,
+                    payout,
+                });
+                self.credit_winnings(bettor, payout, reason)?;
+                if reason == LedgerReason::Refund {
+                    self.emit_refund(bettor, payout, RefundReason::DidNotFinish);
+                }
+            }
+            Ok(())
+        }
+
+        /// Get daily double by ID
+        #[ink(message)]
+        pub fn get_daily_double(&self, bet_id: u32) -> Option<DailyDoubleBet> {
+            self.daily_doubles.get(bet_id as usize).cloned()
+        }
+
+        /// Get all daily doubles
+        #[ink(message)]
+        pub fn get_daily_doubles(&self) -> Vec<DailyDoubleBet> {
+            self.daily_doubles.clone()
+        }
+
+        /// Get stakes held in escrow for unsettled daily doubles
+        #[ink(message)]
+        pub fn get_daily_double_escrow(&self) -> Balance {
+            self.daily_double_escrow
+        }
+
+        // ========================================================================
+        // BETTING EXCHANGE
+        // ========================================================================
+
+        /// Offer to lay an exacta at `odds` to backers staking up to `stake`
+        /// The layer's liability, stake × (odds − 1), is escrowed from their balance
+        #[ink(message)]
+        pub fn offer_lay(&mut self, first_pick: u8, second_pick: u8, odds: u64, stake: Balance) -> Result<u32> {
+            self.ensure_exacta_open(first_pick, second_pick)?;
+            if odds <= PRECISION {
+                return Err(Error::InvalidOdds);
+            }
+            let liability = Self::apply_odds(stake, odds - PRECISION)?;
+            if liability == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let layer = self.env().caller();
+            self.on_wager(layer, liability)?;
+            self.debit_balance(layer, liability, LedgerReason::Bet)?;
+            let offer_id = self.lay_offers.len() as u32;
+            self.lay_offers.push(LayOffer {
+                id: offer_id,
+                layer,
+                first_pick,
+                second_pick,
+                odds,
+                stake_open: stake,
+                liability_open: liability,
+            });
+
+            self.env().emit_event(LayOffered {
+                offer_id,
+                layer,
+                first_pick,
+                second_pick,
+                odds,
+                stake,
+            });
+
+            Ok(offer_id)
+        }
+
+        /// Back an exacta against a lay offer, staking from the caller's balance
+        #[ink(message)]
+        pub fn back_offer(&mut self, offer_id: u32, stake: Balance) -> Result<()> {
+            let backer = self.env().caller();
+            let mut offer = self.lay_offers.get(offer_id as usize).cloned().ok_or(Error::OfferNotFound)?;
+            self.ensure_exacta_open(offer.first_pick, offer.second_pick)?;
+            if stake == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            if stake > offer.stake_open || backer == offer.layer {
+                return Err(Error::OfferUnavailable);
+            }
+
+            // The last match takes the whole remaining escrow, so no dust is left
+            let liability = if stake == offer.stake_open {
+                offer.liability_open
+            } else {
+                Self::apply_odds(stake, offer.odds - PRECISION)?
+            };
+            self.on_wager(backer, stake)?;
+            self.debit_balance(backer, stake, LedgerReason::Bet)?;
+            offer.stake_open -= stake;
+            offer.liability_open = Self::sub(offer.liability_open, liability)?;
+            let layer = offer.layer;
+            self.lay_offers[offer_id as usize] = offer;
+            self.exchange_matches.push(ExchangeMatch {
+                offer_id,
+                backer,
+                layer,
+                stake,
+                liability,
+                status: BetStatus::Pending,
+            });
+
+            self.env().emit_event(OfferMatched {
+                offer_id,
+                backer,
+                stake,
+                liability,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw the unmatched part of the caller's offer, returning its escrow
+        #[ink(message)]
+        pub fn cancel_offer(&mut self, offer_id: u32) -> Result<Balance> {
+            let caller = self.env().caller();
+            let offer = self.lay_offers.get_mut(offer_id as usize).ok_or(Error::OfferNotFound)?;
+            if offer.layer != caller {
+                return Err(Error::NotLayer);
+            }
+            let refund = offer.liability_open;
+            offer.stake_open = 0;
+            offer.liability_open = 0;
+            if refund > 0 {
+                self.credit_balance(caller, refund, LedgerReason::Refund)?;
+                self.emit_refund(caller, refund, RefundReason::Cancelled);
+            }
+            Ok(refund)
+        }
+
+        /// Settle matched exchange bets from the latest race and return
+        /// unmatched escrow. Commission is charged on the winner's net gain;
+        /// a pick that did not finish voids the match
+        fn settle_exchange(&mut self) -> Result<()> {
+            let winning_exacta = self.latest_result.winning_exacta;
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+            let commission = self.house_edge(self.exchange_commission) as u128;
+
+            for i in 0..self.exchange_matches.len() {
+                let matched = self.exchange_matches[i].clone();
+                if matched.status != BetStatus::Pending {
+                    continue;
+                }
+                let offer = &self.lay_offers[matched.offer_id as usize];
+                let exacta = (offer.first_pick, offer.second_pick);
+
+                let status = if did_not_finish.contains(&exacta.0) || did_not_finish.contains(&exacta.1) {
+                    self.credit_winnings(matched.backer, matched.stake, LedgerReason::Refund)?;
+                    self.credit_winnings(matched.layer, matched.liability, LedgerReason::Refund)?;
+                    self.emit_refund(matched.backer, matched.stake, RefundReason::DidNotFinish);
+                    self.emit_refund(matched.layer, matched.liability, RefundReason::DidNotFinish);
+                    BetStatus::Refunded
+                } else {
+                    let (winner, net, status) = if exacta == winning_exacta {
+                        (matched.backer, matched.liability, BetStatus::Won)
+                    } else {
+                        (matched.layer, matched.stake, BetStatus::Lost)
+                    };
+                    let rake = Self::mul_div(net, commission, PRECISION as u128)?;
+                    let kept = Self::mul_div(net, PRECISION as u128 - commission, PRECISION as u128)?;
+                    let pot = Self::add(matched.stake, matched.liability)?;
+                    let payout = Self::add(Self::sub(pot, net)?, kept)?;
+                    self.credit_winnings(winner, payout, LedgerReason::Payout)?;
+                    self.book_rake(rake)?;
+                    self.collect_dust(Self::sub(Self::sub(net, kept)?, rake)?)?;
+                    status
+                };
+                self.exchange_matches[i].status = status;
+            }
+
+            for i in 0..self.lay_offers.len() {
+                let offer = &mut self.lay_offers[i];
+                let (layer, refund) = (offer.layer, offer.liability_open);
+                offer.stake_open = 0;
+                offer.liability_open = 0;
+                if refund > 0 {
+                    self.credit_balance(layer, refund, LedgerReason::Refund)?;
+                    self.emit_refund(layer, refund, RefundReason::Unmatched);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Set the exchange commission (scaled by PRECISION, governance or owner)
+        #[ink(message)]
+        pub fn set_exchange_commission(&mut self, commission: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if commission > PRECISION {
+                return Err(Error::InvalidConfig);
+            }
+            self.exchange_commission = commission;
+            Ok(())
+        }
+
+        /// Get the exchange commission
+        #[ink(message)]
+        pub fn get_exchange_commission(&self) -> u64 {
+            self.exchange_commission
+        }
+
+        /// Get lay offers for the current race
+        #[ink(message)]
+        pub fn get_lay_offers(&self) -> Vec<LayOffer> {
+            self.lay_offers.clone()
+        }
+
+        /// Get matched exchange bets for the current race
+        #[ink(message)]
+        pub fn get_exchange_matches(&self) -> Vec<ExchangeMatch> {
+            self.exchange_matches.clone()
+        }
+
+        // ========================================================================
+        // SEASON FUTURES
+        // ========================================================================
+
+        /// Offer (or withdraw with 0) futures odds on a horse winning the season
+        #[ink(message)]
+        pub fn set_futures_odds(&mut self, horse: u8, odds: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if horse >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if odds != 0 && odds <= PRECISION {
+                return Err(Error::InvalidOdds);
+            }
+            self.futures_odds[horse as usize] = odds;
+            Ok(())
+        }
+
+        /// Back a horse to win the most races this season
+        /// The stake is escrowed until `settle_futures` closes the season
+        #[ink(message, payable)]
+        pub fn place_futures_bet(&mut self, horse: u8) -> Result<u32> {
+            if horse >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            let odds = self.futures_odds[horse as usize];
+            if odds == 0 {
+                return Err(Error::FuturesMarketClosed);
+            }
+
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let bet_id = self.futures_bets.len() as u32;
+            self.futures_bets.push(FuturesBet {
+                id: bet_id,
+                bettor: caller,
+                amount,
+                season: self.season,
+                horse,
+                odds,
+                status: BetStatus::Pending,
+                payout: 0,
+            });
+            self.futures_escrow = Self::add(self.futures_escrow, amount)?;
+            self.on_wager(caller, amount)?;
+
+            self.env().emit_event(FuturesBetPlaced {
+                bettor: caller,
+                bet_id,
+                season: self.season,
+                horse,
+                amount,
+                odds,
+            });
+
+            Ok(bet_id)
+        }
+
+        /// Settle the season's futures and start a new season
+        /// Shorthand for `close_season`, returning the futures champions
+        #[ink(message)]
+        pub fn settle_futures(&mut self) -> Result<Vec<u8>> {
+            Ok(self.close_season()?.champions)
+        }
+
+        /// Close the season: settle futures, snapshot the standings and start a new season
+        #[ink(message)]
+        pub fn close_season(&mut self) -> Result<SeasonStandings> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+
+            let mut standings = self.season_table();
+            standings.champions = self.settle_season_futures()?;
+            standings.closed_at = self.env().block_timestamp();
+            self.season_history.insert(self.season, &standings);
+
+            let leader = standings.standings.first().filter(|standing| standing.points > 0);
+            self.env().emit_event(SeasonClosed {
+                season: self.season,
+                races: standings.races,
+                leader: leader.map(|standing| standing.horse_id),
+                leader_points: leader.map(|standing| standing.points).unwrap_or(0),
+            });
+
+            self.season += 1;
+            self.season_wins = vec![0; NUM_HORSES];
+            self.season_points = vec![0; NUM_HORSES];
+            self.season_races = 0;
+
+            Ok(standings)
+        }
+
+        /// Pay out the current season's futures
+        /// Horses tied on most wins dead-heat: winning payouts are divided by
+        /// the number of champions. A season without wins refunds every stake.
+        fn settle_season_futures(&mut self) -> Result<Vec<u8>> {
+            let most_wins = self.season_wins.iter().copied().max().unwrap_or(0);
+            let champions: Vec<u8> = if most_wins == 0 {
+                Vec::new()
+            } else {
+                (0..NUM_HORSES as u8)
+                    .filter(|&horse| self.season_wins[horse as usize] == most_wins)
+                    .collect()
+            };
+
+            let season = self.season;
+            let mut winning_bets: u32 = 0;
+            let mut total_paid: Balance = 0;
+            let mut total_staked: Balance = 0;
+            let mut total_returned: Balance = 0;
+            let mut credits: Vec<(AccountId, Balance)> = Vec::new();
+            for bet in self.futures_bets.iter_mut() {
+                if bet.season != season || bet.status != BetStatus::Pending {
+                    continue;
+                }
+                if champions.is_empty() {
+                    bet.status = BetStatus::Refunded;
+                    bet.payout = bet.amount;
+                } else if champions.contains(&bet.horse) {
+                    bet.status = BetStatus::Won;
+                    bet.payout = Self::mul_div(
+                        bet.amount,
+                        bet.odds as u128,
+                        PRECISION as u128 * champions.len() as u128,
+                    )?;
+                    winning_bets += 1;
+                    total_paid = Self::add(total_paid, bet.payout)?;
+                } else {
+                    bet.status = BetStatus::Lost;
+                }
+                self.futures_escrow = Self::sub(self.futures_escrow, bet.amount)?;
+                total_staked = Self::add(total_staked, bet.amount)?;
+                total_returned = Self::add(total_returned, bet.payout)?;
+                credits.push((bet.bettor, bet.payout));
+            }
+            for (account, amount) in credits {
+                self.credit_winnings(account, amount, LedgerReason::Payout)?;
+            }
+            self.book_house(total_staked, total_returned)?;
+
+            self.env().emit_event(FuturesSettled {
+                season,
+                champions: champions.clone(),
+                winning_bets,
+                total_paid,
+            });
+
+            Ok(champions)
+        }
+
+        /// Count a finished race towards the season: the winner's win and
+        /// championship points for every horse that finished
+        fn update_season(&mut self, rankings: &[u8]) {
+            self.season_wins[rankings[0] as usize] += 1;
+            for (position, &horse) in rankings.iter().enumerate() {
+                self.season_points[horse as usize] += SEASON_POINTS[position];
+            }
+            self.season_races += 1;
+        }
+
+        /// Current season's table, ranked by points, then wins, then horse ID
+        fn season_table(&self) -> SeasonStandings {
+            let mut standings: Vec<SeasonStanding> = (0..NUM_HORSES)
+                .map(|horse| SeasonStanding {
+                    horse_id: horse as u8,
+                    points: self.season_points[horse],
+                    wins: self.season_wins[horse],
+                })
+                .collect();
+            standings.sort_by(|a, b| {
+                b.points
+                    .cmp(&a.points)
+                    .then(b.wins.cmp(&a.wins))
+                    .then(a.horse_id.cmp(&b.horse_id))
+            });
+            SeasonStandings {
+                season: self.season,
+                races: self.season_races,
+                standings,
+                ..Default::default()
+            }
+        }
+
+        /// Get the live championship standings of the current season
+        #[ink(message)]
+        pub fn get_season_standings(&self) -> SeasonStandings {
+            self.season_table()
+        }
+
+        /// Get the final standings of a closed season
+        #[ink(message)]
+        pub fn get_season_history(&self, season: u32) -> Option<SeasonStandings> {
+            self.season_history.get(season)
+        }
+
+        /// Get current season number and wins per horse
+        #[ink(message)]
+        pub fn get_season_wins(&self) -> (u32, Vec<u32>) {
+            (self.season, self.season_wins.clone())
+        }
+
+        /// Get futures odds per horse (scaled by PRECISION, 0 = not offered)
+        #[ink(message)]
+        pub fn get_futures_odds(&self) -> Vec<u64> {
+            self.futures_odds.clone()
+        }
+
+        /// Get futures bet by ID
+        #[ink(message)]
+        pub fn get_futures_bet(&self, bet_id: u32) -> Option<FuturesBet> {
+            self.futures_bets.get(bet_id as usize).cloned()
+        }
+
+        /// Get stakes held in escrow for unsettled futures
+        #[ink(message)]
+        pub fn get_futures_escrow(&self) -> Balance {
+            self.futures_escrow
+        }
+
+        // ========================================================================
+        // LEADERBOARD
+        // ========================================================================
+
+        /// Fold settled bets into each bettor's season results and re-rank them
+        /// Only the bettors whose results changed move, so the board is kept
+        /// sorted with a binary search instead of sorting every account.
+        fn update_leaderboard(&mut self, results: &[(AccountId, Balance, Balance)]) -> Result<()> {
+            if results.is_empty() {
+                return Ok(());
+            }
+            let season = self.season;
+            let mut board = self.leaderboards.get(season).unwrap_or_default();
+            for &(account, staked, returned) in results {
+                let mut entry = self.bettor_seasons.get((season, account)).unwrap_or(LeaderboardEntry {
+                    account,
+                    net_winnings: 0,
+                    staked: 0,
+                    bets: 0,
+                });
+                entry.staked = Self::add(entry.staked, staked)?;
+                entry.net_winnings = entry
+                    .net_winnings
+                    .checked_add(returned as i128 - staked as i128)
+                    .ok_or(Error::ArithmeticOverflow)?;
+                entry.bets += 1;
+                self.bettor_seasons.insert((season, account), &entry);
+
+                board.retain(|ranked| ranked.account != account);
+                let position = board.partition_point(|ranked| ranked.net_winnings >= entry.net_winnings);
+                if position < LEADERBOARD_SIZE {
+                    board.insert(position, entry);
+                    board.truncate(LEADERBOARD_SIZE);
+                }
+            }
+            self.leaderboards.insert(season, &board);
+            Ok(())
+        }
+
+        /// Get a season's leaderboard, best net winnings first
+        #[ink(message)]
+        pub fn get_leaderboard(&self, season: u32) -> Vec<LeaderboardEntry> {
+            self.leaderboards.get(season).unwrap_or_default()
+        }
+
+        /// Get an account's settled results for a season
+        #[ink(message)]
+        pub fn get_bettor_season(&self, season: u32, account: AccountId) -> Option<LeaderboardEntry> {
+            self.bettor_seasons.get((season, account))
+        }
+
+        // ========================================================================
+        // PICK 6
+        // ========================================================================
+
+        /// Open a Pick 6 pool over the next six races, taking in any carryover
+        #[ink(message)]
+        pub fn open_pick6(&mut self) -> Result<u32> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.pick6_pools.iter().any(|pool| !pool.settled) {
+                return Err(Error::Pick6InProgress);
+            }
+
+            let pool_id = self.pick6_pools.len() as u32;
+            let first_race_id = self.race_id + 1;
+            let carryover_in = self.pick6_carryover;
+            self.pick6_carryover = 0;
+
+            self.pick6_pools.push(Pick6Pool {
+                id: pool_id,
+                first_race_id,
+                carryover_in,
+                ..Default::default()
+            });
+
+            self.env().emit_event(Pick6Opened {
+                pool_id,
+                first_race_id,
+                carryover_in,
+            });
+
+            Ok(pool_id)
+        }
+
+        /// Enter the open Pick 6 pool with one winner pick per leg
+        #[ink(message, payable)]
+        pub fn place_pick6(&mut self, picks: Vec<u8>) -> Result<()> {
+            let pool_index = self
+                .pick6_pools
+                .iter()
+                .position(|pool| !pool.settled)
+                .ok_or(Error::NoPick6Pool)?;
+
+            // Entries close when the first leg starts
+            if self.race_id >= self.pick6_pools[pool_index].first_race_id {
+                return Err(Error::BettingClosed);
+            }
+            if picks.len() != PICK6_LEGS {
+                return Err(Error::InvalidPickCount);
+            }
+            if picks.iter().any(|&pick| pick >= NUM_HORSES as u8) {
+                return Err(Error::InvalidHorseId);
+            }
+
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let pool = &mut self.pick6_pools[pool_index];
+            pool.total_stakes = Self::add(pool.total_stakes, amount)?;
+            pool.entries += 1;
+            let pool_id = pool.id;
+
+            let caller = self.env().caller();
+            self.pick6_entries.push(Pick6Entry {
+                pool_id,
+                bettor: caller,
+                amount,
+                picks,
+                hits: 0,
+                payout: 0,
+            });
+            self.on_wager(caller, amount)?;
+
+            Ok(())
+        }
+
+        /// Settle the open Pick 6 pool once all six legs have finished
+        /// 6-of-6 tickets share the jackpot, 5-of-6 share the consolation;
+        /// unwon portions carry over to the next pool
+        #[ink(message)]
+        pub fn settle_pick6(&mut self) -> Result<Pick6Pool> {
+            let pool_index = self
+                .pick6_pools
+                .iter()
+                .position(|pool| !pool.settled)
+                .ok_or(Error::NoPick6Pool)?;
+            let pool = self.pick6_pools[pool_index].clone();
+
+            let mut winners: Vec<u8> = Vec::new();
+            for leg in 0..PICK6_LEGS as u64 {
+                let result = self
+                    .find_result(pool.first_race_id + leg)
+                    .ok_or(Error::Pick6NotReady)?;
+                winners.push(result.rankings[0]);
+            }
+
+            // Score tickets and total the stakes on each tier
+            let mut jackpot_stake: Balance = 0;
+            let mut consolation_stake: Balance = 0;
+            for entry in self.pick6_entries.iter_mut().filter(|e| e.pool_id == pool.id) {
+                entry.hits = entry
+                    .picks
+                    .iter()
+                    .zip(winners.iter())
+                    .filter(|(pick, winner)| pick == winner)
+                    .count() as u8;
+                if entry.hits as usize == PICK6_LEGS {
+                    jackpot_stake = Self::add(jackpot_stake, entry.amount)?;
+                } else if entry.hits as usize == PICK6_LEGS - 1 {
+                    consolation_stake = Self::add(consolation_stake, entry.amount)?;
+                }
+            }
+
+            let pool_total = Self::add(pool.total_stakes, pool.carryover_in)?;
+            let consolation_pool = Self::mul_div(pool_total, PICK6_CONSOLATION_SHARE as u128, PRECISION as u128)?;
+            let jackpot_pool = Self::sub(pool_total, consolation_pool)?;
+
+            // Pro-rata split by stake within each tier
+            let mut jackpot_paid: Balance = 0;
+            let mut consolation_paid: Balance = 0;
+            let mut jackpot_winners: u32 = 0;
+            let mut consolation_winners: u32 = 0;
+            let mut credits: Vec<(AccountId, Balance)> = Vec::new();
+            for entry in self.pick6_entries.iter_mut().filter(|e| e.pool_id == pool.id) {
+                if entry.hits as usize == PICK6_LEGS {
+                    entry.payout = Self::mul_div(jackpot_pool, entry.amount, jackpot_stake)?;
+                    jackpot_paid = Self::add(jackpot_paid, entry.payout)?;
+                    jackpot_winners += 1;
+                } else if entry.hits as usize == PICK6_LEGS - 1 {
+                    entry.payout = Self::mul_div(consolation_pool, entry.amount, consolation_stake)?;
+                    consolation_paid = Self::add(consolation_paid, entry.payout)?;
+                    consolation_winners += 1;
+                }
+                credits.push((entry.bettor, entry.payout));
+            }
+            for (account, amount) in credits {
+                self.credit_winnings(account, amount, LedgerReason::Payout)?;
+            }
+
+            let mut carryover_out: Balance = 0;
+            for (tier_pool, paid, winners) in [
+                (jackpot_pool, jackpot_paid, jackpot_winners),
+                (consolation_pool, consolation_paid, consolation_winners),
+            ] {
+                let unpaid = Self::sub(tier_pool, paid)?;
+                if winners > 0 {
+                    self.collect_dust(unpaid)?;
+                } else {
+                    carryover_out = Self::add(carryover_out, unpaid)?;
+                }
+            }
+            self.pick6_carryover = Self::add(self.pick6_carryover, carryover_out)?;
+
+            let pool = &mut self.pick6_pools[pool_index];
+            pool.settled = true;
+            pool.jackpot_paid = jackpot_paid;
+            pool.consolation_paid = consolation_paid;
+            pool.carryover_out = carryover_out;
+            let settled = pool.clone();
+
+            self.env().emit_event(Pick6Settled {
+                pool_id: settled.id,
+                jackpot_winners,
+                consolation_winners,
+                carryover_out,
+            });
+
+            Ok(settled)
+        }
+
+        /// Get Pick 6 pool by ID
+        #[ink(message)]
+        pub fn get_pick6_pool(&self, pool_id: u32) -> Option<Pick6Pool> {
+            self.pick6_pools.get(pool_id as usize).cloned()
+        }
+
+        /// Get tickets entered in a Pick 6 pool
+        #[ink(message)]
+        pub fn get_pick6_entries(&self, pool_id: u32) -> Vec<Pick6Entry> {
+            self.pick6_entries
+                .iter()
+                .filter(|entry| entry.pool_id == pool_id)
+                .cloned()
+                .collect()
+        }
+
+        /// Get Pick 6 funds waiting to carry into the next pool
+        #[ink(message)]
+        pub fn get_pick6_carryover(&self) -> Balance {
+            self.pick6_carryover
+        }
+
+        // ========================================================================
+        // RACE MEETINGS
+        // ========================================================================
+
+        /// Create a meeting; the next `num_races` races form its card
+        #[ink(message)]
+        pub fn create_meeting(&mut self, name: Vec<u8>, num_races: u8) -> Result<u32> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.active_meeting.is_some() {
+                return Err(Error::MeetingInProgress);
+            }
+            if num_races == 0 {
+                return Err(Error::InvalidRaceCount);
+            }
+
+            let meeting_id = self.meetings.len() as u32;
+            self.meetings.push(Meeting {
+                id: meeting_id,
+                name: name.clone(),
+                session_start: self.env().block_timestamp(),
+                num_races,
+                race_ids: Vec::new(),
+            });
+            self.active_meeting = Some(meeting_id);
+
+            self.env().emit_event(MeetingCreated {
+                meeting_id,
+                name,
+                num_races,
+            });
+
+            Ok(meeting_id)
+        }
+
+        /// Put the race just started on the active meeting's card
+        fn add_to_meeting(&mut self) {
+            let Some(meeting_id) = self.active_meeting else {
+                return;
+            };
+            let meeting = &mut self.meetings[meeting_id as usize];
+            meeting.race_ids.push(self.race_id);
+
+            // Card complete, later races run outside any meeting
+            if meeting.race_ids.len() >= meeting.num_races as usize {
+                self.active_meeting = None;
+            }
+        }
+
+        /// Meeting ID and card number for a race
+        fn meeting_slot(&self, race_id: u64) -> (Option<u32>, u8) {
+            for meeting in &self.meetings {
+                if let Some(index) = meeting.race_ids.iter().position(|&id| id == race_id) {
+                    return (Some(meeting.id), index as u8 + 1);
+                }
+            }
+            (None, 0)
+        }
+
+        /// Get meeting by ID
+        #[ink(message)]
+        pub fn get_meeting(&self, meeting_id: u32) -> Option<Meeting> {
+            self.meetings.get(meeting_id as usize).cloned()
+        }
+
+        /// Get all meetings
+        #[ink(message)]
+        pub fn get_meetings(&self) -> Vec<Meeting> {
+            self.meetings.clone()
+        }
+
+        /// Get the meeting whose card is still being run
+        #[ink(message)]
+        pub fn get_active_meeting(&self) -> Option<Meeting> {
+            self.active_meeting.and_then(|id| self.get_meeting(id))
+        }
+
+        /// Get results of a meeting's races in card order
+        #[ink(message)]
+        pub fn get_meeting_results(&self, meeting_id: u32) -> Vec<RaceResult> {
+            self.race_results
+                .iter()
+                .filter(|result| result.meeting_id == Some(meeting_id))
+                .cloned()
+                .collect()
+        }
+
+        // ========================================================================
+        // TRACKS
+        // ========================================================================
+
+        /// Register a track profile races can be run on
+        /// Tracks can't be changed once added, so stored results stay verifiable
+        #[ink(message)]
+        pub fn add_track(&mut self, name: Vec<u8>, distance: u64, surface: Surface, going: u64) -> Result<u32> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if !(MIN_TRACK_DISTANCE..=MAX_TRACK_DISTANCE).contains(&distance)
+                || going.abs_diff(PRECISION) > MAX_GOING_DEVIATION
+                || self.tracks.len() >= MAX_TRACKS
+            {
+                return Err(Error::InvalidTrack);
+            }
+
+            let track_id = self.tracks.len() as u32;
+            self.tracks.push(Track {
+                id: track_id,
+                name,
+                distance,
+                surface,
+                going,
+            });
+            Ok(track_id)
+        }
+
+        /// Select the track of the race open for betting, None for the standard course
+        #[ink(message)]
+        pub fn set_race_track(&mut self, track_id: Option<u32>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.status != RaceStatus::Betting {
+                return Err(Error::RaceNotInBettingPhase);
+            }
+
+            let race_id = self.bets_race_id();
+            match track_id {
+                Some(id) if id as usize >= self.tracks.len() => return Err(Error::TrackNotFound),
+                Some(id) => {
+                    self.race_tracks.insert(race_id, &id);
+                }
+                None => self.race_tracks.remove(race_id),
+            }
+            Ok(())
+        }
+
+        /// Track of the race open for betting or running, None on the standard course
+        fn race_track(&self) -> Option<Track> {
+            let track_id = self.race_tracks.get(self.bets_race_id())?;
+            self.tracks.get(track_id as usize).cloned()
+        }
+
+        /// Get track by ID
+        #[ink(message)]
+        pub fn get_track(&self, track_id: u32) -> Option<Track> {
+            self.tracks.get(track_id as usize).cloned()
+        }
+
+        /// Get all registered tracks
+        #[ink(message)]
+        pub fn get_tracks(&self) -> Vec<Track> {
+            self.tracks.clone()
+        }
+
+        /// Get the track a race is (or was) run on, None for the standard course
+        #[ink(message)]
+        pub fn get_race_track(&self, race_id: u64) -> Option<Track> {
+            let track_id = self.race_tracks.get(race_id)?;
+            self.get_track(track_id)
+        }
+
+        // ========================================================================
+        // SETTLEMENT LISTENERS
+        // ========================================================================
+
+        /// Register a contract to be called back after settlement (owner only)
+        #[ink(message)]
+        pub fn add_listener(&mut self, listener: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.listeners.contains(&listener) {
+                return Ok(());
+            }
+            if self.listeners.len() >= MAX_LISTENERS {
+                return Err(Error::TooManyListeners);
+            }
+            self.listeners.push(listener);
+            Ok(())
+        }
+
+        /// Unregister a listener (owner only)
+        #[ink(message)]
+        pub fn remove_listener(&mut self, listener: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.listeners.retain(|l| *l != listener);
+            Ok(())
+        }
+
+        /// Get registered listeners
+        #[ink(message)]
+        pub fn get_listeners(&self) -> Vec<AccountId> {
+            self.listeners.clone()
+        }
+
+        /// Call every listener with the result and exacta payouts
+        /// Each call is gas-capped and a failing listener never blocks
+        /// settlement; it only gets a `ListenerCallFailed` event
+        fn notify_listeners(&mut self, payouts: &[Payout]) {
+            use ink::codegen::TraitCallBuilder;
+            let race_id = self.race_id;
+            let rankings = self.latest_result.rankings.clone();
+            for listener in self.listeners.clone() {
+                let mut callback: ink::contract_ref!(RaceListener) = listener.into();
+                let mut delivered = matches!(
+                    callback
+                        .call_mut()
+                        .on_race_finished(race_id, rankings.clone())
+                        .ref_time_limit(CALLBACK_GAS_LIMIT)
+                        .try_invoke(),
+                    Ok(Ok(()))
+                );
+                for payout in payouts {
+                    delivered &= matches!(
+                        callback
+                            .call_mut()
+                            .on_payout(payout.bettor, payout.payout_amount)
+                            .ref_time_limit(CALLBACK_GAS_LIMIT)
+                            .try_invoke(),
+                        Ok(Ok(()))
+                    );
+                }
+                if !delivered {
+                    self.env().emit_event(ListenerCallFailed { listener, race_id });
+                }
+            }
+        }
+
+        // ========================================================================
+        // RESULT DISPUTES
+        // ========================================================================
+
+        /// Challenge the finished, unsettled race's result
+        /// The race is replayed; if it doesn't match the stored result,
+        /// settlement is frozen for admin review. Returns whether it was upheld.
+        #[ink(message)]
+        pub fn challenge_result(&mut self, race_id: u64) -> Result<bool> {
+            if race_id != self.race_id || self.status != RaceStatus::Finished {
+                return Err(Error::RaceNotFinished);
+            }
+
+            let (matches, _) = self.verify_result(race_id).unwrap_or((false, Vec::new()));
+            let upheld = !matches;
+            if upheld && !self.disputed_races.contains(&race_id) {
+                self.disputed_races.push(race_id);
+            }
+
+            self.env().emit_event(ResultChallenged {
+                race_id,
+                challenger: self.env().caller(),
+                upheld,
+            });
+
+            Ok(upheld)
+        }
+
+        /// Clear a dispute after review so the race can settle (owner only)
+        #[ink(message)]
+        pub fn resolve_dispute(&mut self, race_id: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.disputed_races.retain(|&id| id != race_id);
+            self.env().emit_event(DisputeResolved { race_id });
+            Ok(())
+        }
+
+        /// Set the challenge period (ms, 0 disables it) (owner only)
+        #[ink(message)]
+        pub fn set_challenge_period(&mut self, period: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.challenge_period = period;
+            Ok(())
+        }
+
+        /// Get the challenge period (ms)
+        #[ink(message)]
+        pub fn get_challenge_period(&self) -> u64 {
+            self.challenge_period
+        }
+
+        /// Get races whose settlement is frozen
+        #[ink(message)]
+        pub fn get_disputed_races(&self) -> Vec<u64> {
+            self.disputed_races.clone()
+        }
+
+        // ========================================================================
+        // SETTLEMENT APPROVALS
+        // ========================================================================
+
+        /// Propose settling the finished race, counting as the first approval
+        #[ink(message)]
+        pub fn propose_payouts(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_operator(caller)?;
+            if self.status != RaceStatus::Finished {
+                return Err(Error::RaceNotFinished);
+            }
+
+            self.payout_approvals = Some((self.race_id, vec![caller]));
+            self.env().emit_event(PayoutsApproved {
+                race_id: self.race_id,
+                operator: caller,
+                approvals: 1,
+                threshold: self.approval_threshold,
+            });
+
+            Ok(())
+        }
+
+        /// Approve the proposed settlement; the approval that reaches the
+        /// threshold distributes the payouts
+        #[ink(message)]
+        pub fn approve_payouts(&mut self) -> Result<Option<Vec<Payout>>> {
+            let caller = self.env().caller();
+            self.ensure_operator(caller)?;
+            let race_id = self.race_id;
+            let approvers = match &mut self.payout_approvals {
+                Some((proposed, approvers)) if *proposed == race_id => approvers,
+                _ => return Err(Error::NoProposal),
+            };
+            if approvers.contains(&caller) {
+                return Err(Error::AlreadyApproved);
+            }
+            approvers.push(caller);
+            let approvals = approvers.len() as u32;
+
+            self.env().emit_event(PayoutsApproved {
+                race_id,
+                operator: caller,
+                approvals,
+                threshold: self.approval_threshold,
+            });
+
+            if approvals < self.approval_threshold {
+                return Ok(None);
+            }
+            self.distribute_payouts().map(Some)
+        }
+
+        /// Add an account that may co-sign settlement (owner only)
+        #[ink(message)]
+        pub fn add_settlement_operator(&mut self, operator: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if !self.settlement_operators.contains(&operator) {
+                self.settlement_operators.push(operator);
+            }
+            Ok(())
+        }
+
+        /// Remove a settlement operator (owner only)
+        /// Fails if the remaining operators can't meet the threshold
+        #[ink(message)]
+        pub fn remove_settlement_operator(&mut self, operator: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let remaining = self.settlement_operators.iter().filter(|&&account| account != operator).count();
+            if self.approval_threshold > remaining as u32 + 1 {
+                return Err(Error::InvalidThreshold);
+            }
+            self.settlement_operators.retain(|&account| account != operator);
+            Ok(())
+        }
+
+        /// Set approvals needed to settle, counting the owner (owner only)
+        /// 1 keeps settlement permissionless through `distribute_payouts`
+        #[ink(message)]
+        pub fn set_approval_threshold(&mut self, threshold: u32) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if threshold == 0 || threshold > self.settlement_operators.len() as u32 + 1 {
+                return Err(Error::InvalidThreshold);
+            }
+            self.approval_threshold = threshold;
+            Ok(())
+        }
+
+        /// Get settlement operators and the approval threshold
+        #[ink(message)]
+        pub fn get_settlement_config(&self) -> (Vec<AccountId>, u32) {
+            (self.settlement_operators.clone(), self.approval_threshold)
+        }
+
+        /// Get the current settlement proposal: (race_id, approvers)
+        #[ink(message)]
+        pub fn get_payout_approvals(&self) -> Option<(u64, Vec<AccountId>)> {
+            self.payout_approvals.clone()
+        }
+
+        /// Reject callers that are neither the owner nor a settlement operator
+        fn ensure_operator(&self, account: AccountId) -> Result<()> {
+            if account != self.owner && !self.settlement_operators.contains(&account) {
+                return Err(Error::NotOperator);
+            }
+            Ok(())
+        }
+
+        // ========================================================================
+        // PAYOUT ENGINE
+        // ========================================================================
+
+        /// Calculate and distribute payouts
+        #[ink(message)]
+        pub fn distribute_payouts(&mut self) -> Result<Vec<Payout>> {
+            if self.settlement_reports.contains(self.race_id) {
+                return Err(Error::AlreadySettled);
+            }
+            let to = self.ensure_transition(RaceAction::Settle)?;
+            if self.disputed_races.contains(&self.race_id) {
+                return Err(Error::SettlementFrozen);
+            }
+            if self.approval_threshold > 1 {
+                let approvals = match &self.payout_approvals {
+                    Some((race_id, approvers)) if *race_id == self.race_id => approvers.len() as u32,
+                    _ => 0,
+                };
+                if approvals < self.approval_threshold {
+                    return Err(Error::ApprovalsPending);
+                }
+            }
+            self.payout_approvals = None;
+
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+
+            let did_not_finish = self.latest_result.did_not_finish.clone();
+
+            // Losing paid stakes come from the per-combination totals, so
+            // losing bets need no work of their own
+            let mut losing_staked: Balance = 0;
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if first == second
+                        || (first, second) == winning_exacta
+                        || did_not_finish.contains(&first)
+                        || did_not_finish.contains(&second)
+                    {
+                        continue;
+                    }
+                    losing_staked = Self::add(losing_staked, self.stake_per_combination.get((first, second)).unwrap_or(0))?;
+                }
+            }
+
+            let mut payouts_list: Vec<Payout> = Vec::new();
+            let mut winners: Vec<usize> = Vec::new();
+            // (bettor, staked, returned) per settled bet, for the leaderboard
+            let mut results: Vec<(AccountId, Balance, Balance)> = Vec::new();
+
+            let bets = self.bets.clone();
+            for (bet_index, bet) in bets.iter().enumerate() {
+                // A retried settlement skips bets it already handled
+                if bet.settled {
+                    continue;
+                }
+                self.bets[bet_index].settled = true;
+
+                // Cashed-out bets were paid early, only the house books them
+                if let Some(amount) = bet.cash_out {
+                    self.book_house(bet.amount, amount)?;
+                    results.push((bet.bettor, bet.amount, amount));
+                    continue;
+                }
+
+                // Bets involving a horse that did not finish are refunded
+                // Bets with a receipt NFT settle to whoever holds it now
+                if did_not_finish.contains(&bet.first_pick) || did_not_finish.contains(&bet.second_pick) {
+                    let payee = self.receipt_holder(bet);
+                    self.refunds.push(Refund {
+                        bettor: payee,
+                        amount: bet.amount,
+                        exacta: (bet.first_pick, bet.second_pick),
+                    });
+
+                    self.emit_refund(payee, bet.amount, RefundReason::DidNotFinish);
+                    if bet.free_bet {
+                        let credits = self.free_bet_credits.get(bet.bettor).unwrap_or(0);
+                        self.free_bet_credits.insert(bet.bettor, &Self::add(credits, bet.amount)?);
+                    } else {
+                        match bet.syndicate {
+                            Some(syndicate_id) => self.split_syndicate(syndicate_id, bet.amount, LedgerReason::Refund)?,
+                            None => self.credit_bet(bet, payee, bet.amount, LedgerReason::Refund)?,
+                        }
+                        self.book_house(bet.amount, bet.amount)?;
+                    }
+                } else if bet.first_pick == winning_exacta.0 && bet.second_pick == winning_exacta.1 {
+                    // Winner! In-running bets pay at their locked odds
+                    let payee = self.receipt_holder(bet);
+                    let (multiplier, payout_amount) = match bet.in_running_odds {
+                        Some(odds) => (odds / PRECISION, Self::apply_odds(bet.amount, odds)?),
+                        None => (multiplier, Self::mul_div(bet.amount, multiplier as u128, 1)?),
+                    };
+                    
+                    let payout = Payout {
+                        bettor: payee,
+                        bet_amount: bet.amount,
+                        multiplier,
+                        payout_amount,
+                        exacta: winning_exacta,
+                    };
+                    
+                    payouts_list.push(payout.clone());
+                    self.payouts.push(payout);
+
+                    self.env().emit_event(PayoutDistributed {
+                        race_id: self.race_id,
+                        bettor: payee,
+                        placed_by: bet.bettor,
+                        bet_id: Some(self.bet_id(bet_index as u32)),
+                        bet_index: bet_index as u32,
+                        amount: payout_amount,
+                        multiplier,
+                        demo: false,
+                    });
+                    match bet.syndicate {
+                        Some(syndicate_id) => self.split_syndicate(syndicate_id, payout_amount, LedgerReason::Payout)?,
+                        None => self.credit_bet(bet, payee, payout_amount, LedgerReason::Payout)?,
+                    }
+                    self.mint_trophy(payee, bet_index as u32, payout_amount);
+                    let staked = if bet.free_bet { 0 } else { bet.amount };
+                    self.book_house(staked, payout_amount)?;
+                    results.push((bet.bettor, staked, payout_amount));
+                    winners.push(bet_index);
+                } else if !bet.free_bet {
+                    results.push((bet.bettor, bet.amount, 0));
+                }
+            }
+            self.update_leaderboard(&results)?;
+            self.book_house(losing_staked, 0)?;
+            let carryover_in = self.carryover;
+            self.settle_unwon_pot(&bets, &winners, losing_staked)?;
+            self.settle_demo_bets()?;
+            self.settle_tournament_bets()?;
+            self.settle_predictions();
+
+            self.settle_match_bets()?;
+            self.settle_over_under_bets()?;
+            self.settle_daily_doubles()?;
+            self.settle_exchange()?;
+
+            self.pay_horse_owners()?;
+
+            let exacta_paid = payouts_list
+                .iter()
+                .try_fold(0, |total, payout| Self::add(total, payout.payout_amount))?;
+            let carried_to_winners = if winners.is_empty() { 0 } else { carryover_in };
+            let pnl = self.race_pnl.get(self.race_id).unwrap_or_default();
+            self.settlement_reports.insert(self.race_id, &SettlementReport {
+                race_id: self.race_id,
+                rankings: self.latest_result.rankings.clone(),
+                did_not_finish,
+                winning_exacta,
+                multiplier,
+                winners: winners.len() as u32,
+                exacta_paid: Self::add(exacta_paid, carried_to_winners)?,
+                total_staked: pnl.staked,
+                total_paid: pnl.paid_out,
+                rake: pnl.rake,
+                commission_free: self.commission_free(),
+                sponsored: self.get_sponsored_amount(self.race_id),
+                carryover_in,
+                carryover_out: self.carryover,
+                settled_at: self.env().block_timestamp(),
+            });
+
+            self.set_status(to);
+            self.reward_keeper();
+            self.notify_listeners(&payouts_list);
+            Ok(payouts_list)
+        }
+
+        /// Get payouts for current race
+        #[ink(message)]
+        pub fn get_payouts(&self) -> Vec<Payout> {
+            self.payouts.clone()
+        }
+
+        /// Get refunds for current race
+        #[ink(message)]
+        pub fn get_refunds(&self) -> Vec<Refund> {
+            self.refunds.clone()
+        }
+
+        /// Get the settlement report of a settled race
+        #[ink(message)]
+        pub fn get_settlement_report(&self, race_id: u64) -> Option<SettlementReport> {
+            self.settlement_reports.get(race_id)
+        }
+
+        /// Get what an account will be credited when the finished race settles:
+        /// winning exacta payouts and DNF refunds, syndicate bets pro rata
+        /// 0 unless the race is Finished; carryover and side markets not included
+        #[ink(message)]
+        pub fn get_pending_winnings(&self, account: AccountId) -> Balance {
+            if self.status != RaceStatus::Finished {
+                return 0;
+            }
+            let winning_exacta = self.latest_result.winning_exacta;
+            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1) as u128;
+            let did_not_finish = &self.latest_result.did_not_finish;
+
+            let mut pending: Balance = 0;
+            for bet in &self.bets {
+                if bet.cash_out.is_some() {
+                    continue;
+                }
+                let amount = if did_not_finish.contains(&bet.first_pick) || did_not_finish.contains(&bet.second_pick) {
+                    // Free stakes come back as free bet credits
+                    if bet.free_bet {
+                        continue;
+                    }
+                    bet.amount
+                } else if (bet.first_pick, bet.second_pick) == winning_exacta {
+                    match bet.in_running_odds {
+                        Some(odds) => Self::apply_odds(bet.amount, odds).unwrap_or(0),
+                        None => bet.amount.saturating_mul(multiplier),
+                    }
+                } else {
+                    continue;
+                };
+                let share = match bet.syndicate.and_then(|syndicate_id| self.syndicates.get(syndicate_id)) {
+                    Some(syndicate) => syndicate
+                        .members
+                        .iter()
+                        .filter(|(member, _)| *member == account)
+                        .map(|(_, stake)| Self::mul_div(amount, *stake, syndicate.total).unwrap_or(0))
+                        .sum(),
+                    None if self.receipt_holder(bet) == account => amount,
+                    None => 0,
+                };
+                pending = pending.saturating_add(share);
+            }
+            pending
+        }
+
+        /// Pay any carried pot to this race's winners by stake, or apply the
+        /// unwon pot policy to the losing stakes when nobody won
+        /// A carried pot was booked as paid out by the race it came from.
+        fn settle_unwon_pot(&mut self, bets: &[ExactaBet], winners: &[usize], staked: Balance) -> Result<()> {
+            let carried = core::mem::take(&mut self.carryover);
+            if !winners.is_empty() {
+                if carried > 0 {
+                    self.split_by_stake(bets, winners, carried, LedgerReason::Payout)?;
+                    self.env().emit_event(CarryoverPaid {
+                        race_id: self.race_id,
+                        amount: carried,
+                        winners: winners.len() as u32,
+                    });
+                }
+                return Ok(());
+            }
+
+            let amount = Self::add(staked, carried)?;
+            if amount == 0 {
+                return Ok(());
+            }
+            let policy = match self.unwon_pot_policy {
+                // With no losing stakes there is nobody to refund
+                UnwonPotPolicy::Refund if staked == 0 => UnwonPotPolicy::Treasury,
+                policy => policy,
+            };
+            match policy {
+                UnwonPotPolicy::Retain => self.book_house(carried, 0)?,
+                UnwonPotPolicy::Carryover => {
+                    self.carryover = amount;
+                    self.book_house(0, staked)?;
+                    self.env().emit_event(PotCarriedOver {
+                        from_race: self.race_id,
+                        to_race: self.race_id + 1,
+                        amount,
+                    });
+                }
+                UnwonPotPolicy::Refund => {
+                    // Nobody won, so every open paid bet outside a DNF lost
+                    let did_not_finish = &self.latest_result.did_not_finish;
+                    let losers: Vec<usize> = (0..bets.len())
+                        .filter(|&index| {
+                            let bet = &bets[index];
+                            bet.cash_out.is_none()
+                                && !bet.free_bet
+                                && !did_not_finish.contains(&bet.first_pick)
+                                && !did_not_finish.contains(&bet.second_pick)
+                        })
+                        .collect();
+                    self.split_by_stake(bets, &losers, amount, LedgerReason::Refund)?;
+                    self.book_house(0, staked)?;
+                }
+                UnwonPotPolicy::Treasury => {
+                    self.treasury = Self::add(self.treasury, amount)?;
+                    self.book_house(0, staked)?;
+                }
+            }
+
+            self.unwon_pots.insert(self.race_id, &UnwonPot {
+                race_id: self.race_id,
+                amount,
+                policy,
+            });
+            self.env().emit_event(UnwonPotSettled {
+                race_id: self.race_id,
+                amount,
+                policy,
+            });
+            Ok(())
+        }
+
+        /// Split an amount across bets pro-rata by stake
+        /// Rounding dust goes to the treasury
+        fn split_by_stake(&mut self, bets: &[ExactaBet], indices: &[usize], amount: Balance, reason: LedgerReason) -> Result<()> {
+            let total = indices.iter().try_fold(0, |total, &index| Self::add(total, bets[index].amount))?;
+            let mut paid: Balance = 0;
+            for &index in indices {
+                let bet = &bets[index];
+                let share = Self::mul_div(amount, bet.amount, total)?;
+                let payee = self.receipt_holder(bet);
+                match bet.syndicate {
+                    Some(syndicate_id) => self.split_syndicate(syndicate_id, share, reason)?,
+                    None => self.credit_bet(bet, payee, share, reason)?,
+                }
+                if reason == LedgerReason::Refund {
+                    self.emit_refund(payee, share, RefundReason::UnwonPot);
+                }
+                paid = Self::add(paid, share)?;
+            }
+            self.collect_dust(Self::sub(amount, paid)?)
+        }
+
+        /// Set what happens to the exacta pot when nobody wins (owner only)
+        #[ink(message)]
+        pub fn set_unwon_pot_policy(&mut self, policy: UnwonPotPolicy) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.unwon_pot_policy = policy;
+            Ok(())
+        }
+
+        /// Get the unwon pot policy
+        #[ink(message)]
+        pub fn get_unwon_pot_policy(&self) -> UnwonPotPolicy {
+            self.unwon_pot_policy
+        }
+
+        /// Get the pot carried into the next race
+        #[ink(message)]
+        pub fn get_carryover(&self) -> Balance {
+            self.carryover
+        }
+
+        /// Get the unwon pot of a race that had no winners
+        #[ink(message)]
+        pub fn get_unwon_pot(&self, race_id: u64) -> Option<UnwonPot> {
+            self.unwon_pots.get(race_id)
+        }
+
+        /// Add to the pot of the race open for betting from the caller's balance
+        /// The funds join the carried pot: paid to the race's winners by stake,
+        /// or handled by the unwon pot policy when nobody wins
+        #[ink(message)]
+        pub fn sponsor_race(&mut self, race_id: u64, amount: Balance) -> Result<()> {
+            let sponsor = self.env().caller();
+            if self.status != RaceStatus::Betting || race_id != self.bets_race_id() {
+                return Err(Error::RaceNotInBettingPhase);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            let mut sponsorships = self.sponsorships.get(race_id).unwrap_or_default();
+            let position = sponsorships.iter().position(|entry| entry.sponsor == sponsor);
+            if position.is_none() && sponsorships.len() >= MAX_SPONSORS_PER_RACE {
+                return Err(Error::SponsorLimitReached);
+            }
+            self.ensure_not_frozen(sponsor)?;
+            self.debit_balance(sponsor, amount, LedgerReason::Sponsorship)?;
+            self.carryover = Self::add(self.carryover, amount)?;
+
+            let now = self.env().block_timestamp();
+            match position {
+                Some(index) => {
+                    let entry = &mut sponsorships[index];
+                    entry.amount = Self::add(entry.amount, amount)?;
+                    entry.sponsored_at = now;
+                }
+                None => sponsorships.push(Sponsorship { sponsor, amount, sponsored_at: now }),
+            }
+            self.sponsorships.insert(race_id, &sponsorships);
+
+            self.env().emit_event(RaceSponsored {
+                race_id,
+                sponsor,
+                amount,
+                pot: self.carryover,
+            });
+            Ok(())
+        }
+
+        /// Get the sponsors of a race's pot
+        #[ink(message)]
+        pub fn get_sponsorships(&self, race_id: u64) -> Vec<Sponsorship> {
+            self.sponsorships.get(race_id).unwrap_or_default()
+        }
+
+        /// Get the total sponsors added to a race's pot
+        #[ink(message)]
+        pub fn get_sponsored_amount(&self, race_id: u64) -> Balance {
+            self.get_sponsorships(race_id)
+                .iter()
+                .fold(0, |total, entry| total.saturating_add(entry.amount))
+        }
+
+        /// Mint a trophy to a winner when trophies are enabled
+        /// A failed mint is skipped; it never blocks settlement
+        fn mint_trophy(&mut self, winner: AccountId, bet_index: u32, payout: Balance) {
+            use ink::codegen::TraitCallBuilder;
+            use ink::scale::Encode;
+            let Some(minter) = self.trophy_minter else {
+                return;
+            };
+            let trophy = Trophy {
+                race_id: self.race_id,
+                bet_index,
+                exacta: self.latest_result.winning_exacta,
+                payout,
+            };
+            let mut trophies: ink::contract_ref!(BetReceipts) = minter.into();
+            let _ = trophies
+                .call_mut()
+                .mint(winner, ReceiptId::Bytes(trophy.encode()))
+                .ref_time_limit(CALLBACK_GAS_LIMIT)
+                .try_invoke();
+        }
+
+        /// Enable trophies with a PSP34 minter contract, or disable them with None (owner only)
+        #[ink(message)]
+        pub fn set_trophy_minter(&mut self, minter: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.trophy_minter = minter;
+            Ok(())
+        }
+
+        /// Get the trophy minter contract
+        #[ink(message)]
+        pub fn get_trophy_minter(&self) -> Option<AccountId> {
+            self.trophy_minter
+        }
+
+        // ========================================================================
+        // BALANCES AND CLAIMS
+        // ========================================================================
+
+        /// Credit settled winnings (or refunds) to an account
+        /// In claim mode they wait in `unclaimed` until claimed; the deadline
+        /// restarts with every new credit
+        fn credit_winnings(&mut self, account: AccountId, amount: Balance, reason: LedgerReason) -> Result<()> {
+            if amount == 0 {
+                return Ok(());
+            }
+            self.record_winnings(account, amount);
+            if reason == LedgerReason::Payout {
+                self.record_payout(account, amount)?;
+            }
+            if self.claim_mode {
+                let mut unclaimed = self.unclaimed.get(account).unwrap_or_default();
+                unclaimed.amount = Self::add(unclaimed.amount, amount)?;
+                unclaimed.deadline = self.env().block_timestamp().saturating_add(self.claim_period);
+                self.unclaimed.insert(account, &unclaimed);
+            } else {
+                self.credit_balance(account, amount, reason)?;
+            }
+            Ok(())
+        }
+
+        /// Announce a refund so accounting systems can reconcile it
+        fn emit_refund(&self, bettor: AccountId, amount: Balance, reason: RefundReason) {
+            self.env().emit_event(BetRefunded { bettor, amount, reason });
+        }
+
+        /// Add to an account's balance (a PSP22 mint)
+        fn credit_balance(&mut self, account: AccountId, amount: Balance, reason: LedgerReason) -> Result<()> {
+            self.change_balance(account, amount, true, reason)?;
+            self.total_balances = Self::add(self.total_balances, amount)?;
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(account),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Take from an account's balance (a PSP22 burn)
+        fn debit_balance(&mut self, account: AccountId, amount: Balance, reason: LedgerReason) -> Result<()> {
+            self.change_balance(account, amount, false, reason)?;
+            self.total_balances = Self::sub(self.total_balances, amount)?;
+            self.env().emit_event(Transfer {
+                from: Some(account),
+                to: None,
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Move balance between accounts
+        fn move_balance(&mut self, from: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            self.ensure_not_frozen(from)?;
+            self.ensure_not_excluded(to)?;
+            self.change_balance(from, amount, false, LedgerReason::Transfer)?;
+            self.change_balance(to, amount, true, LedgerReason::Transfer)?;
+
+            let now = self.env().block_timestamp();
+            self.last_activity.insert(from, &now);
+            self.last_activity.insert(to, &now);
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Add to (`credit`) or take from a balance and append it to the ledger
+        fn change_balance(&mut self, account: AccountId, amount: Balance, credit: bool, reason: LedgerReason) -> Result<()> {
+            let balance = self.balances.get(account).unwrap_or(0);
+            let (balance_after, delta) = if credit {
+                (Self::add(balance, amount)?, amount as i128)
+            } else {
+                (balance.checked_sub(amount).ok_or(Error::InsufficientBalance)?, -(amount as i128))
+            };
+            self.balances.insert(account, &balance_after);
+
+            let index = self.ledger_count.get(account).unwrap_or(0);
+            self.ledger.insert((account, index), &LedgerEntry {
+                reason,
+                delta,
+                balance_after,
+                race_id: self.race_id,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.ledger_count.insert(account, &(index + 1));
+            Ok(())
+        }
+
+        /// Get an account's balance ledger, oldest first
+        #[ink(message)]
+        pub fn get_ledger(&self, account: AccountId, offset: u32, limit: u32) -> Vec<LedgerEntry> {
+            let count = self.ledger_count.get(account).unwrap_or(0);
+            (offset..count.min(offset.saturating_add(limit)))
+                .filter_map(|index| self.ledger.get((account, index)))
+                .collect()
+        }
+
+        /// Get number of ledger entries of an account
+        #[ink(message)]
+        pub fn get_ledger_count(&self, account: AccountId) -> u32 {
+            self.ledger_count.get(account).unwrap_or(0)
+        }
+
+        /// Checked `a + b`
+        fn add(a: Balance, b: Balance) -> Result<Balance> {
+            a.checked_add(b).ok_or(Error::ArithmeticOverflow)
+        }
+
+        /// Checked `a - b`
+        fn sub(a: Balance, b: Balance) -> Result<Balance> {
+            a.checked_sub(b).ok_or(Error::ArithmeticOverflow)
+        }
+
+        /// Checked `amount × numerator / denominator`
+        fn mul_div(amount: Balance, numerator: u128, denominator: u128) -> Result<Balance> {
+            amount
+                .checked_mul(numerator)
+                .and_then(|product| product.checked_div(denominator))
+                .ok_or(Error::ArithmeticOverflow)
+        }
+
+        /// Stake × odds (odds scaled by PRECISION)
+        fn apply_odds(amount: Balance, odds: u64) -> Result<Balance> {
+            Self::mul_div(amount, odds as u128, PRECISION as u128)
+        }
+
+        /// Deposit funds into the caller's balance
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            self.credit_deposit(caller, amount)
+        }
+
+        /// Credit many accounts in one call (owner only)
+        /// The transferred value must equal the sum of the entries
+        #[ink(message, payable)]
+        pub fn batch_deposit(&mut self, entries: Vec<(AccountId, Balance)>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let total = entries
+                .iter()
+                .try_fold(0, |total, (_, amount)| Self::add(total, *amount))?;
+            if total != self.env().transferred_value() {
+                return Err(Error::DepositMismatch);
+            }
+
+            for (account, amount) in entries {
+                self.credit_deposit(account, amount)?;
+            }
+
+            Ok(())
+        }
+
+        /// Credit a deposit that arrived from another chain (bridges only)
+        /// Called by an XCM-transact or bridge contract with the transferred
+        /// value; each message ID is credited once and kept for audit
+        #[ink(message, payable)]
+        pub fn deposit_cross_chain(&mut self, account: AccountId, origin_chain: u32, message_id: Hash) -> Result<()> {
+            let bridge = self.env().caller();
+            if !self.bridges.contains(&bridge) {
+                return Err(Error::NotBridge);
+            }
+            if self.cross_chain_deposits.contains(message_id) {
+                return Err(Error::DuplicateMessage);
+            }
+            let amount = self.env().transferred_value();
+            self.credit_deposit(account, amount)?;
+
+            self.cross_chain_deposits.insert(message_id, &CrossChainDeposit {
+                account,
+                amount,
+                origin_chain,
+                message_id,
+                bridge,
+                race_id: self.race_id,
+                timestamp: self.env().block_timestamp(),
+            });
+            self.env().emit_event(CrossChainDeposited {
+                account,
+                origin_chain,
+                message_id,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Get the provenance of a cross-chain deposit
+        #[ink(message)]
+        pub fn get_cross_chain_deposit(&self, message_id: Hash) -> Option<CrossChainDeposit> {
+            self.cross_chain_deposits.get(message_id)
+        }
+
+        /// Allow an XCM or bridge contract to credit deposits (owner only)
+        #[ink(message)]
+        pub fn add_bridge(&mut self, bridge: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if !self.bridges.contains(&bridge) {
+                self.bridges.push(bridge);
+            }
+            Ok(())
+        }
+
+        /// Remove a bridge (owner only)
+        #[ink(message)]
+        pub fn remove_bridge(&mut self, bridge: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.bridges.retain(|&account| account != bridge);
+            Ok(())
+        }
+
+        /// Get the registered bridges
+        #[ink(message)]
+        pub fn get_bridges(&self) -> Vec<AccountId> {
+            self.bridges.clone()
+        }
+
+        /// Credit a deposit to an account's balance
+        fn credit_deposit(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.ensure_not_excluded(account)?;
+            self.ensure_attested(account)?;
+
+            self.credit_balance(account, amount, LedgerReason::Deposit)?;
+            self.last_activity.insert(account, &self.env().block_timestamp());
+
+            self.env().emit_event(Deposited {
+                account,
+                race_id: self.race_id,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw funds from the caller's balance
+        /// Unavailable when withdrawals go through the operator queue
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<()> {
+            self.guarded(|this| {
+                if this.custodial_withdrawals {
+                    return Err(Error::CustodialWithdrawals);
+                }
+                let caller = this.env().caller();
+                this.ensure_not_frozen(caller)?;
+                if amount == 0 {
+                    return Err(Error::ZeroBetAmount);
+                }
+
+                let fee = this.charge_withdrawal(caller, amount)?;
+                this.debit_balance(caller, amount, LedgerReason::Withdrawal)?;
+                this.last_activity.insert(caller, &this.env().block_timestamp());
+                if this.env().transfer(caller, amount - fee).is_err() {
+                    return Err(Error::TransferFailed);
+                }
+
+                this.env().emit_event(Withdrawn {
+                    account: caller,
+                    race_id: this.race_id,
+                    amount,
+                    fee,
+                });
+
+                Ok(())
+            })
+        }
+
+        /// File a withdrawal for the operator to pay out off-chain
+        /// The amount leaves the balance now and comes back if rejected
+        #[ink(message)]
+        pub fn request_withdrawal(&mut self, amount: Balance) -> Result<u32> {
+            let caller = self.env().caller();
+            self.ensure_not_frozen(caller)?;
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let fee = self.charge_withdrawal(caller, amount)?;
+            self.debit_balance(caller, amount, LedgerReason::Withdrawal)?;
+            let now = self.env().block_timestamp();
+            self.last_activity.insert(caller, &now);
+
+            let request_id = self.withdrawal_count;
+            self.withdrawal_requests.insert(request_id, &WithdrawalRequest {
+                id: request_id,
+                account: caller,
+                amount,
+                fee,
+                status: WithdrawalStatus::Pending,
+                requested_at: now,
+                resolved_at: None,
+            });
+            self.withdrawal_count += 1;
+            self.pending_withdrawals.push(request_id);
+
+            self.env().emit_event(WithdrawalRequested {
+                request_id,
+                account: caller,
+                amount,
+            });
+
+            Ok(request_id)
+        }
+
+        /// Mark a pending withdrawal as paid out (owner or settlement operators)
+        #[ink(message)]
+        pub fn process_withdrawal(&mut self, request_id: u32) -> Result<()> {
+            self.resolve_withdrawal(request_id, WithdrawalStatus::Processed)
+        }
+
+        /// Reject a pending withdrawal, returning the funds to the balance
+        /// (owner or settlement operators)
+        #[ink(message)]
+        pub fn reject_withdrawal(&mut self, request_id: u32) -> Result<()> {
+            self.resolve_withdrawal(request_id, WithdrawalStatus::Rejected)
+        }
+
+        /// Close a pending withdrawal as processed or rejected
+        fn resolve_withdrawal(&mut self, request_id: u32, status: WithdrawalStatus) -> Result<()> {
+            self.ensure_operator(self.env().caller())?;
+            let mut request = self.withdrawal_requests.get(request_id).ok_or(Error::WithdrawalNotFound)?;
+            if request.status != WithdrawalStatus::Pending {
+                return Err(Error::WithdrawalNotPending);
+            }
+
+            // A rejection returns the fee and frees up the daily cap
+            if status == WithdrawalStatus::Rejected {
+                self.treasury = Self::sub(self.treasury, request.fee)?;
+                self.credit_balance(request.account, request.amount, LedgerReason::WithdrawalRejected)?;
+                let mut history = self.withdrawal_history.get(request.account).unwrap_or_default();
+                if let Some(index) = history.iter().position(|&entry| entry == (request.requested_at, request.amount)) {
+                    history.remove(index);
+                    self.withdrawal_history.insert(request.account, &history);
+                }
+            }
+            request.status = status;
+            request.resolved_at = Some(self.env().block_timestamp());
+            self.withdrawal_requests.insert(request_id, &request);
+            self.pending_withdrawals.retain(|&id| id != request_id);
+
+            self.env().emit_event(WithdrawalResolved {
+                request_id,
+                account: request.account,
+                amount: request.amount,
+                status,
+            });
+
+            Ok(())
+        }
+
+        /// Route withdrawals through the operator queue (owner only)
+        #[ink(message)]
+        pub fn set_custodial_withdrawals(&mut self, enabled: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.custodial_withdrawals = enabled;
+            Ok(())
+        }
+
+        /// Whether withdrawals go through the operator queue
+        #[ink(message)]
+        pub fn get_custodial_withdrawals(&self) -> bool {
+            self.custodial_withdrawals
+        }
+
+        /// Check a withdrawal against the daily cap, record it and book the
+        /// fee to the treasury; returns the fee
+        fn charge_withdrawal(&mut self, account: AccountId, amount: Balance) -> Result<Balance> {
+            let now = self.env().block_timestamp();
+            let mut history = self.withdrawal_history.get(account).unwrap_or_default();
+            history.retain(|(timestamp, _)| now.saturating_sub(*timestamp) < DAY_MS);
+            let withdrawn = Self::add(Self::window_total(&history, now, DAY_MS), amount)?;
+            if self.daily_withdrawal_cap > 0 && withdrawn > self.daily_withdrawal_cap {
+                return Err(Error::WithdrawalCapExceeded);
+            }
+            history.push((now, amount));
+            self.withdrawal_history.insert(account, &history);
+
+            let fee = Self::apply_odds(amount, self.withdrawal_fee)?;
+            self.treasury = Self::add(self.treasury, fee)?;
+            Ok(fee)
+        }
+
+        /// Set the withdrawal fee (scaled by PRECISION) and the daily
+        /// withdrawal cap per account, 0 for no cap (owner only)
+        #[ink(message)]
+        pub fn set_withdrawal_limits(&mut self, fee: u64, daily_cap: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if fee > PRECISION {
+                return Err(Error::InvalidConfig);
+            }
+            self.withdrawal_fee = fee;
+            self.daily_withdrawal_cap = daily_cap;
+            Ok(())
+        }
+
+        /// Get the withdrawal fee and the daily withdrawal cap
+        #[ink(message)]
+        pub fn get_withdrawal_limits(&self) -> (u64, Balance) {
+            (self.withdrawal_fee, self.daily_withdrawal_cap)
+        }
+
+        /// Get how much an account can still withdraw in the current 24 hours
+        /// None when there is no cap
+        #[ink(message)]
+        pub fn get_withdrawal_allowance(&self, account: AccountId) -> Option<Balance> {
+            if self.daily_withdrawal_cap == 0 {
+                return None;
+            }
+            let history = self.withdrawal_history.get(account).unwrap_or_default();
+            let withdrawn = Self::window_total(&history, self.env().block_timestamp(), DAY_MS);
+            Some(self.daily_withdrawal_cap.saturating_sub(withdrawn))
+        }
+
+        /// Get a withdrawal request
+        #[ink(message)]
+        pub fn get_withdrawal_request(&self, request_id: u32) -> Option<WithdrawalRequest> {
+            self.withdrawal_requests.get(request_id)
+        }
+
+        /// Get all withdrawal requests waiting on the operator, oldest first
+        #[ink(message)]
+        pub fn get_pending_withdrawals(&self) -> Vec<WithdrawalRequest> {
+            self.pending_withdrawals
+                .iter()
+                .filter_map(|&id| self.withdrawal_requests.get(id))
+                .collect()
+        }
+
+        /// Move part of the caller's balance to another account
+        #[ink(message)]
+        pub fn transfer_balance(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.move_balance(caller, to, amount)?;
+
+            self.env().emit_event(BalanceTransferred {
+                from: caller,
+                to,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Run `f` holding the reentrancy lock
+        fn guarded<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+            if self.reentrancy_lock {
+                return Err(Error::ReentrantCall);
+            }
+            self.reentrancy_lock = true;
+            let result = f(self);
+            self.reentrancy_lock = false;
+            result
+        }
+
+        /// Sweep an account's expired unclaimed winnings to the treasury
+        #[ink(message)]
+        pub fn sweep_unclaimed(&mut self, account: AccountId) -> Result<Balance> {
+            let unclaimed = self.unclaimed.get(account).ok_or(Error::NothingToClaim)?;
+            if self.env().block_timestamp() <= unclaimed.deadline {
+                return Err(Error::GracePeriodActive);
+            }
+
+            self.unclaimed.remove(account);
+            self.treasury = Self::add(self.treasury, unclaimed.amount)?;
+
+            self.env().emit_event(UnclaimedSwept {
+                account,
+                amount: unclaimed.amount,
+            });
+
+            Ok(unclaimed.amount)
+        }
+
+        /// Sweep a dormant account's balance to the treasury (owner only)
+        #[ink(message)]
+        pub fn sweep_dormant_balance(&mut self, account: AccountId) -> Result<Balance> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let balance = self.balances.get(account).unwrap_or(0);
+            if balance == 0 {
+                return Err(Error::InsufficientBalance);
+            }
+            let last_activity = self.last_activity.get(account).unwrap_or(0);
+            if self.env().block_timestamp() <= last_activity.saturating_add(self.dormancy_period) {
+                return Err(Error::GracePeriodActive);
+            }
+
+            self.debit_balance(account, balance, LedgerReason::Sweep)?;
+            self.treasury = Self::add(self.treasury, balance)?;
+
+            self.env().emit_event(DormantBalanceSwept {
+                account,
+                amount: balance,
+            });
+
+            Ok(balance)
+        }
+
+        /// Configure claim mode, claim window and dormancy period (ms)
+        #[ink(message)]
+        pub fn set_claim_config(&mut self, claim_mode: bool, claim_period: u64, dormancy_period: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if claim_period == 0 || dormancy_period == 0 {
+                return Err(Error::InvalidDuration);
+            }
+            self.claim_mode = claim_mode;
+            self.claim_period = claim_period;
+            self.dormancy_period = dormancy_period;
+            Ok(())
+        }
+
+        /// Get an account's unclaimed winnings and claim deadline
+        #[ink(message)]
+        pub fn get_unclaimed(&self, account: AccountId) -> Option<UnclaimedWinnings> {
+            self.unclaimed.get(account)
+        }
+
+        /// Get remaining grace periods (ms) for an account:
+        /// (until unclaimed winnings expire, until the balance becomes dormant)
+        #[ink(message)]
+        pub fn get_grace_period(&self, account: AccountId) -> (u64, u64) {
+            let now = self.env().block_timestamp();
+            let claim_remaining = self
+                .unclaimed
+                .get(account)
+                .map(|unclaimed| unclaimed.deadline.saturating_sub(now))
+                .unwrap_or(0);
+            let dormancy_remaining = match self.last_activity.get(account) {
+                Some(last_activity) => (last_activity + self.dormancy_period).saturating_sub(now),
+                None => 0,
+            };
+            (claim_remaining, dormancy_remaining)
+        }
+
+        /// Get claim mode, claim window and dormancy period (ms)
+        #[ink(message)]
+        pub fn get_claim_config(&self) -> (bool, u64, u64) {
+            (self.claim_mode, self.claim_period, self.dormancy_period)
+        }
+
+        // ========================================================================
+        // PSP22 TOKEN INTERFACE
+        // ========================================================================
+
+        /// Sum of all internal balances
+        #[ink(message, selector = 0x162df8c2)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_balances
+        }
+
+        /// Internal balance of an account
+        #[ink(message, selector = 0x6568382f)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        /// Remaining amount `spender` may transfer or bet from `owner`'s balance
+        #[ink(message, selector = 0x4d47d921)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        /// Transfer part of the caller's balance
+        /// Zero-value and self transfers succeed without effect
+        #[ink(message, selector = 0xdb20f9f5)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> core::result::Result<(), PSP22Error> {
+            let from = self.env().caller();
+            if from == to || value == 0 {
+                return Ok(());
+            }
+            Ok(self.move_balance(from, to, value)?)
+        }
+
+        /// Transfer from `from`'s balance using the caller's allowance
+        #[ink(message, selector = 0x54b3c76e)]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> core::result::Result<(), PSP22Error> {
+            let spender = self.env().caller();
+            if from == to || value == 0 {
+                return Ok(());
+            }
+            let allowance = self.allowance(from, spender);
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+
+            self.move_balance(from, to, value)?;
+            self.set_allowance(from, spender, allowance - value);
+            Ok(())
+        }
+
+        /// Let `spender` transfer or bet from the caller's balance up to `value`
+        /// Replaces any previous allowance
+        #[ink(message, selector = 0xb20f1bbd)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> core::result::Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, value);
+            Ok(())
+        }
+
+        /// Raise the allowance of `spender`
+        #[ink(message, selector = 0x96d6b57a)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta_value: Balance) -> core::result::Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender);
+            self.set_allowance(owner, spender, allowance.saturating_add(delta_value));
+            Ok(())
+        }
+
+        /// Lower the allowance of `spender`
+        #[ink(message, selector = 0xfecb57d5)]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta_value: Balance) -> core::result::Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender);
+            if allowance < delta_value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self.set_allowance(owner, spender, allowance - delta_value);
+            Ok(())
+        }
+
+        /// Store an allowance and announce it
+        fn set_allowance(&mut self, owner: AccountId, spender: AccountId, amount: Balance) {
+            self.allowances.insert((owner, spender), &amount);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                amount,
+            });
+        }
+
+        // ========================================================================
+        // RESPONSIBLE GAMBLING
+        // ========================================================================
+
+        /// Set the caller's wager and loss limits (0 = no limit)
+        /// Tighter limits apply immediately; looser ones after a 24-hour delay
+        #[ink(message)]
+        pub fn set_my_limits(&mut self, limits: BettingLimits) -> Result<()> {
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            let current = self.get_my_limits(caller);
+
+            // Limits in another unit can't be compared, so switching counts
+            // as raising them unless none are in force yet
+            let unlimited = BettingLimits { in_fiat: current.in_fiat, ..Default::default() };
+            let tighter = if limits.in_fiat == current.in_fiat || current == unlimited {
+                BettingLimits {
+                    daily_wager: Self::stricter(current.daily_wager, limits.daily_wager),
+                    weekly_wager: Self::stricter(current.weekly_wager, limits.weekly_wager),
+                    daily_loss: Self::stricter(current.daily_loss, limits.daily_loss),
+                    weekly_loss: Self::stricter(current.weekly_loss, limits.weekly_loss),
+                    in_fiat: limits.in_fiat,
+                }
+            } else {
+                current
+            };
+            self.betting_limits.insert(caller, &tighter);
+
+            if tighter == limits {
+                self.pending_limits.remove(caller);
+            } else {
+                self.pending_limits.insert(caller, &(limits, now + LIMIT_INCREASE_DELAY));
+            }
+
+            Ok(())
+        }
+
+        /// Get the limits currently in force for an account
+        #[ink(message)]
+        pub fn get_my_limits(&self, account: AccountId) -> BettingLimits {
+            match self.pending_limits.get(account) {
+                Some((limits, effective_at)) if self.env().block_timestamp() >= effective_at => limits,
+                _ => self.betting_limits.get(account).unwrap_or_default(),
+            }
+        }
+
+        /// Get a pending limit increase: (limits, effective_at)
+        #[ink(message)]
+        pub fn get_pending_limits(&self, account: AccountId) -> Option<(BettingLimits, u64)> {
+            self.pending_limits
+                .get(account)
+                .filter(|(_, effective_at)| self.env().block_timestamp() < *effective_at)
+        }
+
+        /// Block the caller from betting and depositing for `duration` ms
+        /// An active exclusion can be extended but never shortened
+        #[ink(message)]
+        pub fn self_exclude(&mut self, duration: u64) -> Result<()> {
+            if duration == 0 {
+                return Err(Error::InvalidDuration);
+            }
+            let caller = self.env().caller();
+            let until = self.env().block_timestamp() + duration;
+            let until = until.max(self.excluded_until.get(caller).unwrap_or(0));
+            self.excluded_until.insert(caller, &until);
+
+            self.env().emit_event(SelfExclusionStarted {
+                account: caller,
+                until,
+            });
+
+            Ok(())
+        }
+
+        /// Get the end of an account's active self-exclusion, if any
+        #[ink(message)]
+        pub fn get_exclusion(&self, account: AccountId) -> Option<u64> {
+            self.excluded_until
+                .get(account)
+                .filter(|until| self.env().block_timestamp() < *until)
+        }
+
+        /// Reject accounts under an active self-exclusion
+        fn ensure_not_excluded(&self, account: AccountId) -> Result<()> {
+            if self.get_exclusion(account).is_some() {
+                return Err(Error::SelfExcluded);
+            }
+            Ok(())
+        }
+
+        /// The stricter of two limits, where 0 means no limit
+        fn stricter(a: Balance, b: Balance) -> Balance {
+            match (a, b) {
+                (0, limit) | (limit, 0) => limit,
+                (a, b) => a.min(b),
+            }
+        }
+
+        /// Sum of entries newer than `window` ms
+        fn window_total(entries: &[(u64, Balance)], now: u64, window: u64) -> Balance {
+            entries
+                .iter()
+                .filter(|(timestamp, _)| now.saturating_sub(*timestamp) < window)
+                .fold(0, |total: Balance, (_, amount)| total.saturating_add(*amount))
+        }
+
+        /// Check a wager against the account's limits and record it
+        fn check_limits(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            let now = self.env().block_timestamp();
+            let mut limits = self.get_my_limits(account);
+            if limits.in_fiat {
+                limits = BettingLimits {
+                    daily_wager: self.fiat_to_asset(limits.daily_wager)?,
+                    weekly_wager: self.fiat_to_asset(limits.weekly_wager)?,
+                    daily_loss: self.fiat_to_asset(limits.daily_loss)?,
+                    weekly_loss: self.fiat_to_asset(limits.weekly_loss)?,
+                    in_fiat: false,
+                };
+            }
+
+            let mut wagers = self.wager_history.get(account).unwrap_or_default();
+            let mut winnings = self.winnings_history.get(account).unwrap_or_default();
+            wagers.retain(|(timestamp, _)| now.saturating_sub(*timestamp) < WEEK_MS);
+            winnings.retain(|(timestamp, _)| now.saturating_sub(*timestamp) < WEEK_MS);
+
+            for (window, wager_limit, loss_limit) in [
+                (DAY_MS, limits.daily_wager, limits.daily_loss),
+                (WEEK_MS, limits.weekly_wager, limits.weekly_loss),
+            ] {
+                let wagered = Self::add(Self::window_total(&wagers, now, window), amount)?;
+                let won = Self::window_total(&winnings, now, window);
+                if wager_limit > 0 && wagered > wager_limit {
+                    return Err(Error::WagerLimitExceeded);
+                }
+                if loss_limit > 0 && wagered.saturating_sub(won) > loss_limit {
+                    return Err(Error::LossLimitExceeded);
+                }
+            }
+
+            wagers.push((now, amount));
+            self.wager_history.insert(account, &wagers);
+            self.winnings_history.insert(account, &winnings);
+            Ok(())
+        }
+
+        /// Record winnings for loss-limit accounting
+        fn record_winnings(&mut self, account: AccountId, amount: Balance) {
+            let mut winnings = self.winnings_history.get(account).unwrap_or_default();
+            winnings.push((self.env().block_timestamp(), amount));
+            self.winnings_history.insert(account, &winnings);
+        }
+
+        // ========================================================================
+        // PRICE ORACLE
+        // ========================================================================
+
+        /// Set the betting asset's decimals (owner only)
+        /// Only while no balances, stakes, treasury or bankroll are held, since
+        /// stored amounts aren't rescaled
+        #[ink(message)]
+        pub fn set_asset_decimals(&mut self, decimals: u8) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if decimals > MAX_ASSET_DECIMALS {
+                return Err(Error::InvalidConfig);
+            }
+            if self.total_balances > 0 || self.total_pot > 0 || self.treasury > 0 || self.bankroll > 0 {
+                return Err(Error::FundsHeld);
+            }
+            self.asset_decimals = decimals;
+            Ok(())
+        }
+
+        /// Get the betting asset's decimals
+        #[ink(message)]
+        pub fn get_asset_decimals(&self) -> u8 {
+            self.asset_decimals
+        }
+
+        /// Scale an asset amount to the internal precision
+        fn to_internal(&self, amount: Balance) -> Result<Balance> {
+            match self.asset_decimals.cmp(&INTERNAL_DECIMALS) {
+                core::cmp::Ordering::Less => Self::mul_div(amount, 10u128.pow(u32::from(INTERNAL_DECIMALS - self.asset_decimals)), 1),
+                core::cmp::Ordering::Equal => Ok(amount),
+                core::cmp::Ordering::Greater => Ok(amount / 10u128.pow(u32::from(self.asset_decimals - INTERNAL_DECIMALS))),
+            }
+        }
+
+        /// Scale an internal amount back to asset units, rounding down
+        fn to_asset_units(&self, amount: Balance) -> Result<Balance> {
+            match self.asset_decimals.cmp(&INTERNAL_DECIMALS) {
+                core::cmp::Ordering::Less => Ok(amount / 10u128.pow(u32::from(INTERNAL_DECIMALS - self.asset_decimals))),
+                core::cmp::Ordering::Equal => Ok(amount),
+                core::cmp::Ordering::Greater => Self::mul_div(amount, 10u128.pow(u32::from(self.asset_decimals - INTERNAL_DECIMALS)), 1),
+            }
+        }
+
+        /// Set the price feed and how old its quote may get (owner only)
+        /// Clears the cached quote; None leaves fiat limits without a price
+        #[ink(message)]
+        pub fn set_price_feed(&mut self, feed: Option<AccountId>, max_age: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if max_age == 0 {
+                return Err(Error::InvalidConfig);
+            }
+            self.price_feed = feed;
+            self.max_price_age = max_age;
+            self.fiat_rate = None;
+            Ok(())
+        }
+
+        /// Read the price feed into the cache, callable by anyone
+        #[ink(message)]
+        pub fn refresh_price(&mut self) -> Result<FiatRate> {
+            use ink::codegen::TraitCallBuilder;
+            let feed = self.price_feed.ok_or(Error::PriceFeedUnavailable)?;
+            let feed: ink::contract_ref!(PriceFeed) = feed.into();
+            let (price, updated_at) = match feed.call().latest_price().try_invoke() {
+                Ok(Ok(quote)) => quote,
+                _ => return Err(Error::PriceFeedUnavailable),
+            };
+            if price == 0 {
+                return Err(Error::PriceFeedUnavailable);
+            }
+            let rate = FiatRate {
+                price,
+                updated_at: updated_at.min(self.env().block_timestamp()),
+            };
+            self.fiat_rate = Some(rate);
+            Ok(rate)
+        }
+
+        /// Set the minimum and maximum stake in fiat cents, 0 for no limit (owner only)
+        #[ink(message)]
+        pub fn set_fiat_bet_limits(&mut self, min_bet: u128, max_bet: u128) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if max_bet > 0 && min_bet > max_bet {
+                return Err(Error::InvalidConfig);
+            }
+            self.fiat_bet_limits = (min_bet, max_bet);
+            Ok(())
+        }
+
+        /// Get the minimum and maximum stake in fiat cents
+        #[ink(message)]
+        pub fn get_fiat_bet_limits(&self) -> (u128, u128) {
+            self.fiat_bet_limits
+        }
+
+        /// Get the cached price quote
+        #[ink(message)]
+        pub fn get_fiat_rate(&self) -> Option<FiatRate> {
+            self.fiat_rate
+        }
+
+        /// Cached price, refused once older than the allowed age
+        fn fresh_price(&self) -> Result<u128> {
+            let rate = self.fiat_rate.ok_or(Error::PriceFeedUnavailable)?;
+            if self.env().block_timestamp() > rate.updated_at.saturating_add(self.max_price_age) {
+                return Err(Error::PriceStale);
+            }
+            Ok(rate.price)
+        }
+
+        /// Asset units worth `cents` at the cached price (0 stays 0)
+        fn fiat_to_asset(&self, cents: u128) -> Result<Balance> {
+            if cents == 0 {
+                return Ok(0);
+            }
+            self.to_asset_units(Self::mul_div(cents, ASSET_UNIT, self.fresh_price()?)?)
+        }
+
+        /// Check a stake against the fiat minimum and maximum bet
+        fn check_fiat_bet_limits(&self, amount: Balance) -> Result<()> {
+            let (min_bet, max_bet) = self.fiat_bet_limits;
+            if min_bet == 0 && max_bet == 0 {
+                return Ok(());
+            }
+            let value = Self::mul_div(self.to_internal(amount)?, self.fresh_price()?, ASSET_UNIT)?;
+            if value < min_bet {
+                return Err(Error::BetBelowMinimum);
+            }
+            if max_bet > 0 && value > max_bet {
+                return Err(Error::BetAboveMaximum);
+            }
+            Ok(())
+        }
+
+        // ========================================================================
+        // LOYALTY POINTS
+        // ========================================================================
+
+        /// Bookkeeping shared by every wager: self-limits, loyalty points
+        /// and wagering progress
+        fn on_wager(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            self.ensure_not_frozen(account)?;
+            self.ensure_whitelisted(account)?;
+            self.ensure_attested(account)?;
+            self.ensure_not_excluded(account)?;
+            self.check_rate_limit(account)?;
+            self.check_fiat_bet_limits(amount)?;
+            self.check_limits(account, amount)?;
+            self.award_points(account, amount)?;
+            if let Some((wagered, required)) = self.wagering_progress.get(account) {
+                self.wagering_progress.insert(account, &(Self::add(wagered, amount)?, required));
+            }
+            self.record_wager(account, amount)
+        }
+
+        /// Count a bet placement in the current block, failing past the per-block limit
+        fn check_rate_limit(&mut self, account: AccountId) -> Result<()> {
+            if self.max_bets_per_block == 0 {
+                return Ok(());
+            }
+            let block = self.env().block_number();
+            let placed = match self.block_bets.get(account) {
+                Some((last_block, placed)) if last_block == block => placed,
+                _ => 0,
+            };
+            if placed >= self.max_bets_per_block {
+                return Err(Error::RateLimited);
+            }
+            self.block_bets.insert(account, &(block, placed + 1));
+            Ok(())
+        }
+
+        /// Count a wager towards the global statistics
+        fn record_wager(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            if !self.known_bettors.contains(account) {
+                self.known_bettors.insert(account, &());
+                self.global_stats.unique_bettors += 1;
+            }
+            self.global_stats.total_volume = Self::add(self.global_stats.total_volume, amount)?;
+            self.global_stats.total_bets += 1;
+            Ok(())
+        }
+
+        /// Count winnings towards the global statistics, emitting RecordPayout on a new record
+        fn record_payout(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            self.global_stats.total_payouts = Self::add(self.global_stats.total_payouts, amount)?;
+            let previous_record = self.global_stats.biggest_payout;
+            if amount > previous_record {
+                self.global_stats.biggest_payout = amount;
+                self.global_stats.biggest_payout_race = self.race_id;
+                self.global_stats.biggest_payout_bettor = Some(account);
+                self.env().emit_event(RecordPayout {
+                    race_id: self.race_id,
+                    bettor: account,
+                    amount,
+                    previous_record,
+                });
+            }
+            Ok(())
+        }
+
+        /// Award loyalty points for a wager
+        fn award_points(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            let points = Self::mul_div(amount, self.points_accrual_rate as u128, PRECISION as u128)?;
+            if points == 0 {
+                return Ok(());
+            }
+            let total_points = Self::add(self.loyalty_points.get(account).unwrap_or(0), points)?;
+            self.loyalty_points.insert(account, &total_points);
+
+            self.env().emit_event(PointsAwarded {
+                account,
+                points,
+                total_points,
+            });
+            Ok(())
+        }
+
+        /// Convert loyalty points into free-bet credits
+        #[ink(message)]
+        pub fn redeem_points(&mut self, points: u128) -> Result<Balance> {
+            let caller = self.env().caller();
+            let available = self.loyalty_points.get(caller).unwrap_or(0);
+            let credits = points / self.points_per_credit;
+            if points > available {
+                return Err(Error::InsufficientPoints);
+            }
+            if credits == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            // Only whole credits are redeemed, leftover points stay
+            let spent = Self::mul_div(credits, self.points_per_credit, 1)?;
+            self.loyalty_points.insert(caller, &Self::sub(available, spent)?);
+            let free_bets = self.free_bet_credits.get(caller).unwrap_or(0);
+            self.free_bet_credits.insert(caller, &Self::add(free_bets, credits)?);
+
+            self.env().emit_event(PointsRedeemed {
+                account: caller,
+                points: spent,
+                credits,
+            });
+
+            Ok(credits)
+        }
+
+        /// Grant free-bet credits to an account (owner only)
+        /// Each grant adds `amount × wagering_requirement` to the turnover
+        /// needed before credits can be released to the real balance
+        #[ink(message)]
+        pub fn grant_free_bet_credits(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let credits = self.free_bet_credits.get(account).unwrap_or(0);
+            self.free_bet_credits.insert(account, &Self::add(credits, amount)?);
+            let (wagered, required) = self.wagering_progress.get(account).unwrap_or((0, 0));
+            let added = Self::mul_div(amount, self.wagering_requirement as u128, PRECISION as u128)?;
+            self.wagering_progress.insert(account, &(wagered, Self::add(required, added)?));
+
+            self.env().emit_event(FreeBetCreditsGranted { account, amount });
+
+            Ok(())
+        }
+
+        /// Move remaining free-bet credits into the real balance once the
+        /// wagering requirement has been met
+        #[ink(message)]
+        pub fn release_free_bet_credits(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            let (wagered, required) = self.wagering_progress.get(caller).unwrap_or((0, 0));
+            if wagered < required {
+                return Err(Error::WageringRequirementNotMet);
+            }
+            let credits = self.free_bet_credits.get(caller).unwrap_or(0);
+            if credits == 0 {
+                return Err(Error::InsufficientCredits);
+            }
+
+            self.free_bet_credits.remove(caller);
+            self.wagering_progress.remove(caller);
+            self.credit_balance(caller, credits, LedgerReason::Bonus)?;
+
+            self.env().emit_event(FreeBetCreditsReleased {
+                account: caller,
+                amount: credits,
+            });
+
+            Ok(credits)
+        }
+
+        /// Set turnover required per granted credit (scaled by PRECISION)
+        #[ink(message)]
+        pub fn set_wagering_requirement(&mut self, requirement: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.wagering_requirement = requirement;
+            Ok(())
+        }
+
+        /// Get an account's wagering progress: (wagered, required)
+        #[ink(message)]
+        pub fn get_wagering_progress(&self, account: AccountId) -> (Balance, Balance) {
+            self.wagering_progress.get(account).unwrap_or((0, 0))
+        }
+
+        /// Set points accrual rate (scaled by PRECISION) and points per credit
+        #[ink(message)]
+        pub fn set_loyalty_config(&mut self, accrual_rate: u64, points_per_credit: u128) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if points_per_credit == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.points_accrual_rate = accrual_rate;
+            self.points_per_credit = points_per_credit;
+            Ok(())
+        }
+
+        /// Get an account's loyalty points
+        #[ink(message)]
+        pub fn get_points(&self, account: AccountId) -> u128 {
+            self.loyalty_points.get(account).unwrap_or(0)
+        }
+
+        /// Get an account's free-bet credits
+        #[ink(message)]
+        pub fn get_free_bet_credits(&self, account: AccountId) -> Balance {
+            self.free_bet_credits.get(account).unwrap_or(0)
+        }
+
+        /// Grant bonus funds to an account (owner only)
+        /// Like free-bet credits, each grant adds `amount × wagering_requirement`
+        /// to the turnover needed before bonus funds become withdrawable
+        #[ink(message)]
+        pub fn grant_bonus(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.award_bonus(account, amount)
+        }
+
+        /// Credit bonus funds and add their wagering requirement
+        fn award_bonus(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            self.credit_bonus(account, amount)?;
+            let (wagered, required) = self.wagering_progress.get(account).unwrap_or((0, 0));
+            let added = Self::mul_div(amount, self.wagering_requirement as u128, PRECISION as u128)?;
+            self.wagering_progress.insert(account, &(wagered, Self::add(required, added)?));
+
+            self.env().emit_event(BonusGranted { account, amount });
+
+            Ok(())
+        }
+
+        /// Create a promo code from the blake2-256 hash of its text (owner only)
+        /// The code itself never touches the chain until it is redeemed
+        #[ink(message)]
+        pub fn create_promo_code(&mut self, code_hash: Hash, value: Balance, max_uses: u32, expires_at: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if value == 0 || max_uses == 0 {
+                return Err(Error::InvalidConfig);
+            }
+
+            self.promo_codes.insert(code_hash, &PromoCode {
+                value,
+                max_uses,
+                uses: 0,
+                expires_at,
+            });
+
+            self.env().emit_event(PromoCodeCreated {
+                code_hash,
+                value,
+                max_uses,
+                expires_at,
+            });
+
+            Ok(())
+        }
+
+        /// Redeem a promo code for bonus funds, once per account
+        #[ink(message)]
+        pub fn redeem_code(&mut self, code: String) -> Result<Balance> {
+            use ink::env::hash::{Blake2x256, CryptoHash};
+
+            let caller = self.env().caller();
+            let mut hash = [0u8; 32];
+            Blake2x256::hash(code.as_bytes(), &mut hash);
+            let code_hash = Hash::from(hash);
+
+            let mut promo = self.promo_codes.get(code_hash).ok_or(Error::PromoCodeNotFound)?;
+            if self.env().block_timestamp() > promo.expires_at {
+                return Err(Error::PromoCodeExpired);
+            }
+            if promo.uses >= promo.max_uses {
+                return Err(Error::PromoCodeExhausted);
+            }
+            if self.promo_redemptions.contains((code_hash, caller)) {
+                return Err(Error::PromoCodeAlreadyUsed);
+            }
+
+            promo.uses += 1;
+            self.promo_codes.insert(code_hash, &promo);
+            self.promo_redemptions.insert((code_hash, caller), &());
+            self.award_bonus(caller, promo.value)?;
+
+            self.env().emit_event(PromoCodeRedeemed {
+                code_hash,
+                account: caller,
+                value: promo.value,
+            });
+
+            Ok(promo.value)
+        }
+
+        /// Get a promo code by hash
+        #[ink(message)]
+        pub fn get_promo_code(&self, code_hash: Hash) -> Option<PromoCode> {
+            self.promo_codes.get(code_hash)
+        }
+
+        /// Move the bonus balance, including bonus winnings, into the real
+        /// balance once the wagering requirement has been met
+        #[ink(message)]
+        pub fn release_bonus(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            let (wagered, required) = self.wagering_progress.get(caller).unwrap_or((0, 0));
+            if wagered < required {
+                return Err(Error::WageringRequirementNotMet);
+            }
+            let bonus = self.bonus_balances.get(caller).unwrap_or(0);
+            if bonus == 0 {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.bonus_balances.remove(caller);
+            self.wagering_progress.remove(caller);
+            self.credit_balance(caller, bonus, LedgerReason::Bonus)?;
+
+            self.env().emit_event(BonusReleased {
+                account: caller,
+                amount: bonus,
+            });
+
+            Ok(bonus)
+        }
+
+        /// Get an account's bonus balance
+        #[ink(message)]
+        pub fn get_bonus_balance(&self, account: AccountId) -> Balance {
+            self.bonus_balances.get(account).unwrap_or(0)
+        }
+
+        /// Take a stake from the bonus balance first, the rest from the real
+        /// balance; returns the bonus part
+        fn debit_stake(&mut self, account: AccountId, amount: Balance) -> Result<Balance> {
+            let bonus_balance = self.bonus_balances.get(account).unwrap_or(0);
+            let bonus = bonus_balance.min(amount);
+            if amount > bonus {
+                self.debit_balance(account, amount - bonus, LedgerReason::Bet)?;
+            }
+            if bonus > 0 {
+                self.bonus_balances.insert(account, &(bonus_balance - bonus));
+            }
+            Ok(bonus)
+        }
+
+        /// Add to an account's bonus balance
+        fn credit_bonus(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            if amount == 0 {
+                return Ok(());
+            }
+            let balance = self.bonus_balances.get(account).unwrap_or(0);
+            self.bonus_balances.insert(account, &Self::add(balance, amount)?);
+            Ok(())
+        }
+
+        /// Credit a bet's payout or refund, returning the share staked with
+        /// bonus funds to the bonus balance
+        fn credit_bet(&mut self, bet: &ExactaBet, payee: AccountId, amount: Balance, reason: LedgerReason) -> Result<()> {
+            let bonus = Self::mul_div(amount, bet.bonus_stake, bet.amount)?;
+            self.credit_bonus(payee, bonus)?;
+            self.credit_winnings(payee, amount - bonus, reason)
+        }
+
+        // ========================================================================
+        // EXACTA PROBABILITY CALCULATOR
+        // ========================================================================
+
+        /// Calculate exacta probability P(i → j)
+        /// Formula: P(i → j) = (W[i] / sum(W)) * (W[j] / (sum(W) - W[i]))
+        /// where W[i] is the race weight combining strength, acceleration and stamina
+        #[ink(message)]
+        pub fn calculate_exacta_probability(&self, first: u8, second: u8) -> u64 {
+            Self::exacta_probability(&self.race_weights(), first, second)
+        }
+
+        /// Exacta probability P(i → j) for a given set of race weights
+        fn exacta_probability(weights: &[u64; NUM_HORSES], first: u8, second: u8) -> u64 {
+            if first >= NUM_HORSES as u8 || second >= NUM_HORSES as u8 || first == second {
+                return 0;
+            }
+
+            let total_weight: u64 = weights.iter().sum();
+            let w_first = weights[first as usize];
+            let w_second = weights[second as usize];
+
+            // P(first wins) = W[first] / sum(W)
+            let p_first = (w_first * PRECISION) / total_weight;
+
+            // P(second | first won) = W[second] / (sum(W) - W[first])
+            let remaining = total_weight - w_first;
+            let p_second_given_first = (w_second * PRECISION) / remaining;
+
+            // P(exacta) = P(first) * P(second|first)
+            (p_first * p_second_given_first) / PRECISION
+        }
+
+        /// Calculate win probability P(i) = W[i] / sum(W), scaled by PRECISION
+        #[ink(message)]
+        pub fn calculate_win_probability(&self, horse_id: u8) -> u64 {
+            if horse_id >= NUM_HORSES as u8 {
+                return 0;
+            }
+            let weights = self.race_weights();
+            let total_weight: u64 = weights.iter().sum();
+            (weights[horse_id as usize] * PRECISION) / total_weight
+        }
+
+        /// Get probabilities and multipliers for all 30 exactas,
+        /// flagging the ones that can't be bet
+        #[ink(message)]
+        pub fn get_exacta_probability_table(&self) -> Vec<ExactaProbability> {
+            let mut table: Vec<ExactaProbability> = Vec::new();
+
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if first != second {
+                        let prob = self.calculate_exacta_probability(first, second);
+                        let mult = self.get_multiplier(first, second);
+
+                        table.push(ExactaProbability {
+                            first,
+                            second,
+                            probability: prob,
+                            multiplier: mult,
+                            offered: mult > 0,
+                        });
+                    }
+                }
+            }
+
+            table
+        }
+
+        /// Get the odds of every offered exacta
+        #[ink(message)]
+        pub fn get_exacta_odds_table(&self) -> Vec<ExactaOdds> {
+            let mut table: Vec<ExactaOdds> = Vec::new();
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if let Some(odds) = self.get_exacta_odds(first, second) {
+                        table.push(odds);
+                    }
+                }
+            }
+            table
+        }
+
+        /// Expected profit of staking `amount` on an exacta, scaled by PRECISION
+        /// EV = amount × (P(first → second) × multiplier − 1); None if not offered
+        #[ink(message)]
+        pub fn get_expected_value(&self, first: u8, second: u8, amount: Balance) -> Option<i128> {
+            let odds = self.get_exacta_odds(first, second)?;
+            let return_rate = odds.model_probability as i128 * odds.multiplier as i128;
+            Some(amount as i128 * (return_rate - PRECISION as i128))
+        }
+
+        /// Audit the multiplier table against the model
+        /// Each horse's exactas imply a win probability; the excess over the
+        /// model's win probability is that horse's overround
+        #[ink(message)]
+        pub fn get_book_overround(&self) -> BookOverround {
+            let table = self.get_exacta_odds_table();
+            let mut per_horse: Vec<i64> = Vec::new();
+            for horse in 0..NUM_HORSES as u8 {
+                let implied: u64 = table
+                    .iter()
+                    .filter(|odds| odds.first == horse)
+                    .map(|odds| odds.implied_probability)
+                    .sum();
+                per_horse.push(implied as i64 - self.calculate_win_probability(horse) as i64);
+            }
+
+            let implied_total: u64 = table.iter().map(|odds| odds.implied_probability).sum();
+            BookOverround {
+                per_horse,
+                overall: implied_total as i64 - PRECISION as i64,
+            }
+        }
+
+        // ========================================================================
+        // GETTERS
+        // ========================================================================
+
+        /// Get all horses
+        #[ink(message)]
+        pub fn get_horses(&self) -> Vec<Horse> {
+            self.horses.clone()
+        }
+
+        /// Get horse by ID
+        #[ink(message)]
+        pub fn get_horse(&self, id: u8) -> Option<Horse> {
+            self.horses.get(id as usize).cloned()
+        }
+
+        /// Get a horse's recent finishes and form-adjusted strength
+        #[ink(message)]
+        pub fn get_horse_form(&self, id: u8) -> Option<HorseForm> {
+            self.horse_forms.get(id as usize).cloned()
+        }
+
+        /// Get a horse's lifetime starts and 1st/2nd/3rd/DNF counts
+        #[ink(message)]
+        pub fn get_horse_record(&self, id: u8) -> Option<HorseRecord> {
+            self.horse_records.get(id as usize).cloned()
+        }
+
+        /// Get race results history
+        #[ink(message)]
+        pub fn get_race_history(&self) -> Vec<RaceResult> {
+            self.race_results.clone()
+        }
+
+        /// Get display details of a race, including the one open for betting
+        #[ink(message)]
+        pub fn get_race_info(&self, race_id: u64) -> Option<RaceInfo> {
+            self.race_info.get(race_id)
+        }
+
+        /// Get winning margins of the latest race in lengths (scaled by PRECISION)
+        #[ink(message)]
+        pub fn get_margins_in_lengths(&self) -> Vec<u64> {
+            self.latest_result
+                .margins
+                .iter()
+                .map(|margin| (margin * PRECISION) / MS_PER_LENGTH)
+                .collect()
+        }
+
+        /// Find the stored result of a race
+        fn find_result(&self, race_id: u64) -> Option<&RaceResult> {
+            self.race_results.iter().find(|result| result.race_id == race_id)
+        }
+
+        /// Get winners from latest race
+        #[ink(message)]
+        pub fn get_winners(&self) -> (u8, u8) {
+            self.latest_result.winning_exacta
+        }
+
+        /// Get reward multiplier for an exacta combination
+        #[ink(message)]
+        pub fn get_reward_multiplier(&self, first: u8, second: u8) -> u64 {
+            self.get_multiplier(first, second)
+        }
+
+        /// Get race weight W[i] for a horse (strength combined with phase attributes)
+        #[ink(message)]
+        pub fn get_race_weight(&self, horse_id: u8) -> u64 {
+            if horse_id < NUM_HORSES as u8 {
+                self.race_weights()[horse_id as usize]
+            } else {
+                0
+            }
+        }
+
+        /// Get normalized strength for a horse
+        #[ink(message)]
+        pub fn get_normalized_strength(&self, horse_id: u8) -> u64 {
+            if horse_id < NUM_HORSES as u8 {
+                self.horses[horse_id as usize].normalized_strength
+            } else {
+                0
+            }
+        }
+
+        /// Get contract owner
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Get contract-wide volume, payouts, bettor count and the biggest payout
+        #[ink(message)]
+        pub fn get_global_stats(&self) -> GlobalStats {
+            self.global_stats.clone()
+        }
+
+        /// Get the semantic version and storage version of the deployment
+        #[ink(message)]
+        pub fn get_version(&self) -> ContractVersion {
+            self.version
+        }
+
+        /// Get treasury balance
+        #[ink(message)]
+        pub fn get_treasury(&self) -> Balance {
+            self.treasury
+        }
+
+        /// Get keeper reward per lifecycle transition
+        #[ink(message)]
+        pub fn get_keeper_reward(&self) -> Balance {
+            self.keeper_reward
+        }
+
+        /// Get betting and racing window durations (ms)
+        #[ink(message)]
+        pub fn get_durations(&self) -> (u64, u64) {
+            (self.betting_duration, self.racing_duration)
+        }
+
+        /// Get the current phase, when it started, its length and the ms left
+        /// Finished races count down the challenge period, closed races have no timer
+        #[ink(message)]
+        pub fn get_phase_info(&self) -> PhaseInfo {
+            let (started_at, duration) = match self.status {
+                RaceStatus::Betting => (self.betting_start_time, self.betting_duration),
+                RaceStatus::Racing => (self.race_start_time, self.racing_duration),
+                RaceStatus::Finished => (self.race_finish_time, self.challenge_period),
+                RaceStatus::Closed => (self.race_finish_time, 0),
+            };
+            let remaining = started_at
+                .saturating_add(duration)
+                .saturating_sub(self.env().block_timestamp());
+            PhaseInfo {
+                status: self.status.clone(),
+                started_at,
+                duration,
+                remaining,
+            }
+        }
+
+        // ========================================================================
+        // ADMIN FUNCTIONS
+        // ========================================================================
+
+        /// Reset for new race
+        #[ink(message)]
+        pub fn reset_for_new_race(&mut self) -> Result<()> {
+            let to = self.ensure_transition(RaceAction::Reset)?;
+
+            // Bets of a race that never ran leave the bettor index with it
+            let bets_race_id = self.bets_race_id();
+            for bet in self.bets.iter() {
+                self.stake_per_combination.remove((bet.first_pick, bet.second_pick));
+                self.positions_per_combination.remove((bet.first_pick, bet.second_pick));
+                if self.status == RaceStatus::Betting {
+                    self.race_bets.remove((bets_race_id, bet.bettor));
+                }
+            }
+            if self.status == RaceStatus::Betting {
+                self.race_info.remove(bets_race_id);
+                self.race_tracks.remove(bets_race_id);
+            }
+            self.bets.clear();
+            self.largest_bet = None;
+            self.demo_bets.clear();
+            self.refund_tournament_bets()?;
+            self.predictions.clear();
+            self.payouts.clear();
+            self.refunds.clear();
+            self.match_bets.clear();
+            self.over_under_bets.clear();
+            self.lay_offers.clear();
+            self.exchange_matches.clear();
+            self.finish_time_line = None;
+            self.race_phase = 0;
+            self.running_times.clear();
+            self.total_pot = 0;
+            self.set_status(to);
+            self.betting_start_time = self.env().block_timestamp();
+            self.current_seed = 0;
+
+            Ok(())
+        }
+
+        /// Set name, track, distance and start time of the race open for betting,
+        /// and whether it is a commission-free promotional race
+        #[ink(message)]
+        pub fn set_race_info(&mut self, info: RaceInfo) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.status != RaceStatus::Betting {
+                return Err(Error::RaceNotInBettingPhase);
+            }
+            self.race_info.insert(self.bets_race_id(), &info);
+            Ok(())
+        }
+
+        /// Whether the race the current bets are on waives rake and edges
+        fn commission_free(&self) -> bool {
+            self.race_info
+                .get(self.bets_race_id())
+                .is_some_and(|info| info.commission_free)
+        }
+
+        /// A house edge or commission, 0 on commission-free races
+        fn house_edge(&self, edge: u64) -> u64 {
+            if self.commission_free() {
+                0
+            } else {
+                edge
+            }
+        }
+
+        /// Add funds to the treasury
+        #[ink(message, payable)]
+        pub fn fund_treasury(&mut self) -> Result<()> {
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+            self.treasury = Self::add(self.treasury, amount)?;
+
+            self.env().emit_event(TreasuryFunded {
+                funder: self.env().caller(),
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Set reward paid to keepers for each lifecycle transition
+        #[ink(message)]
+        pub fn set_keeper_reward(&mut self, reward: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.keeper_reward = reward;
+            Ok(())
+        }
+
+        /// Set betting and racing window durations (ms)
+        #[ink(message)]
+        pub fn set_durations(&mut self, betting_duration: u64, racing_duration: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if betting_duration <= self.betting_cutoff || racing_duration == 0 {
+                return Err(Error::InvalidDuration);
+            }
+            self.betting_duration = betting_duration;
+            self.racing_duration = racing_duration;
+            Ok(())
+        }
+
+        /// Set the most exacta bets a race can take and one account can place
+        /// in a race, 0 for no cap (owner only)
+        #[ink(message)]
+        pub fn set_bet_limits(&mut self, max_bets_per_race: u32, max_bets_per_account: u32) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.max_bets_per_race = max_bets_per_race;
+            self.max_bets_per_account = max_bets_per_account;
+            Ok(())
+        }
+
+        /// Get the per-race and per-account bet caps
+        #[ink(message)]
+        pub fn get_bet_limits(&self) -> (u32, u32) {
+            (self.max_bets_per_race, self.max_bets_per_account)
+        }
+
+        /// Set the most bets one account can place per block, 0 for no limit,
+        /// to blunt last-moment spam from bots (owner only)
+        #[ink(message)]
+        pub fn set_block_bet_limit(&mut self, limit: u32) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.max_bets_per_block = limit;
+            Ok(())
+        }
+
+        /// Get the per-account, per-block bet limit
+        #[ink(message)]
+        pub fn get_block_bet_limit(&self) -> u32 {
+            self.max_bets_per_block
+        }
+
+        /// Stop taking bets this long before the betting window ends (ms),
+        /// so nobody bets in the moments before the seed is revealed (owner only)
+        #[ink(message)]
+        pub fn set_betting_cutoff(&mut self, cutoff: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if cutoff >= self.betting_duration {
+                return Err(Error::InvalidDuration);
+            }
+            self.betting_cutoff = cutoff;
+            Ok(())
+        }
+
+        /// Get the betting cutoff (ms)
+        #[ink(message)]
+        pub fn get_betting_cutoff(&self) -> u64 {
+            self.betting_cutoff
+        }
+
+        /// Get every runtime parameter in one call
+        #[ink(message)]
+        pub fn get_config(&self) -> Config {
+            Config {
+                betting_duration: self.betting_duration,
+                racing_duration: self.racing_duration,
+                betting_cutoff: self.betting_cutoff,
+                challenge_period: self.challenge_period,
+                keeper_reward: self.keeper_reward,
+                approval_threshold: self.approval_threshold,
+                unwon_pot_policy: self.unwon_pot_policy,
+                claim_mode: self.claim_mode,
+                claim_period: self.claim_period,
+                dormancy_period: self.dormancy_period,
+                custodial_withdrawals: self.custodial_withdrawals,
+                whitelist_mode: self.whitelist_mode,
+                room_fee: self.room_fee,
+                withdrawal_fee: self.withdrawal_fee,
+                daily_withdrawal_cap: self.daily_withdrawal_cap,
+                max_bets_per_race: self.max_bets_per_race,
+                max_bets_per_account: self.max_bets_per_account,
+                wagering_requirement: self.wagering_requirement,
+                points_accrual_rate: self.points_accrual_rate,
+                points_per_credit: self.points_per_credit,
+                match_bet_edge: self.match_bet_edge,
+                daily_double_edge: self.daily_double_edge,
+                exchange_commission: self.exchange_commission,
+                cash_out_factor: self.cash_out_factor,
+                owner_shares: self.owner_shares,
+            }
+        }
+
+        /// Validate and apply every runtime parameter at once (owner only)
+        /// While a governance account is set, the edges, commission, cash-out
+        /// factor and owner shares must be left unchanged.
+        #[ink(message)]
+        pub fn set_config(&mut self, config: Config) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let current = self.get_config();
+            let governed_changed = config.match_bet_edge != current.match_bet_edge
+                || config.daily_double_edge != current.daily_double_edge
+                || config.exchange_commission != current.exchange_commission
+                || config.cash_out_factor != current.cash_out_factor
+                || config.owner_shares != current.owner_shares;
+            if governed_changed {
+                self.ensure_config_authority()?;
+            }
+
+            self.apply_config(&config)?;
+
+            self.env().emit_event(ConfigUpdated {
+                updated_by: caller,
+                config,
+            });
+
+            Ok(())
+        }
+
+        /// Validate a config and apply it, leaving everything untouched if invalid
+        fn apply_config(&mut self, config: &Config) -> Result<()> {
+            if config.betting_duration == 0
+                || config.racing_duration == 0
+                || config.claim_period == 0
+                || config.dormancy_period == 0
+                || config.betting_cutoff >= config.betting_duration
+            {
+                return Err(Error::InvalidDuration);
+            }
+            if config.approval_threshold == 0
+                || config.approval_threshold > self.settlement_operators.len() as u32 + 1
+            {
+                return Err(Error::InvalidThreshold);
+            }
+            if config.points_per_credit == 0
+                || config.withdrawal_fee > PRECISION
+                || config.match_bet_edge >= PRECISION
+                || config.daily_double_edge >= PRECISION
+                || config.exchange_commission > PRECISION
+                || config.cash_out_factor > PRECISION
+                || config.owner_shares.0.saturating_add(config.owner_shares.1) > PRECISION
+            {
+                return Err(Error::InvalidConfig);
+            }
+
+            self.betting_duration = config.betting_duration;
+            self.racing_duration = config.racing_duration;
+            self.betting_cutoff = config.betting_cutoff;
+            self.challenge_period = config.challenge_period;
+            self.keeper_reward = config.keeper_reward;
+            self.approval_threshold = config.approval_threshold;
+            self.unwon_pot_policy = config.unwon_pot_policy;
+            self.claim_mode = config.claim_mode;
+            self.claim_period = config.claim_period;
+            self.dormancy_period = config.dormancy_period;
+            self.custodial_withdrawals = config.custodial_withdrawals;
+            self.whitelist_mode = config.whitelist_mode;
+            self.room_fee = config.room_fee;
+            self.withdrawal_fee = config.withdrawal_fee;
+            self.daily_withdrawal_cap = config.daily_withdrawal_cap;
+            self.max_bets_per_race = config.max_bets_per_race;
+            self.max_bets_per_account = config.max_bets_per_account;
+            self.wagering_requirement = config.wagering_requirement;
+            self.points_accrual_rate = config.points_accrual_rate;
+            self.points_per_credit = config.points_per_credit;
+            self.match_bet_edge = config.match_bet_edge;
+            self.daily_double_edge = config.daily_double_edge;
+            self.exchange_commission = config.exchange_commission;
+            self.cash_out_factor = config.cash_out_factor;
+            self.owner_shares = config.owner_shares;
+            Ok(())
+        }
+
+        /// Freeze an account from betting and withdrawing (compliance hold)
+        #[ink(message)]
+        pub fn freeze_account(&mut self, account: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.frozen.insert(account, &true);
+            self.env().emit_event(AccountFrozen { account });
+            Ok(())
+        }
+
+        /// Lift a freeze
+        #[ink(message)]
+        pub fn unfreeze_account(&mut self, account: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.frozen.remove(account);
+            self.env().emit_event(AccountUnfrozen { account });
+            Ok(())
+        }
+
+        /// Check whether an account is frozen
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen.get(account).unwrap_or(false)
+        }
+
+        /// Reject frozen accounts
+        fn ensure_not_frozen(&self, account: AccountId) -> Result<()> {
+            if self.is_frozen(account) {
+                return Err(Error::AccountFrozen);
+            }
+            Ok(())
+        }
+
+        /// Make the race invite-only: only allowlisted accounts can bet (owner only)
+        #[ink(message)]
+        pub fn set_whitelist_mode(&mut self, enabled: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.whitelist_mode = enabled;
+            Ok(())
+        }
+
+        /// Whether only allowlisted accounts can bet
+        #[ink(message)]
+        pub fn get_whitelist_mode(&self) -> bool {
+            self.whitelist_mode
+        }
+
+        /// Add an account to the allowlist, or take it off (owner only)
+        #[ink(message)]
+        pub fn set_whitelisted(&mut self, account: AccountId, whitelisted: bool) -> Result<()> {
+            self.batch_set_whitelisted(vec![account], whitelisted)
+        }
+
+        /// Add several accounts to the allowlist, or take them off (owner only)
+        #[ink(message)]
+        pub fn batch_set_whitelisted(&mut self, accounts: Vec<AccountId>, whitelisted: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            for account in accounts {
+                if whitelisted {
+                    self.whitelist.insert(account, &true);
+                } else {
+                    self.whitelist.remove(account);
+                }
+                self.env().emit_event(WhitelistUpdated { account, whitelisted });
+            }
+            Ok(())
+        }
+
+        /// Check whether an account is on the allowlist
+        #[ink(message)]
+        pub fn is_whitelisted(&self, account: AccountId) -> bool {
+            self.whitelist.get(account).unwrap_or(false)
+        }
+
+        /// Reject accounts off the allowlist while whitelist mode is on
+        fn ensure_whitelisted(&self, account: AccountId) -> Result<()> {
+            if self.whitelist_mode && !self.is_whitelisted(account) {
+                return Err(Error::NotWhitelisted);
+            }
+            Ok(())
+        }
+
+        /// Require a compliance attestation to bet or deposit (owner only)
+        #[ink(message)]
+        pub fn set_attestation_mode(&mut self, enabled: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.attestation_mode = enabled;
+            Ok(())
+        }
+
+        /// Whether betting and deposits need an attestation
+        #[ink(message)]
+        pub fn get_attestation_mode(&self) -> bool {
+            self.attestation_mode
+        }
+
+        /// Set the compliance operator, None leaves attestations to the owner (owner only)
+        #[ink(message)]
+        pub fn set_compliance_operator(&mut self, operator: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.compliance_operator = operator;
+            Ok(())
+        }
+
+        /// Get the compliance operator
+        #[ink(message)]
+        pub fn get_compliance_operator(&self) -> Option<AccountId> {
+            self.compliance_operator
+        }
+
+        /// Set the registry contract checked for attestations, None for local flags only (owner only)
+        #[ink(message)]
+        pub fn set_attestation_registry(&mut self, registry: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.attestation_registry = registry;
+            Ok(())
+        }
+
+        /// Get the attestation registry contract
+        #[ink(message)]
+        pub fn get_attestation_registry(&self) -> Option<AccountId> {
+            self.attestation_registry
+        }
+
+        /// Attest an account, or revoke its attestation (owner or compliance operator)
+        #[ink(message)]
+        pub fn set_attested(&mut self, account: AccountId, attested: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner && Some(caller) != self.compliance_operator {
+                return Err(Error::NotComplianceOperator);
+            }
+            if attested {
+                self.attested.insert(account, &true);
+            } else {
+                self.attested.remove(account);
+            }
+            self.env().emit_event(AttestationUpdated { account, attested });
+            Ok(())
+        }
+
+        /// Check whether an account is attested locally or by the registry
+        #[ink(message)]
+        pub fn is_attested(&self, account: AccountId) -> bool {
+            use ink::codegen::TraitCallBuilder;
+            if self.attested.get(account).unwrap_or(false) {
+                return true;
+            }
+            let Some(registry) = self.attestation_registry else {
+                return false;
+            };
+            let registry: ink::contract_ref!(AttestationRegistry) = registry.into();
+            matches!(registry.call().is_attested(account).try_invoke(), Ok(Ok(true)))
+        }
+
+        /// Reject accounts without an attestation while attestation mode is on
+        fn ensure_attested(&self, account: AccountId) -> Result<()> {
+            if self.attestation_mode && !self.is_attested(account) {
+                return Err(Error::AttestationRequired);
+            }
+            Ok(())
+        }
+
+        // ========================================================================
+        // HORSE OWNERSHIP
+        // ========================================================================
+
+        /// Give an unowned horse its first owner (owner only)
+        #[ink(message)]
+        pub fn assign_horse(&mut self, horse_id: u8, to: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if horse_id >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if self.horse_owners.contains(horse_id) {
+                return Err(Error::HorseAlreadyOwned);
+            }
+            self.horse_owners.insert(horse_id, &to);
+
+            self.env().emit_event(HorseTransferred {
+                horse_id,
+                from: None,
+                to,
+            });
+
+            Ok(())
+        }
+
+        /// Transfer a horse the caller owns
+        #[ink(message)]
+        pub fn transfer_horse(&mut self, horse_id: u8, to: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if self.horse_owners.get(horse_id) != Some(caller) {
+                return Err(Error::NotHorseOwner);
+            }
+            self.horse_owners.insert(horse_id, &to);
+
+            self.env().emit_event(HorseTransferred {
+                horse_id,
+                from: Some(caller),
+                to,
+            });
+
+            Ok(())
+        }
+
+        /// Get a horse's owner
+        #[ink(message)]
+        pub fn get_horse_owner(&self, horse_id: u8) -> Option<AccountId> {
+            self.horse_owners.get(horse_id)
+        }
+
+        /// Set a horse's silk colors as RGB triples, primary first
+        /// (contract owner or the horse's owner)
+        #[ink(message)]
+        pub fn set_horse_silks(&mut self, horse_id: u8, silks: Vec<u8>) -> Result<()> {
+            if !silks.len().is_multiple_of(3) || silks.len() > MAX_SILK_COLORS * 3 {
+                return Err(Error::InvalidHorseMetadata);
+            }
+            let horse = self.metadata_horse(horse_id)?;
+            self.horses[horse].silks = silks;
+            self.emit_horse_metadata(horse);
+            Ok(())
+        }
+
+        /// Set a horse's image or metadata URI (contract owner or the horse's owner)
+        #[ink(message)]
+        pub fn set_horse_uri(&mut self, horse_id: u8, uri: Vec<u8>) -> Result<()> {
+            if uri.len() > MAX_HORSE_URI_LEN {
+                return Err(Error::InvalidHorseMetadata);
+            }
+            let horse = self.metadata_horse(horse_id)?;
+            self.horses[horse].uri = uri;
+            self.emit_horse_metadata(horse);
+            Ok(())
+        }
+
+        /// Index of a horse whose metadata the caller may change
+        fn metadata_horse(&self, horse_id: u8) -> Result<usize> {
+            if horse_id >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            let caller = self.env().caller();
+            if caller != self.owner && self.horse_owners.get(horse_id) != Some(caller) {
+                return Err(Error::NotHorseOwner);
+            }
+            Ok(horse_id as usize)
+        }
+
+        /// Announce a horse's current display metadata
+        fn emit_horse_metadata(&self, horse: usize) {
+            let horse = &self.horses[horse];
+            self.env().emit_event(HorseMetadataUpdated {
+                horse_id: horse.id,
+                silks: horse.silks.clone(),
+                uri: horse.uri.clone(),
+            });
+        }
+
+        /// Set the profit shares of the winner's and runner-up's owners
+        /// (scaled by PRECISION, governance or owner)
+        #[ink(message)]
+        pub fn set_owner_shares(&mut self, win_share: u64, place_share: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if win_share.saturating_add(place_share) > PRECISION {
+                return Err(Error::InvalidConfig);
+            }
+            self.owner_shares = (win_share, place_share);
+            Ok(())
+        }
+
+        /// Get the winner's and runner-up's owner shares
+        #[ink(message)]
+        pub fn get_owner_shares(&self) -> (u64, u64) {
+            self.owner_shares
+        }
+
+        /// Credit the owners of the first two finishers their share of the
+        /// race's house profit; nothing is paid when the house lost
+        fn pay_horse_owners(&mut self) -> Result<()> {
+            let race_id = self.race_id;
+            let profit = match self.race_pnl.get(race_id) {
+                Some(pnl) if pnl.net > 0 => pnl.net as Balance,
+                _ => return Ok(()),
+            };
+            let rankings = self.latest_result.rankings.clone();
+            let (win_share, place_share) = self.owner_shares;
+
+            for (horse_id, share) in rankings.into_iter().zip([win_share, place_share]) {
+                let Some(owner) = self.horse_owners.get(horse_id) else {
+                    continue;
+                };
+                let amount = Self::mul_div(profit, share as u128, PRECISION as u128)?;
+                if amount == 0 {
+                    continue;
+                }
+                self.credit_balance(owner, amount, LedgerReason::OwnerShare)?;
+                self.book_house(0, amount)?;
+
+                self.env().emit_event(OwnerSharePaid {
+                    owner,
+                    race_id,
+                    horse_id,
+                    amount,
+                });
+            }
+
+            Ok(())
+        }
+
+        // ========================================================================
+        // HOUSE BANKROLL
+        // ========================================================================
+
+        /// Stake into the house bankroll for shares at the current share value
+        #[ink(message, payable)]
+        pub fn stake(&mut self) -> Result<Balance> {
+            self.ensure_bankroll_unlocked()?;
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroBetAmount);
+            }
+
+            let shares = if self.total_bankroll_shares == 0 {
+                amount
+            } else if self.bankroll == 0 {
+                return Err(Error::BankrollDepleted);
+            } else {
+                Self::mul_div(amount, self.total_bankroll_shares, self.bankroll)?
+            };
+
+            self.bankroll = Self::add(self.bankroll, amount)?;
+            self.total_bankroll_shares = Self::add(self.total_bankroll_shares, shares)?;
+            let held = self.bankroll_shares.get(caller).unwrap_or(0);
+            self.bankroll_shares.insert(caller, &Self::add(held, shares)?);
+
+            self.env().emit_event(BankrollStaked {
+                account: caller,
+                amount,
+                shares,
+            });
+
+            Ok(shares)
+        }
+
+        /// Redeem shares for their current value, credited to the caller's balance
+        #[ink(message)]
+        pub fn unstake(&mut self, shares: Balance) -> Result<Balance> {
+            self.ensure_bankroll_unlocked()?;
+            let caller = self.env().caller();
+            let held = self.bankroll_shares.get(caller).unwrap_or(0);
+            if shares == 0 || shares > held {
+                return Err(Error::InsufficientShares);
+            }
+
+            let amount = Self::mul_div(shares, self.bankroll, self.total_bankroll_shares)?;
+            self.bankroll = Self::sub(self.bankroll, amount)?;
+            self.total_bankroll_shares = Self::sub(self.total_bankroll_shares, shares)?;
+            self.bankroll_shares.insert(caller, &(held - shares));
+            self.credit_balance(caller, amount, LedgerReason::Bankroll)?;
+
+            self.env().emit_event(BankrollUnstaked {
+                account: caller,
+                shares,
+                amount,
+            });
+
+            Ok(amount)
+        }
+
+        /// Value of one share, scaled by PRECISION
+        #[ink(message)]
+        pub fn get_share_value(&self) -> Balance {
+            if self.total_bankroll_shares == 0 {
+                return PRECISION as Balance;
+            }
+            Self::mul_div(self.bankroll, PRECISION as u128, self.total_bankroll_shares).unwrap_or(0)
+        }
+
+        /// Get an account's shares and their current value
+        #[ink(message)]
+        pub fn get_bankroll_position(&self, account: AccountId) -> (Balance, Balance) {
+            let shares = self.bankroll_shares.get(account).unwrap_or(0);
+            if self.total_bankroll_shares == 0 {
+                return (shares, 0);
+            }
+            (shares, Self::mul_div(shares, self.bankroll, self.total_bankroll_shares).unwrap_or(0))
+        }
+
+        /// Get the bankroll and shares outstanding
+        #[ink(message)]
+        pub fn get_bankroll(&self) -> (Balance, Balance) {
+            (self.bankroll, self.total_bankroll_shares)
+        }
+
+        /// Book a settled house-priced bet to the race P&L and the bankroll:
+        /// the stake comes in, the payout goes out. Losses beyond the bankroll
+        /// are absorbed by the house; the bankroll is untouched while nobody is staked.
+        fn book_house(&mut self, staked: Balance, paid: Balance) -> Result<()> {
+            let race_id = self.race_id;
+            let mut race = self.race_pnl.get(race_id).unwrap_or(RacePnl { race_id, ..Default::default() });
+            for pnl in [&mut race, &mut self.lifetime_pnl] {
+                pnl.staked = Self::add(pnl.staked, staked)?;
+                pnl.paid_out = Self::add(pnl.paid_out, paid)?;
+                pnl.net = pnl
+                    .net
+                    .checked_add(staked as i128 - paid as i128)
+                    .ok_or(Error::ArithmeticOverflow)?;
+            }
+            self.race_pnl.insert(race_id, &race);
+
+            if self.total_bankroll_shares == 0 {
+                return Ok(());
+            }
+            self.bankroll = Self::add(self.bankroll, staked)?.saturating_sub(paid);
+            Ok(())
+        }
+
+        /// Book commission taken by the house to the race P&L and the treasury
+        fn book_rake(&mut self, rake: Balance) -> Result<()> {
+            let race_id = self.race_id;
+            let mut race = self.race_pnl.get(race_id).unwrap_or(RacePnl { race_id, ..Default::default() });
+            for pnl in [&mut race, &mut self.lifetime_pnl] {
+                pnl.rake = Self::add(pnl.rake, rake)?;
+                pnl.net = pnl.net.checked_add(rake as i128).ok_or(Error::ArithmeticOverflow)?;
+            }
+            self.race_pnl.insert(race_id, &race);
+            self.treasury = Self::add(self.treasury, rake)?;
+            Ok(())
+        }
+
+        /// Get the house P&L of a settled race
+        #[ink(message)]
+        pub fn get_race_pnl(&self, race_id: u64) -> Option<RacePnl> {
+            self.race_pnl.get(race_id)
+        }
+
+        /// Get house P&L across all races
+        #[ink(message)]
+        pub fn get_lifetime_pnl(&self) -> RacePnl {
+            self.lifetime_pnl.clone()
+        }
+
+        /// Move what a floored split left over into the treasury
+        fn collect_dust(&mut self, dust: Balance) -> Result<()> {
+            if dust == 0 {
+                return Ok(());
+            }
+            self.dust = Self::add(self.dust, dust)?;
+            self.treasury = Self::add(self.treasury, dust)?;
+            Ok(())
+        }
+
+        /// Get the rounding dust collected into the treasury so far
+        #[ink(message)]
+        pub fn get_dust(&self) -> Balance {
+            self.dust
+        }
+
+        /// Stakes can only move while no bets are in flight on a race
+        fn ensure_bankroll_unlocked(&self) -> Result<()> {
+            match self.status {
+                RaceStatus::Racing | RaceStatus::Finished => Err(Error::BankrollLocked),
+                _ => Ok(()),
+            }
+        }
+
+        // ========================================================================
+        // GOVERNANCE
+        // ========================================================================
+
+        /// Hand odds and config control to a governance contract, or take it
+        /// back with None. Only the current authority may call this.
+        #[ink(message)]
+        pub fn set_governance(&mut self, governance: Option<AccountId>) -> Result<()> {
+            self.ensure_config_authority()?;
+            self.governance = governance;
+            Ok(())
+        }
+
+        /// Get the governance account, if any
+        #[ink(message)]
+        pub fn get_governance(&self) -> Option<AccountId> {
+            self.governance
+        }
+
+        /// Set an exacta's reward multiplier (0 withdraws the combination)
+        #[ink(message)]
+        pub fn update_multiplier(&mut self, first: u8, second: u8, multiplier: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if first >= NUM_HORSES as u8 || second >= NUM_HORSES as u8 || first == second {
+                return Err(Error::InvalidHorseId);
+            }
+            self.set_multiplier(first, second, multiplier);
+            Ok(())
+        }
+
+        /// Set house edges on head-to-head and daily double odds (scaled by PRECISION)
+        #[ink(message)]
+        pub fn set_house_edge(&mut self, match_bet_edge: u64, daily_double_edge: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if match_bet_edge >= PRECISION || daily_double_edge >= PRECISION {
+                return Err(Error::InvalidConfig);
+            }
+            self.match_bet_edge = match_bet_edge;
+            self.daily_double_edge = daily_double_edge;
+            Ok(())
+        }
+
+        /// Get house edges: (head-to-head, daily double)
+        #[ink(message)]
+        pub fn get_house_edge(&self) -> (u64, u64) {
+            (self.match_bet_edge, self.daily_double_edge)
+        }
+
+        /// Set a horse's base strength (1-10)
+        /// Recomputes normalized strengths and base speed and resets the horse's form
+        #[ink(message)]
+        pub fn set_horse_strength(&mut self, horse_id: u8, strength: u64) -> Result<()> {
+            self.ensure_config_authority()?;
+            if horse_id >= NUM_HORSES as u8 {
+                return Err(Error::InvalidHorseId);
+            }
+            if strength == 0 || strength > 10 {
+                return Err(Error::InvalidConfig);
+            }
+
+            let horse = &mut self.horses[horse_id as usize];
+            horse.strength = strength;
+            horse.base_speed = 14 + strength;
+            let form = &mut self.horse_forms[horse_id as usize];
+            form.adjusted_strength = strength * PRECISION;
+            form.recent_finishes.clear();
+
+            let total_strength: u64 = self.horses.iter().map(|horse| horse.strength).sum();
+            for horse in self.horses.iter_mut() {
+                horse.normalized_strength = (horse.strength * PRECISION) / total_strength;
+            }
+
+            Ok(())
+        }
+
+        /// Governance when set, otherwise the owner
+        fn ensure_config_authority(&self) -> Result<()> {
+            let caller = self.env().caller();
+            match self.governance {
+                Some(governance) if caller != governance => Err(Error::NotGovernance),
+                None if caller != self.owner => Err(Error::NotOwner),
+                _ => Ok(()),
+            }
+        }
+
+        /// Set contract owner
+        #[ink(message)]
+        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.owner = new_owner;
+            Ok(())
+        }
+
+        // ========================================================================
+        // UPGRADES AND MIGRATIONS
+        // ========================================================================
+
+        /// Replace the contract code, keeping storage (owner only)
+        /// Call `migrate` next so the stored state matches the new code
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: Hash) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(ContractUpgraded { code_hash });
+            Ok(())
+        }
+
+        /// Bring stored state from `from_version` up to this code's layout (owner only)
+        /// Runs each step in order and records the new version; refuses a
+        /// second run, a wrong starting version and a downgrade
+        #[ink(message)]
+        pub fn migrate(&mut self, from_version: u32) -> Result<ContractVersion> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let current = ContractVersion {
+                major: CONTRACT_VERSION.0,
+                minor: CONTRACT_VERSION.1,
+                patch: CONTRACT_VERSION.2,
+                storage_version: STORAGE_VERSION,
+            };
+            if self.version == current {
+                return Err(Error::AlreadyMigrated);
+            }
+            if from_version != self.version.storage_version || from_version > STORAGE_VERSION {
+                return Err(Error::StorageVersionMismatch);
+            }
+            for version in from_version..STORAGE_VERSION {
+                self.migrate_step(version)?;
+            }
+            self.version = current;
+            self.env().emit_event(Migrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+            Ok(current)
+        }
+
+        /// Move stored state from `version` to `version + 1`
+        /// Each layout change adds an arm here that reads the old entries
+        /// through their versioned structs (e.g. `ExactaBetV1`) and rewrites them
+        fn migrate_step(&mut self, version: u32) -> Result<()> {
+            #[allow(clippy::match_single_binding)]
+            match version {
+                _ => Err(Error::StorageVersionMismatch),
+            }
+        }
+
+        // ========================================================================
+        // SIMULATION HELPER - Run complete race cycle
+        // ========================================================================
+
+        /// Run a complete race simulation (owner only, in dev mode)
+        #[ink(message)]
+        pub fn simulate_complete_race(&mut self, seed: u64) -> Result<RaceResult> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if !self.dev_mode {
+                return Err(Error::DevModeDisabled);
+            }
+            let to = self.ensure_transition(RaceAction::Simulate)?;
+
+            // Start race
+            self.current_seed = seed;
+            self.race_id += 1;
+            self.set_status(to);
+            self.race_start_time = self.env().block_timestamp();
+            self.add_to_meeting();
+
+            // Run simulation (skips the racing window)
+            self.execute_race()
+        }
+
+        /// Result the race would have with this seed, without changing state
+        #[ink(message)]
+        pub fn preview_race(&self, seed: u64) -> RaceResult {
+            self.simulate(seed, &self.running_weights(), self.race_track().as_ref())
+        }
+
+        /// Allow or forbid the simulation helper (owner only)
+        #[ink(message)]
+        pub fn set_dev_mode(&mut self, enabled: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.dev_mode = enabled;
+            Ok(())
+        }
+
+        /// Whether the simulation helper is allowed
+        #[ink(message)]
+        pub fn get_dev_mode(&self) -> bool {
+            self.dev_mode
+        }
+    }
+
+    // ============================================================================
+    // RACE BOOK INTERFACE
+    // ============================================================================
+
+    impl RaceBook for HorseRace {
+        #[ink(message)]
+        fn place_exacta_bet(&mut self, first_pick: u8, second_pick: u8) -> Result<u32> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            self.record_exacta_bet(caller, first_pick, second_pick, amount, false, None, None, 0, None)
+        }
+
+        #[ink(message)]
+        fn place_exacta_bet_for(
+            &mut self,
+            bettor: AccountId,
+            first_pick: u8,
+            second_pick: u8,
+            amount: Balance,
+        ) -> Result<u32> {
+            let caller = self.env().caller();
+            if caller != bettor {
+                let allowance = self.allowances.get((bettor, caller)).unwrap_or(0);
+                if allowance < amount {
+                    return Err(Error::InsufficientAllowance);
+                }
+                self.allowances.insert((bettor, caller), &Self::sub(allowance, amount)?);
+            }
+
+            let bonus = self.debit_stake(bettor, amount)?;
+            self.record_exacta_bet(bettor, first_pick, second_pick, amount, false, None, None, bonus, None)
+        }
+
+        #[ink(message)]
+        fn claim_payout(&mut self) -> Result<Balance> {
+            self.guarded(|this| {
+                let caller = this.env().caller();
+                let unclaimed = this.unclaimed.get(caller).ok_or(Error::NothingToClaim)?;
+                let now = this.env().block_timestamp();
+                if now > unclaimed.deadline {
+                    return Err(Error::ClaimExpired);
+                }
+
+                this.unclaimed.remove(caller);
+                this.credit_balance(caller, unclaimed.amount, LedgerReason::Claim)?;
+                this.last_activity.insert(caller, &now);
+
+                this.env().emit_event(WinningsClaimed {
+                    account: caller,
+                    amount: unclaimed.amount,
+                });
+
+                Ok(unclaimed.amount)
+            })
+        }
+
+        #[ink(message)]
+        fn get_balance(&self, account: AccountId) -> Balance {
+            self.balances.get(account).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        fn get_exacta_odds(&self, first: u8, second: u8) -> Option<ExactaOdds> {
+            if first >= NUM_HORSES as u8 || second >= NUM_HORSES as u8 || first == second {
+                return None;
+            }
+            let multiplier = self.get_multiplier(first, second);
+            if multiplier == 0 {
+                return None;
+            }
+
+            Some(ExactaOdds {
+                first,
+                second,
+                multiplier,
+                decimal_odds: multiplier * PRECISION,
+                // Rounded to the nearest unit
+                implied_probability: (PRECISION + multiplier / 2) / multiplier,
+                model_probability: self.calculate_exacta_probability(first, second),
+            })
+        }
+
+        #[ink(message)]
+        fn get_status(&self) -> RaceStatus {
+            self.status.clone()
+        }
+
+        #[ink(message)]
+        fn get_race_id(&self) -> u64 {
+            self.race_id
+        }
+
+        #[ink(message)]
+        fn get_latest_result(&self) -> RaceResult {
+            self.latest_result.clone()
+        }
+    }
+
+    // ============================================================================
+    // UNIT TESTS
+    // ============================================================================
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn initialization_works() {
+            let contract = HorseRace::new();
+            
+            // Check horses initialized
+            assert_eq!(contract.get_horses().len(), 6);
+            
+            // Check horse strengths
+            let horses = contract.get_horses();
+            assert_eq!(horses[0].strength, 6);
+            assert_eq!(horses[5].strength, 1);
+            
+            // Check status
+            assert_eq!(contract.get_status(), RaceStatus::Betting);
+
+            // Check version
+            let version = contract.get_version();
+            assert_eq!((version.major, version.minor, version.patch), CONTRACT_VERSION);
+            assert_eq!(version.storage_version, STORAGE_VERSION);
+        }
+
+        #[ink::test]
+        fn migrate_refuses_double_and_mismatched_runs() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.migrate(STORAGE_VERSION), Err(Error::AlreadyMigrated));
+
+            // Code upgraded without a layout change: only the semver moves
+            contract.version.minor = 0;
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.migrate(STORAGE_VERSION), Err(Error::NotOwner));
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.migrate(STORAGE_VERSION - 1), Err(Error::StorageVersionMismatch));
+            assert_eq!(contract.migrate(STORAGE_VERSION).unwrap().minor, CONTRACT_VERSION.1);
+            assert_eq!(contract.migrate(STORAGE_VERSION), Err(Error::AlreadyMigrated));
+
+            // A layout with no migration path is refused and left untouched
+            contract.version.storage_version = 0;
+            assert_eq!(contract.migrate(0), Err(Error::StorageVersionMismatch));
+            assert_eq!(contract.get_version().storage_version, 0);
+        }
+
+        #[ink::test]
+        fn normalized_strength_works() {
+            let contract = HorseRace::new();
+            
+            // H[0] strength = 6, normalized = 6/21 * 10000 = 2857
+            let ns = contract.get_normalized_strength(0);
+            assert!(ns > 2850 && ns < 2860);
+            
+            // H[5] strength = 1, normalized = 1/21 * 10000 = 476
+            let ns = contract.get_normalized_strength(5);
+            assert!(ns > 470 && ns < 480);
+        }
+
+        #[ink::test]
+        fn exacta_probability_works() {
+            let contract = HorseRace::new();
+            
+            // P(0 → 1) should be highest
+            let p_01 = contract.calculate_exacta_probability(0, 1);
+            // P(5 → 4) should be lowest
+            let p_54 = contract.calculate_exacta_probability(5, 4);
+            
+            assert!(p_01 > p_54);
+        }
+
+        #[ink::test]
+        fn multipliers_initialized() {
+            let contract = HorseRace::new();
+            
+            // Check specific multipliers
+            assert_eq!(contract.get_reward_multiplier(0, 5), 60);
+            assert_eq!(contract.get_reward_multiplier(4, 5), 1000);
+            assert_eq!(contract.get_reward_multiplier(5, 4), 1500);
+        }
+
+        #[ink::test]
+        fn probability_table_works() {
+            let contract = HorseRace::new();
+            let table = contract.get_exacta_probability_table();
+            
+            // Should have entries for combinations with multipliers
+            assert!(!table.is_empty());
+            
+            // Each entry should have valid data
+            for entry in &table {
+                assert!(entry.first < 6);
+                assert!(entry.second < 6);
+                assert!(entry.first != entry.second);
+                assert!(entry.multiplier > 0);
+            }
+        }
+
+        #[ink::test]
+        fn race_simulation_deterministic() {
+            // Form drifts after every race, so compare from identical state
+            let mut contract1 = HorseRace::new();
+            let mut contract2 = HorseRace::new();
+            
+            // Run with same seed twice
+            let result1 = contract1.simulate_complete_race(12345).unwrap();
+            let result2 = contract2.simulate_complete_race(12345).unwrap();
+            
+            // Results should be identical
+            assert_eq!(result1.rankings, result2.rankings);
+            assert_eq!(result1.winning_exacta, result2.winning_exacta);
+        }
+
+        #[ink::test]
+        fn sectional_times_and_margins_consistent() {
+            let mut contract = HorseRace::new();
+            let result = contract.simulate_complete_race(777).unwrap();
+
+            assert_eq!(result.sectional_times.len(), result.rankings.len());
+            assert_eq!(result.margins.len(), result.rankings.len() - 1);
+
+            for position in 0..result.rankings.len() {
+                let total: u64 = result.sectional_times[position].iter().sum();
+                assert_eq!(total, result.finish_times[position]);
+            }
+            for position in 0..result.margins.len() {
+                assert_eq!(
+                    result.finish_times[position + 1] - result.finish_times[position],
+                    result.margins[position]
+                );
+            }
+            assert_eq!(contract.get_margins_in_lengths().len(), result.margins.len());
+        }
+
+        #[ink::test]
+        fn phase_attributes_shape_sectionals() {
+            let contract = HorseRace::new();
+
+            // Golden Star (fast starter) beats Silver Arrow (closer) early,
+            // Silver Arrow is quicker in the sprint
+            let starter = contract.nominal_sectionals(2, None);
+            let closer = contract.nominal_sectionals(1, None);
+            assert!(starter[0] < closer[0]);
+            assert!(starter[2] > closer[2]);
+
+            // Probabilities over all exactas still sum to ~100%
+            let mut total = 0;
+            for first in 0..6 {
+                for second in 0..6 {
+                    total += contract.calculate_exacta_probability(first, second);
+                }
+            }
+            assert!(total > 9950 && total <= PRECISION);
+        }
+
+        #[ink::test]
+        fn form_updates_after_race() {
+            let mut contract = HorseRace::new();
+            let before = contract.get_horse_form(0).unwrap();
+            assert_eq!(before.adjusted_strength, 6 * PRECISION);
+            assert!(before.recent_finishes.is_empty());
+
+            for seed in 1..=8 {
+                contract.simulate_complete_race(seed).unwrap();
+                contract.reset_for_new_race().unwrap();
+            }
+
+            let result = contract.get_latest_result();
+            let winner = contract.get_horse_form(result.rankings[0]).unwrap();
+            assert_eq!(winner.recent_finishes.len(), FORM_WINDOW);
+            assert_eq!(*winner.recent_finishes.last().unwrap(), 1);
+
+            for id in 0..6u8 {
+                let form = contract.get_horse_form(id).unwrap();
+                let base = HORSE_STRENGTHS[id as usize] * PRECISION;
+                assert!(form.adjusted_strength >= base - base / 5);
+                assert!(form.adjusted_strength <= base + base / 5);
+            }
+            assert!(contract.get_horse_form(6).is_none());
+        }
+
+        #[ink::test]
+        fn betting_and_racing_windows_enforced() {
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+            assert_eq!(contract.set_durations(0, 2_000), Err(Error::InvalidDuration));
+
+            // Betting closes once the window has elapsed
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(10);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_001);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::BettingClosed));
+
+            // Race can't be run before the racing window elapses
+            contract.start_race(42).unwrap();
+            assert_eq!(contract.run_race_simulation(), Err(Error::RaceStillRunning));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3_001);
+            assert!(contract.run_race_simulation().is_ok());
+        }
+
+        #[ink::test]
+        fn start_race_needs_fresh_seed() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            assert_eq!(contract.start_race(0), Err(Error::InvalidSeed));
+            contract.start_race(42).unwrap();
+            ink::env::test::set_block_timestamp::<Env>(3_001);
+            contract.run_race_simulation().unwrap();
+            contract.distribute_payouts().unwrap();
+            contract.reset_for_new_race().unwrap();
+
+            // The next race can't replay the first one's outcome
+            ink::env::test::set_block_timestamp::<Env>(4_002);
+            assert_eq!(contract.start_race(42), Err(Error::SeedAlreadyUsed));
+            contract.start_race(43).unwrap();
+            assert_eq!(contract.get_race_id(), 2);
+        }
+
+        #[ink::test]
+        fn betting_cutoff_stops_late_bets() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_durations(60_000, 2_000).unwrap();
+            assert_eq!(contract.set_betting_cutoff(60_000), Err(Error::InvalidDuration));
+            contract.set_betting_cutoff(30_000).unwrap();
+            assert_eq!(contract.set_durations(30_000, 2_000), Err(Error::InvalidDuration));
+
+            // Bets stop 30 seconds before the window ends, for every market
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_block_timestamp::<Env>(30_000);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_block_timestamp::<Env>(30_001);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::BettingCutoff));
+            assert_eq!(contract.place_match_bet(0, 1), Err(Error::BettingCutoff));
+
+            // The race itself still can't start before the window ends
+            assert_eq!(contract.start_race(42), Err(Error::BettingStillOpen));
+            assert_eq!(contract.get_betting_cutoff(), 30_000);
+        }
+
+        #[ink::test]
+        fn phase_info_counts_down_each_window() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+            contract.set_challenge_period(500).unwrap();
+
+            ink::env::test::set_block_timestamp::<Env>(400);
+            let info = contract.get_phase_info();
+            assert_eq!(info.status, RaceStatus::Betting);
+            assert_eq!((info.started_at, info.duration, info.remaining), (0, 1_000, 600));
+
+            ink::env::test::set_block_timestamp::<Env>(1_500);
+            assert_eq!(contract.get_phase_info().remaining, 0);
+            contract.start_race(42).unwrap();
+            let info = contract.get_phase_info();
+            assert_eq!(info.status, RaceStatus::Racing);
+            assert_eq!((info.started_at, info.duration, info.remaining), (1_500, 2_000, 2_000));
+
+            // Finished races count down the challenge period
+            ink::env::test::set_block_timestamp::<Env>(3_600);
+            contract.run_race_simulation().unwrap();
+            let info = contract.get_phase_info();
+            assert_eq!(info.status, RaceStatus::Finished);
+            assert_eq!((info.duration, info.remaining), (500, 500));
+        }
+
+        #[ink::test]
+        fn anyone_can_crank_after_windows() {
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+
+            // Not before the betting window has elapsed
+            assert_eq!(contract.start_race(42), Err(Error::BettingStillOpen));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_001);
+            contract.start_race(42).unwrap();
+            assert_eq!(contract.get_status(), RaceStatus::Racing);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3_001);
+            let result = contract.run_race_simulation().unwrap();
+            // Keeper-supplied seeds are mixed with block data
+            assert!(result.seed_used != 42);
+
+            assert!(contract.distribute_payouts().is_ok());
+            assert_eq!(contract.get_status(), RaceStatus::Closed);
+        }
+
+        #[ink::test]
+        fn keepers_are_rewarded_from_treasury() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+            contract.set_keeper_reward(5).unwrap();
+
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.fund_treasury().unwrap();
+            let callee = ink::env::test::callee::<Env>();
+            ink::env::test::set_account_balance::<Env>(callee, 10_000_000);
+
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let before = ink::env::test::get_account_balance::<Env>(accounts.bob).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            contract.start_race(7).unwrap();
+
+            let after = ink::env::test::get_account_balance::<Env>(accounts.bob).unwrap();
+            assert_eq!(after, before + 5);
+            assert_eq!(contract.get_treasury(), 95);
+
+            // Owner cranks are not rewarded
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            ink::env::test::set_block_timestamp::<Env>(3_001);
+            contract.run_race_simulation().unwrap();
+            assert_eq!(contract.get_treasury(), 95);
+        }
+
+        #[ink::test]
+        fn meeting_card_numbers_races() {
+            let mut contract = HorseRace::new();
+            let meeting_id = contract.create_meeting(b"Derby Day".to_vec(), 2).unwrap();
+            assert_eq!(
+                contract.create_meeting(b"Other".to_vec(), 3),
+                Err(Error::MeetingInProgress)
+            );
+
+            let first = contract.simulate_complete_race(1).unwrap();
+            contract.reset_for_new_race().unwrap();
+            let second = contract.simulate_complete_race(2).unwrap();
+            contract.reset_for_new_race().unwrap();
+            let third = contract.simulate_complete_race(3).unwrap();
+
+            assert_eq!((first.meeting_id, first.race_number), (Some(meeting_id), 1));
+            assert_eq!((second.meeting_id, second.race_number), (Some(meeting_id), 2));
+            assert_eq!((third.meeting_id, third.race_number), (None, 0));
+
+            assert!(contract.get_active_meeting().is_none());
+            assert_eq!(contract.get_meeting(meeting_id).unwrap().race_ids, vec![1, 2]);
+            assert_eq!(contract.get_meeting_results(meeting_id).len(), 2);
+        }
+
+        #[ink::test]
+        fn race_info_set_when_opening_race() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = HorseRace::new();
+            let info = RaceInfo {
+                name: b"Spring Cup".to_vec(),
+                track: b"Meadowlands".to_vec(),
+                distance: 1_600,
+                start_time: 5_000,
+                commission_free: false,
+            };
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_race_info(info.clone()), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // Info belongs to the race open for betting, dropped if it's reset unrun
+            contract.set_race_info(info.clone()).unwrap();
+            assert_eq!(contract.get_race_info(1), Some(info.clone()));
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.get_race_info(1), None);
+
+            contract.set_race_info(info.clone()).unwrap();
+            let result = contract.simulate_complete_race(1).unwrap();
+            assert_eq!(result.info, info);
+            assert_eq!(contract.get_race_history()[0].info, info);
+            assert_eq!(contract.set_race_info(info), Err(Error::RaceNotInBettingPhase));
+
+            // Races opened without info keep the defaults
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.simulate_complete_race(2).unwrap().info, RaceInfo::default());
+            assert_eq!(contract.get_race_info(2), None);
+        }
+
+        #[ink::test]
+        fn track_profiles_shape_the_race() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = HorseRace::new();
+            let standard = contract.race_weights();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.add_track(b"Ascot".to_vec(), 2_400, Surface::Turf, 9_000), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.add_track(b"Short".to_vec(), 100, Surface::Dirt, PRECISION), Err(Error::InvalidTrack));
+            assert_eq!(contract.add_track(b"Bog".to_vec(), 1_000, Surface::Turf, 7_000), Err(Error::InvalidTrack));
+            let turf = contract.add_track(b"Ascot".to_vec(), 2_400, Surface::Turf, 9_000).unwrap();
+            assert_eq!(contract.set_race_track(Some(turf + 1)), Err(Error::TrackNotFound));
+
+            // Turf suits Dark Knight and not Golden Star
+            contract.set_race_track(Some(turf)).unwrap();
+            let weights = contract.race_weights();
+            assert!(weights[3] > standard[3]);
+            assert!(weights[2] < standard[2]);
+
+            // The result keeps its track and replays on it
+            let on_turf = contract.simulate_complete_race(42).unwrap();
+            assert_eq!(on_turf.track_id, Some(turf));
+            assert_eq!(on_turf.weights, weights.to_vec());
+            assert!(contract.verify_result(1).unwrap().0);
+            assert_eq!(contract.get_race_track(1).map(|track| track.name), Some(b"Ascot".to_vec()));
+
+            // A longer, softer track runs slower than the standard course
+            contract.reset_for_new_race().unwrap();
+            let standard_result = contract.simulate_complete_race(42).unwrap();
+            assert_eq!(standard_result.track_id, None);
+            assert!(on_turf.finish_times[0] > standard_result.finish_times[0]);
+
+            // Selection is dropped with a race reset before it runs
+            contract.reset_for_new_race().unwrap();
+            contract.set_race_track(Some(turf)).unwrap();
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.get_race_track(3), None);
+        }
+
+        #[ink::test]
+        fn daily_double_settles_after_second_leg() {
+            // Same seeds on identical state give the leg winners
+            let mut scratch = HorseRace::new();
+            let leg1 = scratch.simulate_complete_race(11).unwrap();
+            scratch.reset_for_new_race().unwrap();
+            let leg2 = scratch.simulate_complete_race(12).unwrap();
+            let (w1, w2) = (leg1.rankings[0], leg2.rankings[0]);
+
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            let winning = contract.place_daily_double(w1, w2).unwrap();
+            let losing = contract.place_daily_double(w1, (w2 + 1) % 6).unwrap();
+            assert_eq!(contract.get_daily_double_escrow(), 200);
+
+            contract.simulate_complete_race(11).unwrap();
+            contract.distribute_payouts().unwrap();
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.get_daily_double(winning).unwrap().status, BetStatus::Pending);
+
+            contract.simulate_complete_race(12).unwrap();
+            contract.distribute_payouts().unwrap();
+
+            let won = contract.get_daily_double(winning).unwrap();
+            assert_eq!(won.status, BetStatus::Won);
+            assert_eq!(won.payout, (100 * won.odds as u128) / PRECISION as u128);
+            assert!(won.payout > 100);
+            let lost = contract.get_daily_double(losing).unwrap();
+            assert!(lost.status == BetStatus::Lost || lost.status == BetStatus::Refunded);
+            assert_eq!(contract.get_daily_double_escrow(), 0);
+        }
+
+        #[ink::test]
+        fn pick6_pays_jackpot_and_consolation() {
+            let mut scratch = HorseRace::new();
+            let mut winners: Vec<u8> = Vec::new();
+            for seed in 1..=6 {
+                winners.push(scratch.simulate_complete_race(seed).unwrap().rankings[0]);
+                scratch.reset_for_new_race().unwrap();
+            }
+            let mut five_of_six = winners.clone();
+            five_of_six[5] = (winners[5] + 1) % 6;
+
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.place_pick6(winners.clone()), Err(Error::NoPick6Pool));
+            let pool_id = contract.open_pick6().unwrap();
+            assert_eq!(contract.open_pick6(), Err(Error::Pick6InProgress));
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(contract.place_pick6(vec![0, 1]), Err(Error::InvalidPickCount));
+            contract.place_pick6(winners.clone()).unwrap();
+            contract.place_pick6(five_of_six).unwrap();
+
+            for seed in 1..=6 {
+                if seed == 3 {
+                    assert_eq!(contract.settle_pick6(), Err(Error::Pick6NotReady));
+                }
+                contract.simulate_complete_race(seed).unwrap();
+                contract.reset_for_new_race().unwrap();
+            }
+
+            let pool = contract.settle_pick6().unwrap();
+            assert_eq!(pool.jackpot_paid, 180);
+            assert_eq!(pool.consolation_paid, 20);
+            assert_eq!(pool.carryover_out, 0);
+
+            let entries = contract.get_pick6_entries(pool_id);
+            assert_eq!((entries[0].hits, entries[0].payout), (6, 180));
+            assert_eq!((entries[1].hits, entries[1].payout), (5, 20));
+        }
+
+        #[ink::test]
+        fn match_bets_settle_from_rankings() {
+            // Favourite pays less than the outsider in the same match
+            let mut contract = HorseRace::new();
+            assert!(contract.get_match_odds(0, 5) < contract.get_match_odds(5, 0));
+            assert!(contract.get_match_odds(0, 5) > 0);
+            assert_eq!(contract.get_match_odds(0, 0), 0);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            contract.place_match_bet(0, 5).unwrap();
+            contract.place_match_bet(5, 0).unwrap();
+            assert_eq!(contract.place_match_bet(2, 2), Err(Error::SameHorsePicked));
+
+            contract.simulate_complete_race(99).unwrap();
+            contract.distribute_payouts().unwrap();
+
+            let bets = contract.get_match_bets();
+            let won = bets.iter().filter(|bet| bet.status == BetStatus::Won).count();
+            let refunded = bets.iter().filter(|bet| bet.status == BetStatus::Refunded).count();
+            assert!(won == 1 || refunded == 2);
+            for bet in bets.iter().filter(|bet| bet.status == BetStatus::Won) {
+                assert_eq!(bet.payout, (100 * bet.odds as u128) / PRECISION as u128);
+            }
+        }
+
+        #[ink::test]
+        fn over_under_settles_on_winning_time() {
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(contract.place_over_under_bet(true), Err(Error::NoLineSet));
+            assert_eq!(contract.set_finish_time_line(50_000, PRECISION, 19_000), Err(Error::InvalidOdds));
+
+            contract.set_finish_time_line(50_000, 18_000, 19_000).unwrap();
+            contract.place_over_under_bet(true).unwrap();
+            contract.place_over_under_bet(false).unwrap();
+
+            let result = contract.simulate_complete_race(5).unwrap();
+            contract.distribute_payouts().unwrap();
+
+            let bets = contract.get_over_under_bets();
+            let over_wins = result.finish_times[0] > 50_000;
+            let (winner, loser) = if over_wins { (&bets[0], &bets[1]) } else { (&bets[1], &bets[0]) };
+            assert_eq!(winner.status, BetStatus::Won);
+            assert_eq!(winner.payout, (100 * winner.odds as u128) / PRECISION as u128);
+            assert_eq!(loser.status, BetStatus::Lost);
+
+            contract.reset_for_new_race().unwrap();
+            assert!(contract.get_finish_time_line().is_none());
+        }
+
+        #[ink::test]
+        fn futures_settle_at_season_end() {
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(contract.place_futures_bet(0), Err(Error::FuturesMarketClosed));
+
+            for horse in 0..6 {
+                contract.set_futures_odds(horse, 50_000).unwrap();
+            }
+            let bet_ids: Vec<u32> = (0..6).map(|horse| contract.place_futures_bet(horse).unwrap()).collect();
+            assert_eq!(contract.get_futures_escrow(), 600);
+
+            // Bets survive the per-race reset
+            for seed in 1..=5 {
+                contract.simulate_complete_race(seed).unwrap();
+                contract.reset_for_new_race().unwrap();
+            }
+
+            let champions = contract.settle_futures().unwrap();
+            assert!(!champions.is_empty());
+            for bet_id in bet_ids {
+                let bet = contract.get_futures_bet(bet_id).unwrap();
+                if champions.contains(&bet.horse) {
+                    assert_eq!(bet.status, BetStatus::Won);
+                    assert_eq!(bet.payout, 500 / champions.len() as u128);
+                } else {
+                    assert_eq!(bet.status, BetStatus::Lost);
+                }
+            }
+            assert_eq!(contract.get_futures_escrow(), 0);
+            assert_eq!(contract.get_season_wins(), (2, vec![0; 6]));
+        }
+
+        #[ink::test]
+        fn season_points_snapshot_on_close() {
+            let mut contract = HorseRace::new();
+            let mut points = [0u32; 6];
+            for seed in 1..=4 {
+                let result = contract.simulate_complete_race(seed).unwrap();
+                for (position, &horse) in result.rankings.iter().enumerate() {
+                    points[horse as usize] += SEASON_POINTS[position];
+                }
+                contract.reset_for_new_race().unwrap();
+            }
+
+            let live = contract.get_season_standings();
+            assert_eq!((live.season, live.races), (1, 4));
+            for pair in live.standings.windows(2) {
+                assert!(pair[0].points >= pair[1].points);
+            }
+            for standing in live.standings.iter() {
+                assert_eq!(standing.points, points[standing.horse_id as usize]);
+            }
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.close_season(), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            let closed = contract.close_season().unwrap();
+            assert_eq!(closed.standings, live.standings);
+            assert!(!closed.champions.is_empty());
+            assert_eq!(contract.get_season_history(1), Some(closed));
+
+            let next = contract.get_season_standings();
+            assert_eq!((next.season, next.races), (2, 0));
+            assert!(next.standings.iter().all(|standing| standing.points == 0));
+        }
+
+        #[ink::test]
+        fn leaderboard_ranks_by_net_winnings() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            contract
+                .update_leaderboard(&[(accounts.bob, 100, 500), (accounts.charlie, 100, 0), (accounts.django, 50, 0)])
+                .unwrap();
+            let ranked: Vec<(AccountId, i128)> =
+                contract.get_leaderboard(1).iter().map(|entry| (entry.account, entry.net_winnings)).collect();
+            assert_eq!(ranked, vec![(accounts.bob, 400), (accounts.django, -50), (accounts.charlie, -100)]);
+
+            // Only the changed bettor moves
+            contract.update_leaderboard(&[(accounts.charlie, 100, 1_000)]).unwrap();
+            assert_eq!(contract.get_leaderboard(1)[0].account, accounts.charlie);
+            let charlie = contract.get_bettor_season(1, accounts.charlie).unwrap();
+            assert_eq!((charlie.net_winnings, charlie.staked, charlie.bets), (800, 200, 2));
+
+            // The board keeps only the top entries
+            let crowd: Vec<(AccountId, Balance, Balance)> =
+                (1..=12u8).map(|i| (AccountId::from([i; 32]), 10, 10 + i as u128)).collect();
+            contract.update_leaderboard(&crowd).unwrap();
+            let board = contract.get_leaderboard(1);
+            assert_eq!(board.len(), LEADERBOARD_SIZE);
+            assert_eq!(board[1].account, accounts.bob);
+            assert_eq!(board.last().unwrap().net_winnings, 5);
+            assert!(contract.get_leaderboard(2).is_empty());
+        }
+
+        #[ink::test]
+        fn global_stats_track_volume_and_record_payouts() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            contract.on_wager(accounts.bob, 100).unwrap();
+            contract.on_wager(accounts.bob, 50).unwrap();
+            contract.on_wager(accounts.charlie, 10).unwrap();
+            let stats = contract.get_global_stats();
+            assert_eq!((stats.total_volume, stats.total_bets, stats.unique_bettors), (160, 3, 2));
+
+            contract.credit_winnings(accounts.bob, 500, LedgerReason::Payout).unwrap();
+            contract.credit_winnings(accounts.charlie, 300, LedgerReason::Payout).unwrap();
+            contract.credit_winnings(accounts.charlie, 1_000, LedgerReason::Refund).unwrap();
+            let stats = contract.get_global_stats();
+            assert_eq!(stats.total_payouts, 800);
+            assert_eq!(stats.biggest_payout, 500);
+            assert_eq!(stats.biggest_payout_bettor, Some(accounts.bob));
+            assert_eq!(stats.biggest_payout_race, 0);
+
+            let signature = <RecordPayout as ink::env::Event>::SIGNATURE_TOPIC.unwrap();
+            let records = ink::env::test::recorded_events().filter(|event| event.topics[0] == signature).count();
+            assert_eq!(records, 1);
+        }
+
+        #[ink::test]
+        fn race_result_records_betting_stats() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let offered = contract.get_exacta_odds_table();
+            ink::env::test::set_value_transferred::<Env>(10);
+            for odds in &offered {
+                contract.place_exacta_bet(odds.first, odds.second).unwrap();
+            }
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            for odds in &offered {
+                contract.place_exacta_bet(odds.first, odds.second).unwrap();
+            }
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            let result = contract.simulate_complete_race(11).unwrap();
+            let (first, second) = result.winning_exacta;
+            assert_eq!(result.total_bets, 2 * offered.len() as u32);
+            assert_eq!((result.winning_bets, result.winning_stake), (2, 20));
+            assert_eq!(result.multiplier, contract.get_multiplier(first, second));
+            assert_eq!(contract.get_latest_result(), result);
+        }
+
+        #[ink::test]
+        fn pending_winnings_match_settlement() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(10);
+            for odds in contract.get_exacta_odds_table() {
+                contract.place_exacta_bet(odds.first, odds.second).unwrap();
+            }
+            assert_eq!(contract.get_pending_winnings(accounts.bob), 0);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(11).unwrap();
+            let pending = contract.get_pending_winnings(accounts.bob);
+            assert!(pending > 0);
+            assert_eq!(contract.get_pending_winnings(accounts.charlie), 0);
+
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), pending);
+            assert_eq!(contract.get_pending_winnings(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn settlement_report_summarizes_race() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<Env>(10);
+            for odds in contract.get_exacta_odds_table() {
+                contract.place_exacta_bet(odds.first, odds.second).unwrap();
+            }
+
+            let result = contract.simulate_complete_race(11).unwrap();
+            assert_eq!(contract.get_settlement_report(1), None);
+            contract.distribute_payouts().unwrap();
+
+            let report = contract.get_settlement_report(1).unwrap();
+            let (first, second) = result.winning_exacta;
+            let multiplier = contract.get_multiplier(first, second);
+            assert_eq!(report.rankings, result.rankings);
+            assert_eq!(report.did_not_finish, result.did_not_finish);
+            assert_eq!((report.winning_exacta, report.multiplier), ((first, second), multiplier));
+            assert_eq!((report.winners, report.exacta_paid), (1, 10 * multiplier as u128));
+            let pnl = contract.get_race_pnl(1).unwrap();
+            assert_eq!((report.total_staked, report.total_paid, report.rake), (pnl.staked, pnl.paid_out, pnl.rake));
+            assert_eq!((report.carryover_in, report.carryover_out), (0, 0));
+        }
+
+        #[ink::test]
+        fn bet_ids_follow_bets_to_payout() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(10);
+            let ids: Vec<u32> = contract
+                .get_exacta_odds_table()
+                .iter()
+                .map(|odds| contract.place_exacta_bet(odds.first, odds.second).unwrap())
+                .collect();
+            assert_eq!(ids, (0..ids.len() as u32).collect::<Vec<u32>>());
+
+            // Topping up a position keeps its id
+            let record = contract.get_bet_by_id(ids[1]).unwrap();
+            assert_eq!(contract.place_exacta_bet(record.bet.first_pick, record.bet.second_pick), Ok(ids[1]));
+            assert_eq!(contract.get_bet_by_id(ids[1]).unwrap().bet.amount, 20);
+            assert_eq!(contract.get_bet_by_id(ids.len() as u32), None);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            let result = contract.simulate_complete_race(11).unwrap();
+            contract.distribute_payouts().unwrap();
+            let signature = PayoutDistributed::SIGNATURE_TOPIC.unwrap();
+            let paid: Vec<u32> = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == signature)
+                .filter_map(|event| PayoutDistributed::decode(&mut &event.data[..]).unwrap().bet_id)
+                .collect();
+            assert_eq!(paid.len(), 1);
+            let winner = contract.get_bet_by_id(paid[0]).unwrap();
+            assert_eq!((winner.bet.first_pick, winner.bet.second_pick), result.winning_exacta);
+            assert_eq!(winner.race_id, 1);
+        }
+
+        #[ink::test]
+        fn gift_bets_pay_the_beneficiary() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(10);
+            let offered = contract.get_exacta_odds_table();
+            for odds in &offered {
+                contract.place_gift_bet(odds.first, odds.second, accounts.charlie).unwrap();
+            }
+            let bet = contract.get_bets()[0].clone();
+            assert_eq!((bet.bettor, bet.beneficiary), (accounts.bob, Some(accounts.charlie)));
+
+            // The bettor's own bet on the same exacta stays a separate position
+            let own_id = offered.len() as u32;
+            assert_eq!(contract.place_exacta_bet(offered[0].first, offered[0].second), Ok(own_id));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(11).unwrap();
+            let pending = contract.get_pending_winnings(accounts.charlie);
+            assert!(pending > 0);
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_balance(accounts.charlie), pending);
+
+            let signature = PayoutDistributed::SIGNATURE_TOPIC.unwrap();
+            let paid: Vec<(AccountId, AccountId)> = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == signature)
+                .map(|event| {
+                    let payout = PayoutDistributed::decode(&mut &event.data[..]).unwrap();
+                    (payout.bettor, payout.placed_by)
+                })
+                .collect();
+            assert!(paid.contains(&(accounts.charlie, accounts.bob)));
+        }
+
+        #[ink::test]
+        fn bet_history_by_account() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(2, 3).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(1).unwrap();
+            contract.reset_for_new_race().unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(4, 5).unwrap();
+
+            assert_eq!(contract.get_bet_count_by_account(accounts.bob), 2);
+            let history = contract.get_bets_by_account(accounts.bob, 0, 10);
+            assert_eq!(history.len(), 2);
+            assert_eq!((history[0].race_id, history[0].bet.first_pick), (1, 0));
+            assert_eq!((history[1].race_id, history[1].bet.first_pick), (2, 4));
+
+            let page = contract.get_bets_by_account(accounts.bob, 1, 1);
+            assert_eq!(page.len(), 1);
+            assert_eq!(page[0].id, 2);
+            assert!(contract.get_bets_by_account(accounts.django, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn unclaimed_winnings_expire_to_treasury() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_claim_config(true, 1_000, 5_000).unwrap();
+
+            contract.credit_winnings(accounts.bob, 300, LedgerReason::Payout).unwrap();
+            contract.credit_winnings(accounts.charlie, 200, LedgerReason::Payout).unwrap();
+            assert_eq!(contract.get_unclaimed(accounts.bob).unwrap().amount, 300);
+            assert_eq!(contract.get_grace_period(accounts.bob).0, 1_000);
+            assert_eq!(contract.sweep_unclaimed(accounts.bob), Err(Error::GracePeriodActive));
+
+            // Bob claims in time, Charlie does not
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.claim_payout(), Ok(300));
+            assert_eq!(contract.get_balance(accounts.bob), 300);
+
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.claim_payout(), Err(Error::ClaimExpired));
+            assert_eq!(contract.sweep_unclaimed(accounts.charlie), Ok(200));
+            assert_eq!(contract.get_treasury(), 200);
+            assert_eq!(contract.claim_payout(), Err(Error::NothingToClaim));
+        }
+
+        #[ink::test]
+        fn dormant_balances_sweep_to_treasury() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_claim_config(false, 1_000, 5_000).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(500);
+            contract.deposit().unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 500);
+            assert_eq!(contract.withdraw(600), Err(Error::InsufficientBalance));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.sweep_dormant_balance(accounts.bob), Err(Error::GracePeriodActive));
+            ink::env::test::set_block_timestamp::<Env>(5_001);
+            assert_eq!(contract.get_grace_period(accounts.bob), (0, 0));
+            assert_eq!(contract.sweep_dormant_balance(accounts.bob), Ok(500));
+            assert_eq!(contract.get_balance(accounts.bob), 0);
+            assert_eq!(contract.get_treasury(), 500);
+        }
+
+        #[ink::test]
+        fn loyalty_points_accrue_and_redeem() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(250);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_match_bet(0, 1).unwrap();
+            assert_eq!(contract.get_points(accounts.bob), 500);
+
+            assert_eq!(contract.redeem_points(600), Err(Error::InsufficientPoints));
+            assert_eq!(contract.redeem_points(450), Ok(4));
+            assert_eq!(contract.get_points(accounts.bob), 100);
+            assert_eq!(contract.get_free_bet_credits(accounts.bob), 4);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_loyalty_config(20000, 100).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(2, 3).unwrap();
+            assert_eq!(contract.get_points(accounts.bob), 600);
+        }
+
+        #[ink::test]
+        fn free_bets_need_wagering_before_release() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_wagering_requirement(20000).unwrap();
+            contract.grant_free_bet_credits(accounts.bob, 100).unwrap();
+            assert_eq!(contract.get_wagering_progress(accounts.bob), (0, 200));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.place_free_bet(0, 1, 150), Err(Error::InsufficientCredits));
+            contract.place_free_bet(0, 1, 40).unwrap();
+            assert_eq!(contract.get_free_bet_credits(accounts.bob), 60);
+            assert!(contract.get_bets()[0].free_bet);
+
+            assert_eq!(contract.release_free_bet_credits(), Err(Error::WageringRequirementNotMet));
+
+            ink::env::test::set_value_transferred::<Env>(160);
+            contract.place_exacta_bet(2, 3).unwrap();
+            assert_eq!(contract.get_wagering_progress(accounts.bob), (200, 200));
+            assert_eq!(contract.release_free_bet_credits(), Ok(60));
+            assert_eq!(contract.get_balance(accounts.bob), 60);
+            assert_eq!(contract.get_free_bet_credits(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn bonus_funds_spent_first_and_released_after_turnover() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_wagering_requirement(20000).unwrap();
+            contract.grant_bonus(accounts.bob, 100).unwrap();
+            assert_eq!(contract.get_wagering_progress(accounts.bob), (0, 200));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.deposit().unwrap();
+            contract.place_exacta_bet_for(accounts.bob, 0, 1, 120).unwrap();
+            assert_eq!(contract.get_bonus_balance(accounts.bob), 0);
+            assert_eq!(contract.get_balance(accounts.bob), 30);
+            assert_eq!(contract.get_bets()[0].bonus_stake, 100);
+
+            // The bonus-funded share of a return goes back to the bonus balance
+            let price = contract.cash_out(0).unwrap();
+            let bonus = price * 100 / 120;
+            assert_eq!(contract.get_bonus_balance(accounts.bob), bonus);
+            assert_eq!(contract.get_balance(accounts.bob), 30 + price - bonus);
+            assert_eq!(contract.release_bonus(), Err(Error::WageringRequirementNotMet));
+
+            ink::env::test::set_value_transferred::<Env>(80);
+            contract.place_exacta_bet(2, 3).unwrap();
+            assert_eq!(contract.release_bonus(), Ok(bonus));
+            assert_eq!(contract.get_balance(accounts.bob), 30 + price);
+            assert_eq!(contract.get_bonus_balance(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn promo_codes_credit_bonus_once_per_account() {
+            use ink::env::hash::{Blake2x256, CryptoHash};
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let mut hash = [0u8; 32];
+            Blake2x256::hash(b"DERBY", &mut hash);
+            contract.create_promo_code(Hash::from(hash), 25, 2, 1_000).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.redeem_code(String::from("derby")), Err(Error::PromoCodeNotFound));
+            assert_eq!(contract.redeem_code(String::from("DERBY")), Ok(25));
+            assert_eq!(contract.redeem_code(String::from("DERBY")), Err(Error::PromoCodeAlreadyUsed));
+            assert_eq!(contract.get_bonus_balance(accounts.bob), 25);
+
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            assert_eq!(contract.redeem_code(String::from("DERBY")), Err(Error::PromoCodeExpired));
+            ink::env::test::set_block_timestamp::<Env>(1_000);
+            contract.redeem_code(String::from("DERBY")).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.django);
+            assert_eq!(contract.redeem_code(String::from("DERBY")), Err(Error::PromoCodeExhausted));
+            assert_eq!(contract.get_promo_code(Hash::from(hash)).unwrap().uses, 2);
+        }
+
+        #[ink::test]
+        fn withdrawal_requests_are_processed_or_rejected() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_custodial_withdrawals(true).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(500);
+            contract.deposit().unwrap();
+            assert_eq!(contract.withdraw(100), Err(Error::CustodialWithdrawals));
+            assert_eq!(contract.request_withdrawal(600), Err(Error::InsufficientBalance));
+            assert_eq!(contract.request_withdrawal(100), Ok(0));
+            assert_eq!(contract.request_withdrawal(300), Ok(1));
+            assert_eq!(contract.get_balance(accounts.bob), 100);
+            assert_eq!(contract.get_pending_withdrawals().len(), 2);
+            assert_eq!(contract.process_withdrawal(0), Err(Error::NotOperator));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.process_withdrawal(0).unwrap();
+            contract.reject_withdrawal(1).unwrap();
+            assert_eq!(contract.reject_withdrawal(0), Err(Error::WithdrawalNotPending));
+            assert_eq!(contract.get_withdrawal_request(0).unwrap().status, WithdrawalStatus::Processed);
+            assert_eq!(contract.get_withdrawal_request(1).unwrap().status, WithdrawalStatus::Rejected);
+            assert!(contract.get_pending_withdrawals().is_empty());
+            assert_eq!(contract.get_balance(accounts.bob), 400);
+        }
+
+        #[ink::test]
+        fn withdrawals_pay_fee_within_daily_cap() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.set_withdrawal_limits(10_001, 0), Err(Error::InvalidConfig));
+            contract.set_withdrawal_limits(100, 1_000).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(2_000);
+            contract.deposit().unwrap();
+            assert_eq!(contract.get_withdrawal_allowance(accounts.bob), Some(1_000));
+            contract.withdraw(600).unwrap();
+            assert_eq!(contract.get_treasury(), 6);
+            assert_eq!(contract.withdraw(500), Err(Error::WithdrawalCapExceeded));
+            assert_eq!(contract.get_withdrawal_allowance(accounts.bob), Some(400));
+
+            // Rejected requests return the fee and the allowance
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_custodial_withdrawals(true).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let request_id = contract.request_withdrawal(400).unwrap();
+            assert_eq!(contract.get_withdrawal_request(request_id).unwrap().fee, 4);
+            assert_eq!(contract.get_withdrawal_allowance(accounts.bob), Some(0));
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.reject_withdrawal(request_id).unwrap();
+            assert_eq!(contract.get_treasury(), 6);
+            assert_eq!(contract.get_withdrawal_allowance(accounts.bob), Some(400));
+
+            // The window rolls after a day
+            ink::env::test::set_block_timestamp::<Env>(DAY_MS);
+            assert_eq!(contract.get_withdrawal_allowance(accounts.bob), Some(1_000));
+        }
+
+        #[ink::test]
+        fn self_limits_lower_now_and_raise_later() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+
+            let limits = BettingLimits { daily_wager: 100, ..Default::default() };
+            contract.set_my_limits(limits).unwrap();
+            assert_eq!(contract.get_my_limits(accounts.bob), limits);
+
+            ink::env::test::set_value_transferred::<Env>(60);
+            contract.place_exacta_bet(0, 1).unwrap();
+            assert_eq!(contract.place_exacta_bet(0, 2), Err(Error::WagerLimitExceeded));
+
+            // Raising is delayed by 24 hours
+            let raised = BettingLimits { daily_wager: 500, ..Default::default() };
+            contract.set_my_limits(raised).unwrap();
+            assert_eq!(contract.get_my_limits(accounts.bob), limits);
+            assert_eq!(contract.get_pending_limits(accounts.bob), Some((raised, LIMIT_INCREASE_DELAY)));
+            assert_eq!(contract.place_exacta_bet(0, 2), Err(Error::WagerLimitExceeded));
+
+            ink::env::test::set_block_timestamp::<Env>(LIMIT_INCREASE_DELAY);
+            assert_eq!(contract.get_my_limits(accounts.bob), raised);
+            assert_eq!(contract.get_pending_limits(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn fiat_limits_convert_at_cached_rate() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            // $1 to $500 at $5 a token
+            contract.set_fiat_bet_limits(100, 50_000).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(ASSET_UNIT);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::PriceFeedUnavailable));
+            contract.fiat_rate = Some(FiatRate { price: 500, updated_at: 0 });
+
+            ink::env::test::set_value_transferred::<Env>(ASSET_UNIT / 10);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::BetBelowMinimum));
+            ink::env::test::set_value_transferred::<Env>(ASSET_UNIT * 101);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::BetAboveMaximum));
+
+            // A $10 daily limit applies immediately to an unlimited account
+            let limits = BettingLimits { daily_wager: 1_000, in_fiat: true, ..Default::default() };
+            contract.set_my_limits(limits).unwrap();
+            assert_eq!(contract.get_my_limits(accounts.bob), limits);
+            ink::env::test::set_value_transferred::<Env>(ASSET_UNIT * 3 / 2);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_value_transferred::<Env>(ASSET_UNIT);
+            assert_eq!(contract.place_exacta_bet(0, 2), Err(Error::WagerLimitExceeded));
+
+            // Switching back to asset units waits like any raise
+            contract.set_my_limits(BettingLimits { daily_wager: 1, ..Default::default() }).unwrap();
+            assert_eq!(contract.get_my_limits(accounts.bob), limits);
+
+            contract.max_price_age = 1_000;
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            assert_eq!(contract.place_exacta_bet(0, 2), Err(Error::PriceStale));
+            assert_eq!(contract.set_fiat_bet_limits(0, 0), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn asset_decimals_normalize_fiat_limits() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.set_asset_decimals(19), Err(Error::InvalidConfig));
+
+            // A 6-decimal stablecoin at $1 with a $1 minimum bet
+            contract.set_asset_decimals(6).unwrap();
+            contract.set_fiat_bet_limits(100, 0).unwrap();
+            contract.fiat_rate = Some(FiatRate { price: 100, updated_at: 0 });
+            assert_eq!(contract.fiat_to_asset(100), Ok(1_000_000));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(999_999);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::BetBelowMinimum));
+            ink::env::test::set_value_transferred::<Env>(1_000_000);
+            contract.place_exacta_bet(0, 1).unwrap();
+
+            // The same $1 in an 18-decimal asset
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.set_asset_decimals(18), Err(Error::FundsHeld));
+            let mut contract = HorseRace::new();
+            contract.set_asset_decimals(18).unwrap();
+            contract.fiat_rate = Some(FiatRate { price: 100, updated_at: 0 });
+            assert_eq!(contract.fiat_to_asset(100), Ok(1_000_000_000_000_000_000));
+        }
+
+        #[ink::test]
+        fn self_exclusion_blocks_bets_and_deposits() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+
+            contract.self_exclude(1000).unwrap();
+            assert_eq!(contract.get_exclusion(accounts.bob), Some(1000));
+            // Can't be shortened
+            contract.self_exclude(10).unwrap();
+            assert_eq!(contract.get_exclusion(accounts.bob), Some(1000));
+
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::SelfExcluded));
+            assert_eq!(contract.deposit(), Err(Error::SelfExcluded));
+
+            ink::env::test::set_block_timestamp::<Env>(1000);
+            assert_eq!(contract.get_exclusion(accounts.bob), None);
+            contract.place_exacta_bet(0, 1).unwrap();
+        }
+
+        #[ink::test]
+        fn frozen_accounts_cannot_bet_or_withdraw() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+            assert_eq!(contract.freeze_account(accounts.bob), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.freeze_account(accounts.bob).unwrap();
+            assert!(contract.is_frozen(accounts.bob));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::AccountFrozen));
+            assert_eq!(contract.withdraw(50), Err(Error::AccountFrozen));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.unfreeze_account(accounts.bob).unwrap();
+            assert!(!contract.is_frozen(accounts.bob));
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(0, 1).unwrap();
+        }
+
+        #[ink::test]
+        fn whitelist_mode_limits_bettors() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_whitelist_mode(true).unwrap();
+            contract.batch_set_whitelisted(vec![accounts.bob, accounts.charlie], true).unwrap();
+            contract.set_whitelisted(accounts.charlie, false).unwrap();
+            assert!(contract.is_whitelisted(accounts.bob));
+            assert!(!contract.is_whitelisted(accounts.charlie));
+
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(0, 1).unwrap();
+            assert_eq!(contract.set_whitelisted(accounts.bob, true), Err(Error::NotOwner));
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::NotWhitelisted));
+
+            // Open races take everyone again
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_whitelist_mode(false).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(0, 1).unwrap();
+        }
+
+        #[ink::test]
+        fn attestation_mode_gates_bets_and_deposits() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_attestation_mode(true).unwrap();
+            contract.set_compliance_operator(Some(accounts.django)).unwrap();
+
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::AttestationRequired));
+            assert_eq!(contract.deposit(), Err(Error::AttestationRequired));
+            assert_eq!(contract.set_attested(accounts.bob, true), Err(Error::NotComplianceOperator));
+
+            ink::env::test::set_caller::<Env>(accounts.django);
+            contract.set_attested(accounts.bob, true).unwrap();
+            assert!(contract.is_attested(accounts.bob));
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.deposit().unwrap();
+            contract.place_exacta_bet(0, 1).unwrap();
+
+            // Revoked attestations take effect on the next bet
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_attested(accounts.bob, false).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.place_exacta_bet(0, 1), Err(Error::AttestationRequired));
+        }
+
+        #[ink::test]
+        fn transitions_are_checked_in_one_place() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(
+                contract.run_race_simulation(),
+                Err(Error::InvalidTransition { from: RaceStatus::Betting, to: RaceStatus::Finished })
+            );
+            assert_eq!(
+                contract.distribute_payouts(),
+                Err(Error::InvalidTransition { from: RaceStatus::Betting, to: RaceStatus::Closed })
+            );
+
+            // The simulation helper can't skip over a race that already ran
+            contract.simulate_complete_race(1).unwrap();
+            let rerun = Err(Error::InvalidTransition { from: RaceStatus::Finished, to: RaceStatus::Racing });
+            assert_eq!(contract.simulate_complete_race(2).map(|_| ()), rerun);
+            assert_eq!(contract.start_race(2), rerun);
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.reset_for_new_race(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn simulation_helper_needs_owner_and_dev_mode() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.simulate_complete_race(7).map(|_| ()), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_dev_mode(false).unwrap();
+            assert_eq!(contract.simulate_complete_race(7).map(|_| ()), Err(Error::DevModeDisabled));
+
+            // The preview is pure and matches the real run
+            let preview = contract.preview_race(7);
+            assert_eq!((contract.get_status(), contract.get_race_id()), (RaceStatus::Betting, 0));
+            contract.set_dev_mode(true).unwrap();
+            let result = contract.simulate_complete_race(7).unwrap();
+            assert_eq!((result.rankings, result.winning_exacta), (preview.rankings, preview.winning_exacta));
+        }
+
+        #[ink::test]
+        fn status_transitions_emit_events() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+
+            let mut contract = HorseRace::new();
+            contract.simulate_complete_race(7).unwrap();
+            contract.distribute_payouts().unwrap();
+            contract.reset_for_new_race().unwrap();
+
+            let signature = RaceStatusChanged::SIGNATURE_TOPIC.unwrap();
+            let transitions: Vec<(RaceStatus, RaceStatus)> = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == signature)
+                .map(|event| {
+                    let changed = RaceStatusChanged::decode(&mut &event.data[..]).unwrap();
+                    (changed.from, changed.to)
+                })
+                .collect();
+            assert_eq!(transitions, vec![
+                (RaceStatus::Betting, RaceStatus::Racing),
+                (RaceStatus::Racing, RaceStatus::Finished),
+                (RaceStatus::Finished, RaceStatus::Closed),
+                (RaceStatus::Closed, RaceStatus::Betting),
+            ]);
+        }
+
+        #[ink::test]
+        fn balance_overflow_is_rejected() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.credit_winnings(accounts.bob, Balance::MAX, LedgerReason::Payout).unwrap();
+            assert_eq!(contract.credit_winnings(accounts.bob, 1, LedgerReason::Payout), Err(Error::ArithmeticOverflow));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(1);
+            assert_eq!(contract.deposit(), Err(Error::ArithmeticOverflow));
+            assert_eq!(contract.get_balance(accounts.bob), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn locked_contract_rejects_reentry() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+
+            contract.reentrancy_lock = true;
+            assert_eq!(contract.withdraw(50), Err(Error::ReentrantCall));
+            assert_eq!(contract.claim_payout(), Err(Error::ReentrantCall));
+
+            contract.reentrancy_lock = false;
+            ink::env::test::set_account_balance::<Env>(ink::env::test::callee::<Env>(), 10_000_000);
+            contract.withdraw(50).unwrap();
+            assert!(!contract.reentrancy_lock);
+        }
+
+        #[ink::test]
+        fn batch_deposit_credits_each_account() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let entries = vec![(accounts.bob, 300), (accounts.charlie, 200)];
+
+            ink::env::test::set_value_transferred::<Env>(400);
+            assert_eq!(contract.batch_deposit(entries.clone()), Err(Error::DepositMismatch));
+
+            ink::env::test::set_value_transferred::<Env>(500);
+            contract.batch_deposit(entries.clone()).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 300);
+            assert_eq!(contract.get_balance(accounts.charlie), 200);
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.batch_deposit(entries), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn cross_chain_deposits_record_provenance() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let message_id = Hash::from([7; 32]);
+
+            ink::env::test::set_caller::<Env>(accounts.django);
+            ink::env::test::set_value_transferred::<Env>(250);
+            assert_eq!(contract.deposit_cross_chain(accounts.bob, 2000, message_id), Err(Error::NotBridge));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.add_bridge(accounts.django).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.django);
+            contract.deposit_cross_chain(accounts.bob, 2000, message_id).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 250);
+            let deposit = contract.get_cross_chain_deposit(message_id).unwrap();
+            assert_eq!((deposit.account, deposit.amount, deposit.origin_chain), (accounts.bob, 250, 2000));
+            assert_eq!(deposit.bridge, accounts.django);
+
+            // A replayed message is refused
+            assert_eq!(contract.deposit_cross_chain(accounts.bob, 2000, message_id), Err(Error::DuplicateMessage));
+            assert_eq!(contract.get_balance(accounts.bob), 250);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.remove_bridge(accounts.django).unwrap();
+            assert!(contract.get_bridges().is_empty());
+        }
+
+        #[ink::test]
+        fn balances_transfer_between_accounts() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+
+            assert_eq!(contract.transfer_balance(accounts.charlie, 150), Err(Error::InsufficientBalance));
+            contract.transfer_balance(accounts.charlie, 40).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 60);
+            assert_eq!(contract.get_balance(accounts.charlie), 40);
+        }
+
+        #[ink::test]
+        fn operators_bet_within_allowance() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+            contract.approve(accounts.charlie, 50).unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet_for(accounts.bob, 0, 1, 30).unwrap();
+            assert_eq!(contract.allowance(accounts.bob, accounts.charlie), 20);
+            assert_eq!(contract.get_balance(accounts.bob), 70);
+            assert_eq!(contract.get_bets()[0].bettor, accounts.bob);
+            assert_eq!(
+                contract.place_exacta_bet_for(accounts.bob, 0, 1, 30),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(contract.place_exacta_bet_for(accounts.django, 0, 1, 0), Err(Error::ZeroBetAmount));
+        }
+
+        #[ink::test]
+        fn relayed_signed_bets_are_verified() {
+            use schnorrkel::{ExpansionMode, MiniSecretKey};
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let keypair = MiniSecretKey::from_bytes(&[7; 32])
+                .unwrap()
+                .expand_to_keypair(ExpansionMode::Ed25519);
+            let bettor = AccountId::from(keypair.public.to_bytes());
+
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(bettor);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+
+            let bet = SignedBet { bettor, first_pick: 2, second_pick: 4, amount: 40, nonce: 0 };
+            let signature = keypair.sign_simple(b"substrate", &contract.signed_bet_message(&bet));
+            let signature = BetSignature::Sr25519(signature.to_bytes());
+
+            // Relayer submits the bet
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            let tampered = SignedBet { amount: 90, ..bet.clone() };
+            assert_eq!(contract.place_signed_bet(tampered, signature.clone()), Err(Error::InvalidSignature));
+            contract.place_signed_bet(bet.clone(), signature.clone()).unwrap();
+            assert_eq!(contract.get_balance(bettor), 60);
+            assert_eq!(contract.get_bets()[0].bettor, bettor);
+            assert_eq!(contract.get_nonce(bettor), 1);
+
+            // Replays are rejected
+            assert_eq!(contract.place_signed_bet(bet, signature), Err(Error::InvalidNonce));
+        }
+
+        #[ink::test]
+        fn exacta_odds_formats() {
+            let contract = HorseRace::new();
+            // H[0] → H[2] pays 3x
+            let odds = contract.get_exacta_odds(0, 2).unwrap();
+            assert_eq!(odds.multiplier, 3);
+            assert_eq!(odds.decimal_odds, 30000);
+            assert_eq!(odds.implied_probability, 3333);
+            assert_eq!(odds.model_probability, contract.calculate_exacta_probability(0, 2));
+            assert_eq!(contract.get_exacta_odds(1, 1), None);
+            assert_eq!(contract.get_exacta_odds_table().len(), 30);
+        }
+
+        #[ink::test]
+        fn book_overround_sums_implied_probabilities() {
+            let contract = HorseRace::new();
+            let book = contract.get_book_overround();
+            assert_eq!(book.per_horse.len(), NUM_HORSES);
+
+            let implied: i64 = contract
+                .get_exacta_odds_table()
+                .iter()
+                .map(|odds| odds.implied_probability as i64)
+                .sum();
+            assert_eq!(book.overall, implied - PRECISION as i64);
+            // The favourite's exactas are priced short of the model
+            assert!(book.per_horse[0] > 0);
+        }
+
+        #[ink::test]
+        fn expected_value_combines_probability_and_multiplier() {
+            let contract = HorseRace::new();
+            let probability = contract.calculate_exacta_probability(0, 1) as i128;
+            // H[0] → H[1] pays 2x
+            assert_eq!(
+                contract.get_expected_value(0, 1, 100),
+                Some(100 * (probability * 2 - PRECISION as i128))
+            );
+            assert_eq!(contract.get_expected_value(0, 0, 100), None);
+        }
+
+        #[ink::test]
+        fn probability_table_lists_every_exacta() {
+            let mut contract = HorseRace::new();
+            contract.set_multiplier(2, 0, 0);
+
+            let table = contract.get_exacta_probability_table();
+            assert_eq!(table.len(), NUM_HORSES * (NUM_HORSES - 1));
+            let unoffered: Vec<(u8, u8)> = table
+                .iter()
+                .filter(|entry| !entry.offered)
+                .map(|entry| (entry.first, entry.second))
+                .collect();
+            assert_eq!(unoffered, vec![(2, 0)]);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(contract.place_exacta_bet(2, 0), Err(Error::CombinationNotOffered));
+        }
+
+        #[ink::test]
+        fn pool_breakdown_groups_stakes_by_combination() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(3, 2).unwrap();
+
+            let breakdown = contract.get_pool_breakdown();
+            assert_eq!(breakdown.len(), 30);
+            let share = |first, second| {
+                breakdown.iter().find(|s| s.first == first && s.second == second).unwrap().clone()
             };
-            
-            // Initialize horses
-            contract.initialize_horses();
-            
-            // Initialize reward multipliers
-            contract.initialize_multipliers();
-            
-            contract
+            assert_eq!((share(0, 1).total_staked, share(0, 1).bet_count), (200, 2));
+            assert_eq!((share(3, 2).total_staked, share(3, 2).bet_count), (50, 1));
+            assert_eq!(share(1, 0).bet_count, 0);
         }
 
-        /// Default constructor
-        #[ink(constructor)]
-        pub fn default() -> Self {
-            Self::new()
+        #[ink::test]
+        fn horse_popularity_splits_first_and_second_picks() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(0, 2).unwrap();
+            contract.place_exacta_bet(1, 0).unwrap();
+
+            let popularity = contract.get_horse_popularity();
+            assert_eq!(popularity.len(), 6);
+            let horse = |id: usize| {
+                let entry = &popularity[id];
+                (entry.first_stake, entry.first_bets, entry.second_stake, entry.second_bets)
+            };
+            assert_eq!(horse(0), (150, 2, 50, 1));
+            assert_eq!(horse(1), (50, 1, 100, 1));
+            assert_eq!(horse(2), (0, 0, 50, 1));
+            assert_eq!(horse(5), (0, 0, 0, 0));
         }
 
-        // ========================================================================
-        // INITIALIZATION HELPERS
-        // ========================================================================
+        #[ink::test]
+        fn market_summary_reads_aggregates() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.get_market_summary().most_backed, None);
 
-        /// Initialize the 6 horses with their strengths
-        fn initialize_horses(&mut self) {
-            let names = [
-                b"Thunder Bolt".to_vec(),
-                b"Silver Arrow".to_vec(),
-                b"Golden Star".to_vec(),
-                b"Dark Knight".to_vec(),
-                b"Wild Spirit".to_vec(),
-                b"Lucky Charm".to_vec(),
-            ];
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(80);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(2, 3).unwrap();
+            contract.place_exacta_bet(2, 3).unwrap();
 
-            for i in 0..NUM_HORSES {
-                let strength = HORSE_STRENGTHS[i];
-                // S[i] = strength[i] / sum(strength) * PRECISION
-                let normalized = (strength * PRECISION) / TOTAL_STRENGTH;
-                // Bs[i] = 14 + strength[i]
-                let base_speed = 14 + strength;
+            let summary = contract.get_market_summary();
+            assert_eq!((summary.most_backed, summary.most_backed_stake), (Some((0, 1)), 180));
+            assert_eq!(summary.least_backed_stake, 0);
+            assert_ne!(summary.least_backed, Some((0, 1)));
+            assert_eq!(summary.total_pot, 340);
+            assert_eq!((summary.largest_bet, summary.largest_bettor), (160, Some(accounts.charlie)));
 
-                self.horses.push(Horse {
-                    id: i as u8,
-                    name: names[i].clone(),
-                    strength,
-                    normalized_strength: normalized,
-                    base_speed,
-                });
-            }
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.get_market_summary().largest_bettor, None);
         }
 
-        /// Initialize exacta reward multipliers based on specification
-        fn initialize_multipliers(&mut self) {
-            // Initialize 36 slots (6x6 matrix)
-            self.reward_multipliers = vec![0; 36];
+        #[ink::test]
+        fn top_up_bet_grows_position() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 2_000).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(10);
+            let bet_id = contract.place_exacta_bet(0, 1).unwrap();
 
-            // H[0] combinations
-            self.set_multiplier(0, 5, 60);
-            self.set_multiplier(0, 4, 30);
-            self.set_multiplier(0, 3, 10);
-            self.set_multiplier(0, 2, 3);
-            self.set_multiplier(0, 1, 2);
+            ink::env::test::set_value_transferred::<Env>(15);
+            contract.top_up_bet(bet_id).unwrap();
+            let record = contract.get_bet_by_id(bet_id).unwrap();
+            assert_eq!((record.bet.amount, record.bet.additions), (25, 2));
+            assert_eq!((contract.get_bets().len(), contract.get_total_pot()), (1, 25));
+            assert_eq!(contract.get_combination_stake(0, 1).0, 25);
 
-            // H[1] combinations
-            self.set_multiplier(1, 5, 175);
-            self.set_multiplier(1, 4, 125);
-            self.set_multiplier(1, 3, 20);
-            self.set_multiplier(1, 2, 5);
-            self.set_multiplier(1, 0, 3);  // Reverse of H[0]→H[1]
+            assert_eq!(contract.top_up_bet(bet_id + 1), Err(Error::BetNotFound));
+            ink::env::test::set_value_transferred::<Env>(0);
+            assert_eq!(contract.top_up_bet(bet_id), Err(Error::ZeroBetAmount));
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(15);
+            assert_eq!(contract.top_up_bet(bet_id), Err(Error::NotBetOwner));
 
-            // H[2] combinations
-            self.set_multiplier(2, 5, 100);
-            self.set_multiplier(2, 4, 80);
-            self.set_multiplier(2, 3, 8);
-            self.set_multiplier(2, 1, 6);
-            self.set_multiplier(2, 0, 4);
+            // Only while betting is open
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            assert_eq!(contract.top_up_bet(bet_id), Err(Error::BettingClosed));
+        }
 
-            // H[3] combinations
-            self.set_multiplier(3, 5, 500);
-            self.set_multiplier(3, 4, 250);
-            self.set_multiplier(3, 2, 12);
-            self.set_multiplier(3, 1, 15);
-            self.set_multiplier(3, 0, 8);
+        #[ink::test]
+        fn repeat_bets_merge_into_one_position() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(2, 3).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(0, 1).unwrap();
+
+            assert_eq!(contract.get_bets().len(), 3);
+            let positions = contract.get_positions(accounts.bob, 0, 1);
+            assert_eq!(positions.len(), 1);
+            assert_eq!((positions[0].amount, positions[0].additions), (150, 2));
+            assert_eq!(contract.get_total_pot(), 250);
+
+            // The archive holds the position, not each addition
+            assert_eq!(contract.get_bet_count_by_account(accounts.bob), 2);
+            let history = contract.get_bets_by_account(accounts.bob, 0, 10);
+            assert_eq!((history[0].bet.amount, history[0].bet.additions), (150, 2));
+        }
+
+        #[ink::test]
+        fn bettor_index_finds_winning_bets() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            // Bob covers every exacta, Charlie takes one
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            for first in 0..NUM_HORSES as u8 {
+                for second in 0..NUM_HORSES as u8 {
+                    if first != second {
+                        contract.place_exacta_bet(first, second).unwrap();
+                    }
+                }
+            }
+            assert_eq!(contract.get_race_bet_indices(1, accounts.charlie), vec![0]);
+            assert_eq!(contract.get_race_bet_indices(1, accounts.bob), (1..31).collect::<Vec<u32>>());
+            assert!(contract.get_winning_bets(accounts.bob).is_empty());
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            let result = contract.simulate_complete_race(3).unwrap();
+            let winning = contract.get_winning_bets(accounts.bob);
+            assert_eq!(winning.len(), 1);
+            let bet = &contract.get_bets()[winning[0].0 as usize];
+            assert_eq!((bet.first_pick, bet.second_pick), result.winning_exacta);
+            let multiplier = contract.get_reward_multiplier(bet.first_pick, bet.second_pick) as u128;
+            assert_eq!(winning[0].1, 10 * multiplier);
+        }
+
+        #[ink::test]
+        fn bets_are_rate_limited_per_block() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_block_bet_limit(2).unwrap();
+            assert_eq!(contract.get_block_bet_limit(), 2);
+
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.set_block_bet_limit(0), Err(Error::NotOwner));
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(0, 2).unwrap();
+            assert_eq!(contract.place_exacta_bet(0, 3), Err(Error::RateLimited));
+
+            // Other accounts and later blocks are not affected
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(0, 3).unwrap();
+            ink::env::test::advance_block::<Env>();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(0, 3).unwrap();
+        }
 
-            // H[4] combinations
-            self.set_multiplier(4, 5, 1000);
-            self.set_multiplier(4, 3, 300);
-            self.set_multiplier(4, 2, 100);
-            self.set_multiplier(4, 1, 150);
-            self.set_multiplier(4, 0, 40);
+        #[ink::test]
+        fn bet_caps_limit_new_positions() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.get_bet_limits(), (DEFAULT_MAX_BETS_PER_RACE, DEFAULT_MAX_BETS_PER_ACCOUNT));
+            contract.set_bet_limits(3, 2).unwrap();
 
-            // H[5] combinations (weakest horse winning)
-            self.set_multiplier(5, 4, 1500);
-            self.set_multiplier(5, 3, 600);
-            self.set_multiplier(5, 2, 200);
-            self.set_multiplier(5, 1, 250);
-            self.set_multiplier(5, 0, 80);
+            // A third exacta is over Bob's cap, topping up an open one is not
+            ink::env::test::set_value_transferred::<Env>(10);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(0, 2).unwrap();
+            assert_eq!(contract.place_exacta_bet(0, 3), Err(Error::AccountBetLimitReached));
+            contract.place_exacta_bet(0, 1).unwrap();
+
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_exacta_bet(1, 0).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.django);
+            assert_eq!(contract.place_exacta_bet(1, 2), Err(Error::RaceBetLimitReached));
+            assert_eq!(contract.get_bets().len(), 3);
+            assert_eq!(contract.set_bet_limits(0, 0), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_bet_limits(0, 0).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.django);
+            contract.place_exacta_bet(1, 2).unwrap();
         }
 
-        fn set_multiplier(&mut self, first: u8, second: u8, multiplier: u64) {
-            let index = (first as usize) * 6 + (second as usize);
-            if index < 36 {
-                self.reward_multipliers[index] = multiplier;
-            }
+        #[ink::test]
+        fn stakes_aggregate_per_combination() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_value_transferred::<Env>(30);
+            contract.place_exacta_bet(2, 3).unwrap();
+
+            let multiplier = contract.get_reward_multiplier(0, 1) as u128;
+            assert_eq!(contract.get_combination_stake(0, 1), (150, 150 * multiplier));
+            assert_eq!(contract.get_combination_stake(1, 0), (0, 0));
+
+            // Settlement books every paid stake, losing ones from the totals
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(7).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_race_pnl(1).unwrap().staked, 180);
+
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.get_combination_stake(0, 1), (0, 0));
         }
 
-        fn get_multiplier(&self, first: u8, second: u8) -> u64 {
-            let index = (first as usize) * 6 + (second as usize);
-            if index < 36 {
-                self.reward_multipliers[index]
-            } else {
-                0
-            }
+        #[ink::test]
+        fn preview_payouts_reads_combination_maps() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(0, 1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(0, 1).unwrap();
+
+            // Repeat bets top up one position, so two winners share 200
+            let multiplier = contract.get_multiplier(0, 1) as u128;
+            assert_eq!(contract.preview_payouts(0, 1), (200 * multiplier, 2));
+            assert_eq!(contract.preview_payouts(1, 0), (0, 0));
+
+            // An unwon pot carried over goes to the winners as well
+            contract.carryover = 40;
+            assert_eq!(contract.preview_payouts(0, 1), (200 * multiplier + 40, 2));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.reset_for_new_race().unwrap();
+            assert_eq!(contract.preview_payouts(0, 1), (0, 0));
         }
 
-        // ========================================================================
-        // BETTING FUNCTIONS
-        // ========================================================================
+        #[ink::test]
+        fn horse_records_count_placings() {
+            let mut contract = HorseRace::new();
+            let mut results = Vec::new();
+            for seed in 1..=5 {
+                results.push(contract.simulate_complete_race(seed).unwrap());
+                contract.reset_for_new_race().unwrap();
+            }
 
-        /// Place an exacta bet (predict 1st and 2nd place in order)
-        #[ink(message, payable)]
-        pub fn place_exacta_bet(&mut self, first_pick: u8, second_pick: u8) -> Result<()> {
-            // Validate race status
-            if self.status != RaceStatus::Betting {
-                return Err(Error::BettingClosed);
+            let mut total_wins = 0;
+            for horse in 0..NUM_HORSES as u8 {
+                let record = contract.get_horse_record(horse).unwrap();
+                assert_eq!(record.starts, 5);
+                let wins = results.iter().filter(|r| r.rankings[0] == horse).count() as u32;
+                let thirds = results.iter().filter(|r| r.rankings[2] == horse).count() as u32;
+                let dnfs = results.iter().filter(|r| r.did_not_finish.contains(&horse)).count() as u32;
+                assert_eq!((record.wins, record.thirds, record.did_not_finish), (wins, thirds, dnfs));
+                total_wins += record.wins;
             }
+            assert_eq!(total_wins, 5);
+            assert_eq!(contract.get_horse_record(6), None);
+        }
 
-            // Validate picks
-            if first_pick >= NUM_HORSES as u8 || second_pick >= NUM_HORSES as u8 {
-                return Err(Error::InvalidHorseId);
+        #[ink::test]
+        fn stored_results_verify_against_replay() {
+            let mut contract = HorseRace::new();
+            for seed in [11, 22, 33] {
+                contract.simulate_complete_race(seed).unwrap();
+                contract.reset_for_new_race().unwrap();
             }
-            if first_pick == second_pick {
-                return Err(Error::SameHorsePicked);
+            for race_id in 1..=3 {
+                let stored = contract.find_result(race_id).unwrap().rankings.clone();
+                assert_eq!(contract.verify_result(race_id), Some((true, stored)));
             }
 
-            let caller = self.env().caller();
-            let amount = self.env().transferred_value();
-
-            if amount == 0 {
-                return Err(Error::ZeroBetAmount);
-            }
+            // Tampered rankings no longer match the replay
+            let original = contract.race_results[1].rankings.clone();
+            contract.race_results[1].rankings.swap(0, 1);
+            assert_eq!(contract.verify_result(2), Some((false, original)));
+            assert_eq!(contract.verify_result(9), None);
+        }
 
-            // Create bet
-            let bet = ExactaBet {
-                bettor: caller,
-                amount,
-                first_pick,
-                second_pick,
-                timestamp: self.env().block_timestamp(),
-            };
+        #[ink::test]
+        fn disputes_hold_settlement() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_challenge_period(1000).unwrap();
+            contract.simulate_complete_race(5).unwrap();
 
-            self.bets.push(bet);
-            self.total_pot += amount;
+            assert_eq!(contract.distribute_payouts(), Err(Error::ChallengePeriodActive));
+            // An honest result survives a challenge
+            assert_eq!(contract.challenge_result(1), Ok(false));
 
-            // Emit event
-            self.env().emit_event(BetPlaced {
-                bettor: caller,
-                first_pick,
-                second_pick,
-                amount,
-            });
+            // A tampered one freezes settlement
+            contract.race_results[0].rankings.swap(0, 1);
+            assert_eq!(contract.challenge_result(1), Ok(true));
+            assert_eq!(contract.get_disputed_races(), vec![1]);
+            ink::env::test::set_block_timestamp::<Env>(1000);
+            assert_eq!(contract.distribute_payouts(), Err(Error::SettlementFrozen));
 
-            Ok(())
+            contract.resolve_dispute(1).unwrap();
+            assert!(contract.distribute_payouts().is_ok());
         }
 
-        /// Get all bets for current race
-        #[ink(message)]
-        pub fn get_bets(&self) -> Vec<ExactaBet> {
-            self.bets.clone()
+        #[ink::test]
+        fn settlement_needs_operator_approvals() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.add_settlement_operator(accounts.bob).unwrap();
+            assert_eq!(contract.set_approval_threshold(3), Err(Error::InvalidThreshold));
+            contract.set_approval_threshold(2).unwrap();
+            contract.simulate_complete_race(3).unwrap();
+
+            assert_eq!(contract.distribute_payouts(), Err(Error::ApprovalsPending));
+            assert_eq!(contract.approve_payouts(), Err(Error::NoProposal));
+            contract.propose_payouts().unwrap();
+            assert_eq!(contract.approve_payouts(), Err(Error::AlreadyApproved));
+
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.approve_payouts(), Err(Error::NotOperator));
+
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert!(matches!(contract.approve_payouts(), Ok(Some(_))));
+            assert_eq!(contract.get_status(), RaceStatus::Closed);
+            assert_eq!(contract.get_payout_approvals(), None);
         }
 
-        /// Get total pot for current race
-        #[ink(message)]
-        pub fn get_total_pot(&self) -> Balance {
-            self.total_pot
+        #[ink::test]
+        fn governance_takes_over_config() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_governance(Some(accounts.eve)).unwrap();
+
+            // The owner can no longer change the book
+            assert_eq!(contract.update_multiplier(0, 1, 5), Err(Error::NotGovernance));
+            assert_eq!(contract.set_governance(None), Err(Error::NotGovernance));
+
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            contract.update_multiplier(0, 1, 5).unwrap();
+            assert_eq!(contract.get_exacta_odds(0, 1).unwrap().multiplier, 5);
+            contract.set_house_edge(800, 2000).unwrap();
+            assert_eq!(contract.get_house_edge(), (800, 2000));
+            assert_eq!(contract.set_house_edge(PRECISION, 0), Err(Error::InvalidConfig));
+
+            let before = contract.calculate_win_probability(5);
+            contract.set_horse_strength(5, 6).unwrap();
+            let horse = contract.get_horse(5).unwrap();
+            assert_eq!((horse.strength, horse.base_speed), (6, 20));
+            assert_eq!(horse.normalized_strength, (6 * PRECISION) / 26);
+            assert!(contract.calculate_win_probability(5) > before);
         }
 
-        // ========================================================================
-        // RACE SIMULATION ENGINE
-        // ========================================================================
+        #[ink::test]
+        fn config_applies_as_one_set() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
 
-        /// Start the race with a given seed for deterministic simulation
-        #[ink(message)]
-        pub fn start_race(&mut self, seed: u64) -> Result<()> {
-            // Only owner can start race
-            if self.env().caller() != self.owner {
-                return Err(Error::NotOwner);
-            }
+            let mut config = contract.get_config();
+            assert_eq!(config.betting_duration, DEFAULT_BETTING_DURATION);
+            config.betting_duration = 1_000;
+            config.withdrawal_fee = 50;
+            config.match_bet_edge = 700;
 
-            if self.status != RaceStatus::Betting {
-                return Err(Error::RaceNotInBettingPhase);
-            }
+            // One invalid field leaves everything untouched
+            let mut invalid = config.clone();
+            invalid.claim_period = 0;
+            assert_eq!(contract.set_config(invalid), Err(Error::InvalidDuration));
+            assert_eq!(contract.get_durations().0, DEFAULT_BETTING_DURATION);
 
-            self.current_seed = seed;
-            self.race_id += 1;
-            self.status = RaceStatus::Racing;
-            self.race_start_time = self.env().block_timestamp();
+            contract.set_config(config.clone()).unwrap();
+            assert_eq!(contract.get_config(), config);
+            assert_eq!(contract.get_withdrawal_limits().0, 50);
 
-            self.env().emit_event(RaceStarted {
-                race_id: self.race_id,
-                seed,
-                total_bets: self.bets.len() as u32,
-            });
+            // Governed fields stay with governance
+            contract.set_governance(Some(accounts.eve)).unwrap();
+            config.racing_duration = 5_000;
+            contract.set_config(config.clone()).unwrap();
+            config.match_bet_edge = 900;
+            assert_eq!(contract.set_config(config.clone()), Err(Error::NotGovernance));
 
-            Ok(())
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.set_config(config), Err(Error::NotOwner));
         }
 
-        /// Run the race simulation and determine winners
-        /// Uses probability-weighted selection based on the exacta formula:
-        /// P(i wins 1st) = S[i] / sum(S)
-        /// P(j wins 2nd | i won 1st) = S[j] / (sum(S) - S[i])
-        #[ink(message)]
-        pub fn run_race_simulation(&mut self) -> Result<RaceResult> {
-            if self.status != RaceStatus::Racing {
-                return Err(Error::RaceNotInProgress);
-            }
+        #[ink::test]
+        fn factory_constructor_applies_config() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut config = HorseRace::new().get_config();
+            config.betting_duration = 1_000;
+            config.room_fee = 0;
 
-            let mut rng_state = self.current_seed;
-            
-            // Track which horses are still available
-            let mut available: Vec<bool> = vec![true; NUM_HORSES];
-            let mut rankings: Vec<u8> = Vec::new();
-            let mut finish_times: Vec<u64> = Vec::new();
-            
-            // Select each position using weighted probability
-            for position in 0..NUM_HORSES {
-                // Calculate total remaining strength
-                let mut remaining_strength: u64 = 0;
-                for i in 0..NUM_HORSES {
-                    if available[i] {
-                        remaining_strength += HORSE_STRENGTHS[i];
-                    }
-                }
-                
-                if remaining_strength == 0 {
-                    break;
-                }
-                
-                // Generate random number for selection
-                rng_state = self.next_random(rng_state);
-                let random_val = rng_state % remaining_strength;
-                
-                // Select horse based on weighted probability
-                let mut cumulative: u64 = 0;
-                let mut selected_horse: u8 = 0;
-                
-                for i in 0..NUM_HORSES {
-                    if available[i] {
-                        cumulative += HORSE_STRENGTHS[i];
-                        if random_val < cumulative {
-                            selected_horse = i as u8;
-                            break;
-                        }
-                    }
-                }
-                
-                // Mark horse as finished
-                available[selected_horse as usize] = false;
-                rankings.push(selected_horse);
-                
-                // Simulate finish time (roughly based on position)
-                // Base time + position offset + small random variation
-                rng_state = self.next_random(rng_state);
-                let time_variation = rng_state % 5;
-                let finish_time = 50 + (position as u64 * 2) + time_variation;
-                finish_times.push(finish_time);
-            }
+            // The deploying factory hands the instance to its operator
+            let contract = HorseRace::with_config(accounts.bob, config.clone()).unwrap();
+            assert_eq!(contract.get_owner(), accounts.bob);
+            assert_eq!(contract.get_config(), config);
 
-            let result = RaceResult {
-                race_id: self.race_id,
-                rankings: rankings.clone(),
-                finish_times,
-                winning_exacta: (rankings[0], rankings[1]),
-                total_pot: self.total_pot,
-                seed_used: self.current_seed,
-            };
+            // A new instance has no settlement operators to co-sign
+            config.approval_threshold = 2;
+            assert_eq!(HorseRace::with_config(accounts.bob, config).err(), Some(Error::InvalidThreshold));
+        }
 
-            self.latest_result = result.clone();
-            self.race_results.push(result.clone());
-            self.status = RaceStatus::Finished;
+        #[ink::test]
+        fn bankroll_shares_track_house_results() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(1000);
+            assert_eq!(contract.stake(), Ok(1000));
+            assert_eq!(contract.get_share_value(), PRECISION as Balance);
 
-            self.env().emit_event(RaceFinished {
-                race_id: self.race_id,
-                first_place: rankings[0],
-                second_place: rankings[1],
-                third_place: rankings[2],
-            });
+            // House wins a losing stake, then pays a winner 2x
+            contract.book_house(100, 0).unwrap();
+            assert_eq!(contract.get_share_value(), 11000);
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(550);
+            assert_eq!(contract.stake(), Ok(500));
+            contract.book_house(100, 200).unwrap();
+            assert_eq!(contract.get_bankroll(), (1550, 1500));
 
-            Ok(result)
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.unstake(2000), Err(Error::InsufficientShares));
+            assert_eq!(contract.unstake(1000), Ok(1033));
+            assert_eq!(contract.get_balance(accounts.bob), 1033);
+            assert_eq!(contract.get_bankroll_position(accounts.charlie), (500, 517));
         }
 
-        /// Simple LCG random number generator
-        fn next_random(&self, state: u64) -> u64 {
-            // LCG parameters (same as glibc)
-            const A: u64 = 1103515245;
-            const C: u64 = 12345;
-            const M: u64 = 2147483648; // 2^31
-            
-            (state.wrapping_mul(A).wrapping_add(C)) % M
+        #[ink::test]
+        fn race_pnl_tracks_house_result() {
+            let mut contract = HorseRace::new();
+            contract.race_id = 1;
+            contract.book_house(100, 0).unwrap();
+            contract.book_house(100, 500).unwrap();
+            let race = contract.get_race_pnl(1).unwrap();
+            assert_eq!((race.staked, race.paid_out, race.net), (200, 500, -300));
+
+            // Next race is tracked separately, lifetime sums both
+            contract.race_id = 2;
+            contract.book_house(300, 0).unwrap();
+            assert_eq!(contract.get_race_pnl(2).unwrap().net, 300);
+            let lifetime = contract.get_lifetime_pnl();
+            assert_eq!((lifetime.staked, lifetime.paid_out, lifetime.net), (500, 500, 0));
+            assert_eq!(contract.get_race_pnl(3), None);
         }
 
-        // ========================================================================
-        // PAYOUT ENGINE
-        // ========================================================================
+        #[ink::test]
+        fn ledger_records_balance_changes() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+            contract.place_exacta_bet_for(accounts.bob, 1, 2, 30).unwrap();
+            contract.transfer_balance(accounts.charlie, 20).unwrap();
 
-        /// Calculate and distribute payouts
-        #[ink(message)]
-        pub fn distribute_payouts(&mut self) -> Result<Vec<Payout>> {
-            if self.status != RaceStatus::Finished {
-                return Err(Error::RaceNotFinished);
-            }
+            let ledger = contract.get_ledger(accounts.bob, 0, 10);
+            let summary: Vec<_> = ledger.iter().map(|e| (e.reason, e.delta, e.balance_after)).collect();
+            assert_eq!(summary, vec![
+                (LedgerReason::Deposit, 100, 100),
+                (LedgerReason::Bet, -30, 70),
+                (LedgerReason::Transfer, -20, 50),
+            ]);
+            assert_eq!(contract.get_ledger_count(accounts.bob), 3);
+            assert_eq!(contract.get_ledger(accounts.bob, 1, 1), ledger[1..2].to_vec());
+            assert!(contract.get_ledger(accounts.bob, 3, 10).is_empty());
+            assert_eq!(contract.get_ledger(accounts.charlie, 0, 10)[0].delta, 20);
+        }
 
-            let winning_exacta = self.latest_result.winning_exacta;
-            let multiplier = self.get_multiplier(winning_exacta.0, winning_exacta.1);
+        #[ink::test]
+        fn psp22_transfers_over_balances() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.deposit().unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+            assert_eq!(contract.total_supply(), 100);
 
-            let mut payouts_list: Vec<Payout> = Vec::new();
+            contract.transfer(accounts.charlie, 30, vec![]).unwrap();
+            assert_eq!(contract.transfer(accounts.charlie, 500, vec![]), Err(PSP22Error::InsufficientBalance));
+            contract.approve(accounts.django, 40).unwrap();
+            contract.increase_allowance(accounts.django, 10).unwrap();
 
-            for bet in &self.bets {
-                if bet.first_pick == winning_exacta.0 && bet.second_pick == winning_exacta.1 {
-                    // Winner!
-                    let payout_amount = bet.amount * multiplier as u128;
-                    
-                    let payout = Payout {
-                        bettor: bet.bettor,
-                        bet_amount: bet.amount,
-                        multiplier,
-                        payout_amount,
-                        exacta: winning_exacta,
-                    };
-                    
-                    payouts_list.push(payout.clone());
-                    self.payouts.push(payout);
+            ink::env::test::set_caller::<Env>(accounts.django);
+            assert_eq!(
+                contract.transfer_from(accounts.bob, accounts.eve, 60, vec![]),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            contract.transfer_from(accounts.bob, accounts.eve, 50, vec![]).unwrap();
+            assert_eq!(contract.allowance(accounts.bob, accounts.django), 0);
 
-                    self.env().emit_event(PayoutDistributed {
-                        bettor: bet.bettor,
-                        amount: payout_amount,
-                        multiplier,
-                    });
-                }
+            assert_eq!(contract.balance_of(accounts.bob), 20);
+            assert_eq!(contract.balance_of(accounts.charlie), 30);
+            assert_eq!(contract.balance_of(accounts.eve), 50);
+            assert_eq!(contract.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn listeners_are_capped_and_owner_only() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            for id in 0..MAX_LISTENERS as u8 {
+                contract.add_listener(AccountId::from([id; 32])).unwrap();
             }
+            contract.add_listener(AccountId::from([0; 32])).unwrap();
+            assert_eq!(contract.add_listener(AccountId::from([9; 32])), Err(Error::TooManyListeners));
+
+            contract.remove_listener(AccountId::from([0; 32])).unwrap();
+            assert_eq!(contract.get_listeners().len(), MAX_LISTENERS - 1);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.add_listener(accounts.bob), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn receipt_contract_is_owner_set() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(1, 2).unwrap();
+            assert_eq!(contract.get_bets()[0].receipt, None);
+            assert_eq!(contract.receipt_holder(&contract.get_bets()[0]), accounts.bob);
+            assert_eq!(contract.set_receipt_contract(Some(accounts.django)), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_receipt_contract(Some(accounts.django)).unwrap();
+            assert_eq!(contract.get_receipt_contract(), Some(accounts.django));
+        }
+
+        #[ink::test]
+        fn horse_owners_share_race_profit() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.assign_horse(3, accounts.bob).unwrap();
+            contract.assign_horse(5, accounts.charlie).unwrap();
+            assert_eq!(contract.assign_horse(3, accounts.eve), Err(Error::HorseAlreadyOwned));
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.transfer_horse(5, accounts.django).unwrap();
+            assert_eq!(contract.transfer_horse(5, accounts.eve), Err(Error::NotHorseOwner));
 
-            self.status = RaceStatus::Closed;
-            Ok(payouts_list)
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.set_owner_shares(6000, 5000), Err(Error::InvalidConfig));
+            contract.set_owner_shares(1000, 500).unwrap();
+
+            // House made 1000 on a race won by horse 3, horse 5 second
+            contract.race_id = 1;
+            contract.book_house(1000, 0).unwrap();
+            contract.latest_result.rankings = vec![3, 5, 0, 1, 2, 4];
+            contract.pay_horse_owners().unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 100);
+            assert_eq!(contract.get_balance(accounts.django), 50);
+            assert_eq!(contract.get_race_pnl(1).unwrap().net, 850);
         }
 
-        /// Get payouts for current race
-        #[ink(message)]
-        pub fn get_payouts(&self) -> Vec<Payout> {
-            self.payouts.clone()
+        #[ink::test]
+        fn horse_metadata_set_by_owners() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let silks = vec![0xC8, 0x10, 0x2E, 0xFF, 0xFF, 0xFF];
+            contract.set_horse_silks(0, silks.clone()).unwrap();
+            contract.set_horse_uri(0, b"ipfs://horse-0".to_vec()).unwrap();
+            let horse = contract.get_horse(0).unwrap();
+            assert_eq!(horse.silks, silks);
+            assert_eq!(horse.uri, b"ipfs://horse-0".to_vec());
+
+            assert_eq!(contract.set_horse_silks(0, vec![1, 2]), Err(Error::InvalidHorseMetadata));
+            assert_eq!(contract.set_horse_silks(0, vec![0; 15]), Err(Error::InvalidHorseMetadata));
+            assert_eq!(contract.set_horse_uri(0, vec![b'a'; 257]), Err(Error::InvalidHorseMetadata));
+            assert_eq!(contract.set_horse_uri(6, Vec::new()), Err(Error::InvalidHorseId));
+
+            // A horse's owner can dress their own horse only
+            contract.assign_horse(2, accounts.bob).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.set_horse_silks(2, vec![0, 0, 0]).unwrap();
+            assert_eq!(contract.get_horse(2).unwrap().silks, vec![0, 0, 0]);
+            assert_eq!(contract.set_horse_uri(0, Vec::new()), Err(Error::NotHorseOwner));
         }
 
-        // ========================================================================
-        // EXACTA PROBABILITY CALCULATOR
-        // ========================================================================
+        #[ink::test]
+        fn trophies_are_off_by_default() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.get_trophy_minter(), None);
+            // Without a minter, settlement makes no cross-contract calls
+            contract.simulate_complete_race(7).unwrap();
+            contract.distribute_payouts().unwrap();
 
-        /// Calculate exacta probability P(i → j)
-        /// Formula: P(i → j) = (S[i] / sum(S)) * (S[j] / (sum(S) - S[i]))
-        #[ink(message)]
-        pub fn calculate_exacta_probability(&self, first: u8, second: u8) -> u64 {
-            if first >= NUM_HORSES as u8 || second >= NUM_HORSES as u8 || first == second {
-                return 0;
-            }
+            contract.set_trophy_minter(Some(accounts.django)).unwrap();
+            assert_eq!(contract.get_trophy_minter(), Some(accounts.django));
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.set_trophy_minter(None), Err(Error::NotOwner));
+        }
 
-            let s_first = HORSE_STRENGTHS[first as usize];
-            let s_second = HORSE_STRENGTHS[second as usize];
+        #[ink::test]
+        fn syndicate_winnings_split_pro_rata() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let (first, second) = HorseRace::new().simulate_complete_race(11).unwrap().winning_exacta;
+            for (account, amount) in [(accounts.bob, 300), (accounts.charlie, 100), (accounts.eve, 50)] {
+                ink::env::test::set_caller::<Env>(account);
+                ink::env::test::set_value_transferred::<Env>(amount);
+                contract.deposit().unwrap();
+            }
+            ink::env::test::set_value_transferred::<Env>(0);
 
-            // P(first wins) = S[first] / TOTAL_STRENGTH
-            let p_first = (s_first * PRECISION) / TOTAL_STRENGTH;
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let id = contract.create_syndicate(first, second).unwrap();
+            contract.join_syndicate(id, 300).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.join_syndicate(id, 100).unwrap();
+            assert_eq!(contract.place_syndicate_bet(id), Err(Error::NotSyndicateCreator));
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            contract.join_syndicate(id, 50).unwrap();
+            assert_eq!(contract.leave_syndicate(id), Ok(50));
+            assert_eq!(contract.get_balance(accounts.eve), 50);
 
-            // P(second | first won) = S[second] / (TOTAL_STRENGTH - S[first])
-            let remaining = TOTAL_STRENGTH - s_first;
-            let p_second_given_first = (s_second * PRECISION) / remaining;
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.place_syndicate_bet(id).unwrap();
+            assert_eq!(contract.join_syndicate(id, 10), Err(Error::SyndicateClosed));
+            assert_eq!(contract.get_bets()[0].amount, 400);
 
-            // P(exacta) = P(first) * P(second|first)
-            (p_first * p_second_given_first) / PRECISION
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(11).unwrap();
+            contract.distribute_payouts().unwrap();
+            let payout = 400 * contract.get_multiplier(first, second) as Balance;
+            assert_eq!(contract.get_balance(accounts.bob), payout * 3 / 4);
+            assert_eq!(contract.get_balance(accounts.charlie), payout / 4);
         }
 
-        /// Get all exacta probabilities and multipliers
-        #[ink(message)]
-        pub fn get_exacta_probability_table(&self) -> Vec<ExactaProbability> {
-            let mut table: Vec<ExactaProbability> = Vec::new();
+        #[ink::test]
+        fn private_rooms_run_on_committed_seed() {
+            use ink::env::hash::{Blake2x256, CryptoHash};
 
-            for first in 0..NUM_HORSES as u8 {
-                for second in 0..NUM_HORSES as u8 {
-                    if first != second {
-                        let prob = self.calculate_exacta_probability(first, second);
-                        let mult = self.get_multiplier(first, second);
-                        
-                        if mult > 0 {
-                            table.push(ExactaProbability {
-                                first,
-                                second,
-                                probability: prob,
-                                multiplier: mult,
-                            });
-                        }
-                    }
-                }
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            contract.set_room_fee(100).unwrap();
+            let expected = contract.simulate(7, &contract.track_weights(None), None);
+            let (first, second) = expected.winning_exacta;
+            let mut seed_hash = [0u8; 32];
+            Blake2x256::hash(&7u64.to_le_bytes(), &mut seed_hash);
+            for account in [accounts.bob, accounts.charlie, accounts.eve] {
+                ink::env::test::set_caller::<Env>(account);
+                ink::env::test::set_value_transferred::<Env>(1000);
+                contract.deposit().unwrap();
             }
+            ink::env::test::set_value_transferred::<Env>(0);
 
-            table
-        }
+            // Bob pays the fee and invites Charlie
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let room_id = contract.create_room(Hash::from(seed_hash), 1_000, vec![accounts.charlie]).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 900);
+            assert_eq!(contract.place_room_bet(room_id, first, second, 10), Err(Error::NotRoomMember));
 
-        // ========================================================================
-        // GETTERS
-        // ========================================================================
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            assert_eq!(contract.place_room_bet(room_id, first, second, 10), Err(Error::NotRoomMember));
+            assert_eq!(contract.add_room_members(room_id, vec![accounts.eve]), Err(Error::NotRoomCreator));
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            contract.add_room_members(room_id, vec![accounts.eve]).unwrap();
 
-        /// Get all horses
-        #[ink(message)]
-        pub fn get_horses(&self) -> Vec<Horse> {
-            self.horses.clone()
-        }
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.place_room_bet(room_id, first, second, 10).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            contract.place_room_bet(room_id, second, first, 20).unwrap();
+            assert_eq!(contract.get_room(room_id).unwrap().total_pot, 30);
 
-        /// Get horse by ID
-        #[ink(message)]
-        pub fn get_horse(&self, id: u8) -> Option<Horse> {
-            self.horses.get(id as usize).cloned()
-        }
+            // The creator reveals the committed seed after the window
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.run_room(room_id, 7), Err(Error::BettingStillOpen));
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            assert_eq!(contract.run_room(room_id, 8), Err(Error::SeedMismatch));
+            let result = contract.run_room(room_id, 7).unwrap();
+            assert_eq!(result.rankings, expected.rankings);
+            let multiplier = contract.get_multiplier(first, second) as Balance;
+            assert_eq!(contract.get_balance(accounts.charlie), 990 + 10 * multiplier);
+            assert_eq!(contract.get_balance(accounts.eve), 980);
+            assert_eq!(contract.run_room(room_id, 7), Err(Error::RoomClosed));
+            assert_eq!(contract.get_room(room_id).unwrap().status, RaceStatus::Closed);
 
-        /// Get current race status
-        #[ink(message)]
-        pub fn get_status(&self) -> RaceStatus {
-            self.status.clone()
+            // An unrevealed room is refunded once the reveal period lapses
+            let room_id = contract.create_room(Hash::from(seed_hash), 1_000, vec![accounts.eve]).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            contract.place_room_bet(room_id, first, second, 30).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.cancel_room(room_id), Err(Error::GracePeriodActive));
+            ink::env::test::set_block_timestamp::<Env>(2_002 + ROOM_REVEAL_PERIOD);
+            assert_eq!(contract.cancel_room(room_id), Ok(30));
+            assert_eq!(contract.get_balance(accounts.eve), 980);
         }
 
-        /// Get current race ID
-        #[ink(message)]
-        pub fn get_race_id(&self) -> u64 {
-            self.race_id
-        }
+        #[ink::test]
+        fn exchange_matches_back_and_lay() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let result = HorseRace::new().simulate_complete_race(11).unwrap();
+            let (first, second) = result.winning_exacta;
+            let loser = (second, first);
+            for account in [accounts.bob, accounts.charlie, accounts.eve] {
+                ink::env::test::set_caller::<Env>(account);
+                ink::env::test::set_value_transferred::<Env>(1000);
+                contract.deposit().unwrap();
+            }
+            ink::env::test::set_value_transferred::<Env>(0);
 
-        /// Get latest race result
-        #[ink(message)]
-        pub fn get_latest_result(&self) -> RaceResult {
-            self.latest_result.clone()
-        }
+            // Bob lays both exactas at 4.0 for up to 100 each (300 liability each)
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let winning_offer = contract.offer_lay(first, second, 4 * PRECISION, 100).unwrap();
+            let losing_offer = contract.offer_lay(loser.0, loser.1, 4 * PRECISION, 100).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 400);
+            assert_eq!(contract.back_offer(winning_offer, 10), Err(Error::OfferUnavailable));
 
-        /// Get race results history
-        #[ink(message)]
-        pub fn get_race_history(&self) -> Vec<RaceResult> {
-            self.race_results.clone()
-        }
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.back_offer(winning_offer, 50).unwrap();
+            assert_eq!(contract.back_offer(winning_offer, 60), Err(Error::OfferUnavailable));
+            ink::env::test::set_caller::<Env>(accounts.eve);
+            contract.back_offer(losing_offer, 100).unwrap();
+            assert_eq!(contract.cancel_offer(losing_offer), Err(Error::NotLayer));
 
-        /// Get winners from latest race
-        #[ink(message)]
-        pub fn get_winners(&self) -> (u8, u8) {
-            self.latest_result.winning_exacta
-        }
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(11).unwrap();
+            contract.distribute_payouts().unwrap();
 
-        /// Get reward multiplier for an exacta combination
-        #[ink(message)]
-        pub fn get_reward_multiplier(&self, first: u8, second: u8) -> u64 {
-            self.get_multiplier(first, second)
+            // Charlie wins 150 net less 5%, the half unit going to dust;
+            // Bob wins Eve's 100 less 5% and gets back the unmatched 150 escrow
+            assert_eq!(contract.get_balance(accounts.charlie), 950 + 200 - 8);
+            assert_eq!(contract.get_balance(accounts.eve), 900);
+            assert_eq!(contract.get_balance(accounts.bob), 400 + 150 + 400 - 5);
+            assert_eq!(contract.get_race_pnl(1).unwrap().rake, 12);
+            assert_eq!(contract.get_dust(), 1);
         }
 
-        /// Get normalized strength for a horse
-        #[ink(message)]
-        pub fn get_normalized_strength(&self, horse_id: u8) -> u64 {
-            if horse_id < NUM_HORSES as u8 {
-                self.horses[horse_id as usize].normalized_strength
-            } else {
-                0
+        #[ink::test]
+        fn commission_free_race_waives_rake() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            let result = HorseRace::new().simulate_complete_race(11).unwrap();
+            let (first, second) = result.winning_exacta;
+            let edged_odds = contract.get_match_odds(0, 1);
+            contract.set_race_info(RaceInfo { commission_free: true, ..Default::default() }).unwrap();
+            assert!(contract.get_match_odds(0, 1) > edged_odds);
+            assert!(contract.get_race_info(1).unwrap().commission_free);
+
+            for account in [accounts.bob, accounts.charlie] {
+                ink::env::test::set_caller::<Env>(account);
+                ink::env::test::set_value_transferred::<Env>(1000);
+                contract.deposit().unwrap();
             }
-        }
+            ink::env::test::set_value_transferred::<Env>(0);
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            let offer = contract.offer_lay(first, second, 4 * PRECISION, 100).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            contract.back_offer(offer, 50).unwrap();
 
-        /// Get contract owner
-        #[ink(message)]
-        pub fn get_owner(&self) -> AccountId {
-            self.owner
+            // Charlie keeps the whole 150 net win
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(11).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_balance(accounts.charlie), 950 + 200);
+            let report = contract.get_settlement_report(1).unwrap();
+            assert_eq!((report.rake, report.commission_free), (0, true));
         }
 
-        // ========================================================================
-        // ADMIN FUNCTIONS
-        // ========================================================================
+        #[ink::test]
+        fn open_bets_can_be_cashed_out() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(1000);
+            contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(4, 5).unwrap();
 
-        /// Reset for new race
-        #[ink(message)]
-        pub fn reset_for_new_race(&mut self) -> Result<()> {
-            if self.env().caller() != self.owner {
-                return Err(Error::NotOwner);
-            }
+            // Expected return is below the stake, so the price is value × 90%
+            let multiplier = contract.get_multiplier(0, 1) as u128;
+            let probability = contract.calculate_exacta_probability(0, 1) as u128;
+            let value = 1000 * multiplier * probability / PRECISION as u128;
+            let price = contract.get_cash_out_price(0).unwrap();
+            assert_eq!(price, value.min(1000) * 9 / 10);
 
-            self.bets.clear();
-            self.payouts.clear();
-            self.total_pot = 0;
-            self.status = RaceStatus::Betting;
-            self.betting_start_time = self.env().block_timestamp();
-            self.current_seed = 0;
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.cash_out(0), Err(Error::NotBetOwner));
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.cash_out(0), Ok(price));
+            assert_eq!(contract.cash_out(0), Err(Error::CashOutUnavailable));
+            assert_eq!(contract.get_balance(accounts.bob), price);
+            assert_eq!(contract.get_total_pot(), 1000);
+            assert_eq!(contract.get_bets_by_account(accounts.bob, 0, 1)[0].bet.cash_out, Some(price));
 
-            Ok(())
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.set_cash_out_factor(0).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.cash_out(1), Err(Error::CashOutUnavailable));
         }
 
-        /// Set contract owner
-        #[ink(message)]
-        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<()> {
-            if self.env().caller() != self.owner {
-                return Err(Error::NotOwner);
-            }
-            self.owner = new_owner;
-            Ok(())
+        #[ink::test]
+        fn owner_refunds_bet_with_reason_code() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            let bet_id = contract.place_exacta_bet(0, 1).unwrap();
+            contract.place_exacta_bet(2, 3).unwrap();
+            assert_eq!(contract.courtesy_refund(bet_id, 7), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.courtesy_refund(bet_id, 7), Ok(100));
+            assert_eq!(contract.courtesy_refund(bet_id, 7), Err(Error::BetAlreadyClosed));
+            assert_eq!(contract.courtesy_refund(5, 7), Err(Error::BetNotFound));
+            assert_eq!(contract.get_balance(accounts.bob), 100);
+            assert_eq!((contract.get_total_pot(), contract.get_combination_stake(0, 1).0), (100, 0));
+
+            let signature = BetRefunded::SIGNATURE_TOPIC.unwrap();
+            let reasons: Vec<RefundReason> = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == signature)
+                .map(|event| BetRefunded::decode(&mut &event.data[..]).unwrap().reason)
+                .collect();
+            assert_eq!(reasons, vec![RefundReason::Courtesy { bet_id, code: 7 }]);
+
+            // The refunded bet drops out of settlement
+            contract.simulate_complete_race(7).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert!(contract.get_payouts().iter().all(|payout| payout.exacta != (0, 1)));
+            assert_eq!(contract.courtesy_refund(bet_id, 7), Err(Error::RaceNotInBettingPhase));
         }
 
-        // ========================================================================
-        // SIMULATION HELPER - Run complete race cycle
-        // ========================================================================
+        #[ink::test]
+        fn in_running_bets_between_phases() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+            contract.set_durations(1_000, 3_000).unwrap();
+            ink::env::test::set_block_timestamp::<Env>(1_001);
+            contract.start_race(42).unwrap();
 
-        /// Run a complete race simulation (for testing)
-        #[ink(message)]
-        pub fn simulate_complete_race(&mut self, seed: u64) -> Result<RaceResult> {
-            // Start race
-            self.current_seed = seed;
-            self.race_id += 1;
-            self.status = RaceStatus::Racing;
-            self.race_start_time = self.env().block_timestamp();
+            // Closed until the first phase has run
+            ink::env::test::set_value_transferred::<Env>(100);
+            assert_eq!(contract.place_in_running_bet(0, 1), Err(Error::BettingClosed));
+            assert_eq!(contract.run_phase(), Err(Error::RaceStillRunning));
+            ink::env::test::set_block_timestamp::<Env>(2_001);
+            let standings = contract.run_phase().unwrap();
+            assert_eq!(standings.len(), 6);
+            assert_eq!(contract.get_race_phase(), 1);
+            let times = contract.get_running_times();
+            assert!(standings.windows(2).all(|pair| times[pair[0] as usize] <= times[pair[1] as usize]));
 
-            // Run simulation
-            self.run_race_simulation()
+            // Odds are locked on the bet and archived under the running race
+            let odds = contract.get_in_running_odds(0, 1);
+            assert!(odds > 0);
+            contract.place_in_running_bet(0, 1).unwrap();
+            assert_eq!(contract.get_bets()[0].in_running_odds, Some(odds));
+            assert_eq!(contract.get_bets_by_account(contract.owner, 0, 1)[0].race_id, 1);
+
+            ink::env::test::set_block_timestamp::<Env>(3_001);
+            contract.run_phase().unwrap();
+            assert_eq!(contract.run_phase(), Err(Error::FinalPhaseOnly));
+
+            // Closed once the race can be resolved
+            ink::env::test::set_block_timestamp::<Env>(4_001);
+            assert_eq!(contract.place_in_running_bet(0, 1), Err(Error::BettingClosed));
+            let result = contract.run_race_simulation().unwrap();
+            assert_eq!(contract.verify_result(result.race_id).map(|(ok, _)| ok), Some(true));
         }
-    }
 
-    // ============================================================================
-    // UNIT TESTS
-    // ============================================================================
+        #[ink::test]
+        fn unwon_pot_carries_over_or_refunds() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            // Find a seed where the reversed first race exacta and the
+            // second race exacta are both offered
+            let (mut losing, mut winning, mut seed) = ((0, 0), (0, 0), 0);
+            for candidate in 1..100 {
+                let mut scratch = HorseRace::new();
+                let first = scratch.simulate_complete_race(candidate).unwrap().winning_exacta;
+                scratch.reset_for_new_race().unwrap();
+                let second = scratch.simulate_complete_race(candidate).unwrap().winning_exacta;
+                if scratch.get_multiplier(first.1, first.0) > 0 && scratch.get_multiplier(second.0, second.1) > 0 {
+                    (losing, winning, seed) = ((first.1, first.0), second, candidate);
+                    break;
+                }
+            }
+            assert!(seed != 0);
+
+            let mut contract = HorseRace::new();
+            contract.set_unwon_pot_policy(UnwonPotPolicy::Carryover).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.set_unwon_pot_policy(UnwonPotPolicy::Refund), Err(Error::NotOwner));
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(losing.0, losing.1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(seed).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_carryover(), 100);
+            assert_eq!(contract.get_unwon_pot(1), Some(UnwonPot { race_id: 1, amount: 100, policy: UnwonPotPolicy::Carryover }));
+            let carried = ink::env::test::recorded_events()
+                .find(|event| event.topics[0] == PotCarriedOver::SIGNATURE_TOPIC.unwrap())
+                .map(|event| PotCarriedOver::decode(&mut &event.data[..]).unwrap())
+                .unwrap();
+            assert_eq!((carried.from_race, carried.to_race, carried.amount), (1, 2, 100));
+            assert_eq!(contract.get_race_pnl(1).unwrap().net, 0);
+
+            // The next race's winner collects the carried pot on top
+            contract.reset_for_new_race().unwrap();
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(winning.0, winning.1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(seed).unwrap();
+            contract.distribute_payouts().unwrap();
+            let multiplier = contract.get_multiplier(winning.0, winning.1) as u128;
+            assert_eq!(contract.get_balance(accounts.charlie), 50 * multiplier + 100);
+            assert_eq!(contract.get_carryover(), 0);
+
+            // Refunds return the losing stakes
+            contract.reset_for_new_race().unwrap();
+            contract.set_unwon_pot_policy(UnwonPotPolicy::Refund).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(100);
+            contract.place_exacta_bet(losing.0, losing.1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(seed).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 100);
+            assert_eq!(contract.get_unwon_pot(3).unwrap().policy, UnwonPotPolicy::Refund);
+            let refund = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == BetRefunded::SIGNATURE_TOPIC.unwrap())
+                .map(|event| BetRefunded::decode(&mut &event.data[..]).unwrap())
+                .last()
+                .unwrap();
+            assert_eq!((refund.bettor, refund.amount, refund.reason), (accounts.bob, 100, RefundReason::UnwonPot));
+        }
 
         #[ink::test]
-        fn initialization_works() {
-            let contract = HorseRace::new();
-            
-            // Check horses initialized
-            assert_eq!(contract.get_horses().len(), 6);
-            
-            // Check horse strengths
-            let horses = contract.get_horses();
-            assert_eq!(horses[0].strength, 6);
-            assert_eq!(horses[5].strength, 1);
-            
-            // Check status
-            assert_eq!(contract.get_status(), RaceStatus::Betting);
+        fn sponsors_boost_the_race_pot() {
+            use ink::env::Event;
+            use ink::scale::Decode;
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut seed = 0;
+            let mut winning = (0, 0);
+            for candidate in 1..100 {
+                let mut scratch = HorseRace::new();
+                let exacta = scratch.simulate_complete_race(candidate).unwrap().winning_exacta;
+                if scratch.get_multiplier(exacta.0, exacta.1) > 0 {
+                    (seed, winning) = (candidate, exacta);
+                    break;
+                }
+            }
+            assert!(seed != 0);
+
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            ink::env::test::set_value_transferred::<Env>(500);
+            contract.deposit().unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+            assert_eq!(contract.sponsor_race(2, 100), Err(Error::RaceNotInBettingPhase));
+            assert_eq!(contract.sponsor_race(1, 0), Err(Error::ZeroBetAmount));
+            assert_eq!(contract.sponsor_race(1, 600), Err(Error::InsufficientBalance));
+            contract.sponsor_race(1, 100).unwrap();
+            contract.sponsor_race(1, 50).unwrap();
+            assert_eq!(contract.get_balance(accounts.bob), 350);
+            assert_eq!(contract.get_sponsorships(1).len(), 1);
+            assert_eq!((contract.get_sponsored_amount(1), contract.get_carryover()), (150, 150));
+            let sponsored = ink::env::test::recorded_events()
+                .filter(|event| event.topics[0] == RaceSponsored::SIGNATURE_TOPIC.unwrap())
+                .map(|event| RaceSponsored::decode(&mut &event.data[..]).unwrap())
+                .last()
+                .unwrap();
+            assert_eq!((sponsored.race_id, sponsored.sponsor, sponsored.amount, sponsored.pot), (1, accounts.bob, 50, 150));
+
+            // The winner collects the sponsored pot on top of the payout
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(50);
+            contract.place_exacta_bet(winning.0, winning.1).unwrap();
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(seed).unwrap();
+            assert_eq!(contract.sponsor_race(1, 10), Err(Error::RaceNotInBettingPhase));
+            contract.distribute_payouts().unwrap();
+            let multiplier = contract.get_multiplier(winning.0, winning.1) as u128;
+            assert_eq!(contract.get_balance(accounts.charlie), 50 * multiplier + 150);
+            let report = contract.get_settlement_report(1).unwrap();
+            assert_eq!((report.sponsored, report.carryover_in, report.carryover_out), (150, 150, 0));
         }
 
         #[ink::test]
-        fn normalized_strength_works() {
-            let contract = HorseRace::new();
-            
-            // H[0] strength = 6, normalized = 6/21 * 10000 = 2857
-            let ns = contract.get_normalized_strength(0);
-            assert!(ns > 2850 && ns < 2860);
-            
-            // H[5] strength = 1, normalized = 1/21 * 10000 = 476
-            let ns = contract.get_normalized_strength(5);
-            assert!(ns > 470 && ns < 480);
+        fn split_floors_shares_and_collects_dust() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_value_transferred::<Env>(1);
+            for account in [accounts.bob, accounts.charlie, accounts.django] {
+                ink::env::test::set_caller::<Env>(account);
+                contract.place_exacta_bet(0, 1).unwrap();
+            }
+
+            let bets = contract.get_bets();
+            contract.split_by_stake(&bets, &[0, 1, 2], 10, LedgerReason::Payout).unwrap();
+            for account in [accounts.bob, accounts.charlie, accounts.django] {
+                assert_eq!(contract.get_balance(account), 3);
+            }
+            assert_eq!((contract.get_dust(), contract.get_treasury()), (1, 1));
         }
 
         #[ink::test]
-        fn exacta_probability_works() {
-            let contract = HorseRace::new();
-            
-            // P(0 → 1) should be highest
-            let p_01 = contract.calculate_exacta_probability(0, 1);
-            // P(5 → 4) should be lowest
-            let p_54 = contract.calculate_exacta_probability(5, 4);
-            
-            assert!(p_01 > p_54);
+        fn tournament_splits_entry_fees_by_chips() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            assert_eq!(contract.create_tournament(100, 0, 1), Err(Error::InvalidTournament));
+            let id = contract.create_tournament(100, 1_000, 1).unwrap();
+            for account in [accounts.bob, accounts.charlie, accounts.django] {
+                contract.credit_balance(account, 100, LedgerReason::Deposit).unwrap();
+                ink::env::test::set_caller::<Env>(account);
+                contract.join_tournament(id).unwrap();
+            }
+            assert_eq!(contract.join_tournament(id), Err(Error::AlreadyEntered));
+            assert_eq!(contract.get_tournament(id).unwrap().prize_pool, 300);
+
+            // Bob spreads chips over every exacta, Charlie backs one, Django sits out
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            for odds in contract.get_exacta_odds_table() {
+                contract.place_tournament_bet(id, odds.first, odds.second, 10).unwrap();
+            }
+            ink::env::test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(contract.place_tournament_bet(id, 0, 1, 1_001), Err(Error::InsufficientBalance));
+            contract.place_tournament_bet(id, 0, 1, 1_000).unwrap();
+            assert_eq!(contract.get_balance(accounts.charlie), 0);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            assert_eq!(contract.settle_tournament(id), Err(Error::TournamentNotFinished));
+            contract.simulate_complete_race(42).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert!(contract.get_tournament_bets().is_empty());
+
+            let winners = contract.settle_tournament(id).unwrap();
+            let prizes: Vec<Balance> = winners.iter().map(|&(_, prize)| prize).collect();
+            assert_eq!(prizes, vec![150, 90, 60]);
+            let tournament = contract.get_tournament(id).unwrap();
+            let chips = |account: AccountId| tournament.entrants.iter().find(|entry| entry.account == account).unwrap().chips;
+            assert!(chips(winners[0].0) >= chips(winners[1].0));
+            assert!(chips(winners[1].0) >= chips(winners[2].0));
+            assert_eq!(contract.get_balance(winners[0].0), 150);
+            assert_eq!(contract.settle_tournament(id), Err(Error::TournamentClosed));
         }
 
         #[ink::test]
-        fn multipliers_initialized() {
-            let contract = HorseRace::new();
-            
-            // Check specific multipliers
-            assert_eq!(contract.get_reward_multiplier(0, 5), 60);
-            assert_eq!(contract.get_reward_multiplier(4, 5), 1000);
-            assert_eq!(contract.get_reward_multiplier(5, 4), 1500);
+        fn predictions_score_points_without_stakes() {
+            type Env = ink::env::DefaultEnvironment;
+            let mut contract = HorseRace::new();
+
+            // One predictor per offered exacta so one of them hits
+            let table = contract.get_exacta_odds_table();
+            for (i, odds) in table.iter().enumerate() {
+                ink::env::test::set_caller::<Env>(AccountId::from([i as u8 + 1; 32]));
+                contract.predict_exacta(odds.first, odds.second).unwrap();
+            }
+            assert_eq!(contract.predict_exacta(table[0].first, table[0].second), Err(Error::AlreadyPredicted));
+            assert_eq!(contract.get_predictions().len(), table.len());
+            assert_eq!(contract.get_total_pot(), 0);
+
+            ink::env::test::set_caller::<Env>(ink::env::test::default_accounts::<Env>().alice);
+            let result = contract.simulate_complete_race(42).unwrap();
+            contract.distribute_payouts().unwrap();
+            assert!(contract.get_predictions().is_empty());
+
+            let (first, second) = result.winning_exacta;
+            let multiplier = contract.get_multiplier(first, second);
+            let hit = table.iter().position(|odds| (odds.first, odds.second) == (first, second)).unwrap();
+            let winner = AccountId::from([hit as u8 + 1; 32]);
+            let score = contract.get_prediction_score(1, winner).unwrap();
+            assert_eq!((score.points, score.predictions, score.hits), (multiplier, 1, 1));
+            let board = contract.get_prediction_leaderboard(1);
+            assert_eq!(board[0].account, winner);
+            assert!(board.iter().skip(1).all(|score| score.points == 0));
+            assert_eq!(contract.get_balance(winner), 0);
         }
 
         #[ink::test]
-        fn probability_table_works() {
-            let contract = HorseRace::new();
-            let table = contract.get_exacta_probability_table();
-            
-            // Should have entries for combinations with multipliers
-            assert!(!table.is_empty());
-            
-            // Each entry should have valid data
-            for entry in &table {
-                assert!(entry.first < 6);
-                assert!(entry.second < 6);
-                assert!(entry.first != entry.second);
-                assert!(entry.multiplier > 0);
+        fn demo_bets_use_play_money() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let mut contract = HorseRace::new();
+            ink::env::test::set_caller::<Env>(accounts.bob);
+            assert_eq!(contract.demo_place_exacta_bet(0, 1, 100), Err(Error::InsufficientBalance));
+            assert_eq!(contract.demo_faucet(), Ok(DEMO_FAUCET_AMOUNT));
+            assert_eq!(contract.demo_faucet(), Ok(0));
+
+            // Back every offered exacta so one of them wins
+            let mut staked = 0;
+            for odds in contract.get_exacta_odds_table() {
+                contract.demo_place_exacta_bet(odds.first, odds.second, 100).unwrap();
+                staked += 100;
             }
+            assert_eq!(contract.get_demo_balance(accounts.bob), DEMO_FAUCET_AMOUNT - staked);
+            assert!(contract.get_bets().is_empty());
+            assert_eq!(contract.get_total_pot(), 0);
+
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            let result = contract.simulate_complete_race(42).unwrap();
+            assert!(contract.distribute_payouts().unwrap().is_empty());
+            let (first, second) = result.winning_exacta;
+            let multiplier = contract.get_multiplier(first, second) as u128;
+            let returned: u128 = contract
+                .get_demo_bets()
+                .iter()
+                .filter(|bet| result.did_not_finish.contains(&bet.first_pick) || result.did_not_finish.contains(&bet.second_pick))
+                .map(|bet| bet.amount)
+                .sum();
+            let won = if multiplier > 0 { 100 * multiplier } else { 0 };
+            assert_eq!(contract.get_demo_balance(accounts.bob), DEMO_FAUCET_AMOUNT - staked + returned + won);
+            assert_eq!(contract.get_balance(accounts.bob), 0);
         }
 
         #[ink::test]
-        fn race_simulation_deterministic() {
+        fn settlement_never_credits_twice() {
+            type Env = ink::env::DefaultEnvironment;
+            let accounts = ink::env::test::default_accounts::<Env>();
+            let (mut winning, mut seed) = ((0, 0), 0);
+            for candidate in 1..100 {
+                let mut scratch = HorseRace::new();
+                let exacta = scratch.simulate_complete_race(candidate).unwrap().winning_exacta;
+                if scratch.get_multiplier(exacta.0, exacta.1) > 0 {
+                    (winning, seed) = (exacta, candidate);
+                    break;
+                }
+            }
+            assert!(seed != 0);
+
             let mut contract = HorseRace::new();
-            
-            // Run with same seed twice
-            let result1 = contract.simulate_complete_race(12345).unwrap();
+            ink::env::test::set_value_transferred::<Env>(100);
+            for account in [accounts.bob, accounts.charlie] {
+                ink::env::test::set_caller::<Env>(account);
+                contract.place_exacta_bet(winning.0, winning.1).unwrap();
+            }
+            ink::env::test::set_caller::<Env>(accounts.alice);
+            contract.simulate_complete_race(seed).unwrap();
+            let payout = 100 * contract.get_multiplier(winning.0, winning.1) as u128;
+
+            // Restart after a run that paid Bob and stopped
+            contract.bets[0].settled = true;
+            contract.credit_winnings(accounts.bob, payout, LedgerReason::Payout).unwrap();
+            let payouts = contract.distribute_payouts().unwrap();
+            assert_eq!(payouts.len(), 1);
+            assert_eq!(contract.get_balance(accounts.bob), payout);
+            assert_eq!(contract.get_balance(accounts.charlie), payout);
+
+            // Re-entry is refused, also once the next race opens
+            assert_eq!(contract.distribute_payouts(), Err(Error::AlreadySettled));
             contract.reset_for_new_race().unwrap();
-            let result2 = contract.simulate_complete_race(12345).unwrap();
-            
-            // Results should be identical
-            assert_eq!(result1.rankings, result2.rankings);
-            assert_eq!(result1.winning_exacta, result2.winning_exacta);
+            assert_eq!(contract.distribute_payouts(), Err(Error::AlreadySettled));
+            assert_eq!(contract.get_balance(accounts.charlie), payout);
+        }
+
+        #[ink::test]
+        fn dnf_bets_are_refunded() {
+            // Find a seed that produces a DNF
+            let mut scratch = HorseRace::new();
+            let mut seed = 0;
+            let mut dnf_horse = 0;
+            for candidate in 1..1000 {
+                let result = scratch.simulate_complete_race(candidate).unwrap();
+                scratch.reset_for_new_race().unwrap();
+                if let Some(&horse) = result.did_not_finish.first() {
+                    assert!(!result.rankings.contains(&horse));
+                    assert_eq!(result.rankings.len() + result.did_not_finish.len(), 6);
+                    seed = candidate;
+                    dnf_horse = horse;
+                    break;
+                }
+            }
+            assert!(seed != 0);
+
+            let mut contract = HorseRace::new();
+            let other = (dnf_horse + 1) % 6;
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            contract.place_exacta_bet(dnf_horse, other).unwrap();
+
+            contract.simulate_complete_race(seed).unwrap();
+            let payouts = contract.distribute_payouts().unwrap();
+
+            assert!(payouts.is_empty());
+            let refunds = contract.get_refunds();
+            assert_eq!(refunds.len(), 1);
+            assert_eq!(refunds[0].amount, 100);
+            assert_eq!(refunds[0].exacta, (dnf_horse, other));
         }
     }
 